@@ -0,0 +1,94 @@
+#ifndef DUSTMASKER_H
+#define DUSTMASKER_H
+
+/* Generated with cbindgen from src/capi.rs, do not edit by hand */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Outcome of the functions of the C API
+ */
+typedef enum DustStatus {
+  /**
+   * The call succeeded
+   */
+  DustOk = 0,
+  /**
+   * A required pointer was null
+   */
+  DustNullPointer = 1,
+  /**
+   * The sequence is shorter than 4 bases
+   */
+  DustSequenceLengthError = 2,
+  /**
+   * The window size is smaller than 3
+   */
+  DustWindowSizeError = 3,
+} DustStatus;
+
+/**
+ * Low-complexity regions of a sequence, allocated by `dust_intervals` and
+ * released with `dust_intervals_free`. `positions` holds `len` pairs of start
+ * and end positions, so it is `2 * len` elements long.
+ */
+typedef struct DustIntervals {
+  uintptr_t *positions;
+  uintptr_t len;
+} DustIntervals;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Identify the low-complexity regions in the `length` bases of `sequence`,
+ * storing them in `out`. The positions must be released with
+ * `dust_intervals_free`. `threads` is the number of threads used to process
+ * long sequences, 0 meaning one per available core.
+ *
+ * # Safety
+ *
+ * `sequence` must point to `length` readable bytes and `out` must be a valid
+ * pointer to a `DustIntervals`.
+ */
+enum DustStatus dust_intervals(const uint8_t *sequence,
+                               uintptr_t length,
+                               uintptr_t window_size,
+                               uintptr_t score_threshold,
+                               uintptr_t threads,
+                               struct DustIntervals *out);
+
+/**
+ * Release the positions allocated by `dust_intervals`, leaving `intervals`
+ * empty. Releasing empty intervals does nothing.
+ *
+ * # Safety
+ *
+ * `intervals` must be null or point to intervals filled by `dust_intervals`.
+ */
+void dust_intervals_free(struct DustIntervals *intervals);
+
+/**
+ * Mask the low-complexity regions in the `length` bases of `sequence` in
+ * place, converting them to lowercase or, if `hard` is true, replacing them
+ * with 'N'.
+ *
+ * # Safety
+ *
+ * `sequence` must point to `length` writable bytes.
+ */
+enum DustStatus dust_mask(uint8_t *sequence,
+                          uintptr_t length,
+                          uintptr_t window_size,
+                          uintptr_t score_threshold,
+                          uintptr_t threads,
+                          bool hard);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* DUSTMASKER_H */
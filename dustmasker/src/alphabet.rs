@@ -0,0 +1,107 @@
+//! Alphabets mapping the characters of a sequence to the codes compared by the
+//! algorithm
+
+use crate::sdust::AMBIGUOUS;
+use crate::InputError;
+
+/// Maximum number of symbols of an alphabet, so that the triplets of codes fit
+/// in a byte
+pub const MAX_ALPHABET_SIZE: usize = 6;
+
+/// The symbols compared by the algorithm and the characters that stand for each
+/// one of them. Characters outside the alphabet are ambiguous, and break the
+/// triplets like an N does in a nucleotide sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    /// Code of each character, `AMBIGUOUS` for the ones outside the alphabet
+    codes: [u8; 256],
+    size: usize,
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::dna()
+    }
+}
+
+impl Alphabet {
+    /// The nucleotides A, C, G and T, in either case, used by default
+    pub fn dna() -> Self {
+        Alphabet::new(&[b"Aa", b"Cc", b"Gg", b"Tt"]).unwrap()
+    }
+
+    /// The nucleotides of bisulfite-converted (or EM-seq) reads of the original
+    /// strands, where unmethylated Cs are read as Ts, so C and T are the same
+    /// symbol whatever the methylation state. The score threshold should be
+    /// scaled with [`bisulfite_score_threshold`].
+    pub fn bisulfite_ct() -> Self {
+        Alphabet::new(&[b"Aa", b"CcTt", b"Gg"]).unwrap()
+    }
+
+    /// The nucleotides of bisulfite-converted reads of the complementary
+    /// strands, where the conversion reads Gs as As, so G and A are the same
+    /// symbol. The score threshold should be scaled with
+    /// [`bisulfite_score_threshold`].
+    pub fn bisulfite_ga() -> Self {
+        Alphabet::new(&[b"AaGg", b"Cc", b"Tt"]).unwrap()
+    }
+
+    /// An alphabet with one symbol per element of `symbols`, each one standing
+    /// for the characters it contains. For example, a methylation-aware
+    /// alphabet can be defined as `[b"Aa", b"Cc", b"Gg", b"Tt", b"Mm"]`, or an
+    /// ambiguity code can be counted as one of the nucleotides.
+    pub fn new(symbols: &[&[u8]]) -> Result<Self, InputError> {
+        if symbols.is_empty() || symbols.len() > MAX_ALPHABET_SIZE {
+            return Err(InputError::AlphabetSizeError(symbols.len()));
+        }
+        let mut codes = [AMBIGUOUS; 256];
+        for (code, characters) in symbols.iter().enumerate() {
+            for &character in characters.iter() {
+                if codes[character as usize] != AMBIGUOUS {
+                    return Err(InputError::AlphabetCharacterError(character as char));
+                }
+                codes[character as usize] = code as u8;
+            }
+        }
+        Ok(Alphabet {
+            codes,
+            size: symbols.len(),
+        })
+    }
+
+    /// Number of symbols of the alphabet
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether `character` stands for a symbol of the alphabet
+    pub fn contains(&self, character: u8) -> bool {
+        self.codes[character as usize] != AMBIGUOUS
+    }
+
+    /// Encode `sequence` into `codes`, mapping the characters outside the
+    /// alphabet to `AMBIGUOUS`
+    #[inline]
+    pub(crate) fn encode(&self, sequence: &[u8], codes: &mut [u8]) {
+        for (code, &character) in codes.iter_mut().zip(sequence) {
+            *code = self.codes[character as usize];
+        }
+    }
+}
+
+/// The score threshold equivalent to `score_threshold` for bisulfite-converted
+/// reads scored with [`Alphabet::bisulfite_ct`] or [`Alphabet::bisulfite_ga`].
+///
+/// The merged symbol accounts for about half of the bases of converted reads,
+/// so two random triplets are identical with probability (3/8)³ instead of
+/// (1/4)³, and random windows score 27/8 times higher than in unconverted
+/// sequences. Without scaling, large parts of converted reads are masked.
+///
+/// ```
+/// use dustmasker::alphabet::bisulfite_score_threshold;
+///
+/// assert_eq!(bisulfite_score_threshold(20), 68);
+/// ```
+pub fn bisulfite_score_threshold(score_threshold: usize) -> usize {
+    (score_threshold * 27 + 4) / 8
+}
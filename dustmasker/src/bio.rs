@@ -0,0 +1,36 @@
+//! Interoperability with [rust-bio](https://docs.rs/bio), enabled with the
+//! `bio` feature. Sequences given as `bio::utils::TextSlice` can be passed to
+//! every function of the library, and the regions can be converted to rust-bio
+//! intervals and interval trees.
+
+use crate::masker::Masker;
+use ::bio::data_structures::interval_tree::ArrayBackedIntervalTree;
+use ::bio::utils::{Interval, TextSlice};
+
+/// The low-complexity regions in `text` identified by `masker`, as rust-bio
+/// intervals
+pub fn intervals(masker: &dyn Masker, text: TextSlice<'_>) -> Vec<Interval<usize>> {
+    let mut intervals = Vec::new();
+    masker.process_with(text, &mut |start, end| {
+        // Regions starting past the end of the sequence are clipped to an
+        // empty range, which rust-bio doesn't accept
+        if let Ok(interval) = Interval::new(start..end) {
+            intervals.push(interval)
+        }
+    });
+    intervals
+}
+
+/// The low-complexity regions in `text` identified by `masker`, indexed in an
+/// interval tree to find the ones overlapping given ranges
+pub fn interval_tree(
+    masker: &dyn Masker,
+    text: TextSlice<'_>,
+) -> ArrayBackedIntervalTree<usize, ()> {
+    let mut tree: ArrayBackedIntervalTree<usize, ()> = intervals(masker, text)
+        .into_iter()
+        .map(|interval| (interval, ()))
+        .collect();
+    tree.index();
+    tree
+}
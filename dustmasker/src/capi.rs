@@ -0,0 +1,238 @@
+//! C ABI of the library, enabled with the `capi` feature, so that tools written
+//! in other languages can bind to the same implementation. The header is
+//! generated with `cbindgen --config cbindgen.toml --output include/dustmasker.h`.
+
+use crate::masker::{Masker, SymmetricDustMasker};
+use crate::{validate_inputs, InputError};
+use std::ptr;
+use std::slice;
+
+/// Outcome of the functions of the C API
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DustStatus {
+    /// The call succeeded
+    DustOk = 0,
+    /// A required pointer was null
+    DustNullPointer = 1,
+    /// The sequence is shorter than 4 bases
+    DustSequenceLengthError = 2,
+    /// The window size is smaller than 3
+    DustWindowSizeError = 3,
+}
+
+/// Low-complexity regions of a sequence, allocated by `dust_intervals` and
+/// released with `dust_intervals_free`. `positions` holds `len` pairs of start
+/// and end positions, so it is `2 * len` elements long.
+#[repr(C)]
+#[derive(Debug)]
+pub struct DustIntervals {
+    pub positions: *mut usize,
+    pub len: usize,
+}
+
+/// Check the arguments shared by the functions of the C API, returning the
+/// sequence as a slice
+unsafe fn check_inputs<'a>(
+    sequence: *const u8,
+    length: usize,
+    window_size: usize,
+) -> Result<&'a [u8], DustStatus> {
+    if sequence.is_null() {
+        return Err(DustStatus::DustNullPointer);
+    }
+    let sequence = slice::from_raw_parts(sequence, length);
+    match validate_inputs(sequence, window_size) {
+        Ok(()) => Ok(sequence),
+        Err(InputError::WindowSizeError(_)) => Err(DustStatus::DustWindowSizeError),
+        Err(_) => Err(DustStatus::DustSequenceLengthError),
+    }
+}
+
+/// Identify the low-complexity regions in the `length` bases of `sequence`,
+/// storing them in `out`. The positions must be released with
+/// `dust_intervals_free`. `threads` is the number of threads used to process
+/// long sequences, 0 meaning one per available core.
+///
+/// # Safety
+///
+/// `sequence` must point to `length` readable bytes and `out` must be a valid
+/// pointer to a `DustIntervals`.
+#[no_mangle]
+pub unsafe extern "C" fn dust_intervals(
+    sequence: *const u8,
+    length: usize,
+    window_size: usize,
+    score_threshold: usize,
+    threads: usize,
+    out: *mut DustIntervals,
+) -> DustStatus {
+    if out.is_null() {
+        return DustStatus::DustNullPointer;
+    }
+    let sequence = match check_inputs(sequence, length, window_size) {
+        Ok(sequence) => sequence,
+        Err(status) => return status,
+    };
+    let masker = SymmetricDustMasker {
+        window_size,
+        score_threshold,
+        threads,
+    };
+    let mut positions = Vec::new();
+    masker.process_with(sequence, &mut |start, end| {
+        positions.push(start);
+        positions.push(end);
+    });
+    let positions = positions.into_boxed_slice();
+    let len = positions.len() / 2;
+    *out = DustIntervals {
+        positions: Box::into_raw(positions) as *mut usize,
+        len,
+    };
+    DustStatus::DustOk
+}
+
+/// Release the positions allocated by `dust_intervals`, leaving `intervals`
+/// empty. Releasing empty intervals does nothing.
+///
+/// # Safety
+///
+/// `intervals` must be null or point to intervals filled by `dust_intervals`.
+#[no_mangle]
+pub unsafe extern "C" fn dust_intervals_free(intervals: *mut DustIntervals) {
+    let Some(intervals) = intervals.as_mut() else {
+        return;
+    };
+    if !intervals.positions.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            intervals.positions,
+            2 * intervals.len,
+        )));
+    }
+    intervals.positions = ptr::null_mut();
+    intervals.len = 0;
+}
+
+/// Mask the low-complexity regions in the `length` bases of `sequence` in
+/// place, converting them to lowercase or, if `hard` is true, replacing them
+/// with 'N'.
+///
+/// # Safety
+///
+/// `sequence` must point to `length` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn dust_mask(
+    sequence: *mut u8,
+    length: usize,
+    window_size: usize,
+    score_threshold: usize,
+    threads: usize,
+    hard: bool,
+) -> DustStatus {
+    if let Err(status) = check_inputs(sequence, length, window_size) {
+        return status;
+    }
+    let masker = SymmetricDustMasker {
+        window_size,
+        score_threshold,
+        threads,
+    };
+    // The intervals are collected first, as the sequence can't be modified
+    // while it is scanned
+    let intervals = masker.intervals(slice::from_raw_parts(sequence, length));
+    let sequence = slice::from_raw_parts_mut(sequence, length);
+    for (start, end) in intervals {
+        let region = &mut sequence[start..end];
+        if hard {
+            region.fill(b'N');
+        } else {
+            region.make_ascii_lowercase();
+        }
+    }
+    DustStatus::DustOk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_sequence, serial_intervals, Rng};
+
+    #[test]
+    fn intervals_round_trip() {
+        let mut rng = Rng::new(11);
+        let sequence = random_sequence(&mut rng, 200_000);
+        let expected = serial_intervals(&sequence, 64, 20);
+        for threads in [1, 4] {
+            let mut intervals = DustIntervals {
+                positions: ptr::null_mut(),
+                len: 0,
+            };
+            let status = unsafe {
+                dust_intervals(
+                    sequence.as_ptr(),
+                    sequence.len(),
+                    64,
+                    20,
+                    threads,
+                    &mut intervals,
+                )
+            };
+            assert_eq!(status, DustStatus::DustOk);
+            let positions =
+                unsafe { slice::from_raw_parts(intervals.positions, 2 * intervals.len) };
+            let pairs: Vec<(usize, usize)> =
+                positions.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+            assert_eq!(pairs, expected);
+            unsafe { dust_intervals_free(&mut intervals) };
+            assert!(intervals.positions.is_null());
+            assert_eq!(intervals.len, 0);
+            // Releasing the intervals again does nothing
+            unsafe { dust_intervals_free(&mut intervals) };
+        }
+    }
+
+    #[test]
+    fn invalid_inputs_are_reported() {
+        let mut intervals = DustIntervals {
+            positions: ptr::null_mut(),
+            len: 0,
+        };
+        let sequence = b"ACGTACGT";
+        let call = |sequence: *const u8, length, window_size, out| unsafe {
+            dust_intervals(sequence, length, window_size, 20, 1, out)
+        };
+        assert_eq!(
+            call(ptr::null(), 8, 64, &mut intervals),
+            DustStatus::DustNullPointer
+        );
+        assert_eq!(
+            call(sequence.as_ptr(), 8, 64, ptr::null_mut()),
+            DustStatus::DustNullPointer
+        );
+        assert_eq!(
+            call(sequence.as_ptr(), 3, 64, &mut intervals),
+            DustStatus::DustSequenceLengthError
+        );
+        assert_eq!(
+            call(sequence.as_ptr(), 8, 2, &mut intervals),
+            DustStatus::DustWindowSizeError
+        );
+        assert!(intervals.positions.is_null());
+        unsafe { dust_intervals_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn mask_in_place() {
+        let sequence = b"CGTATATATATAGTATGCGTACTGGGGGGGCT";
+        for (hard, expected) in [
+            (false, b"CGtatatatataGTATGCGTACTgggggggCT"),
+            (true, b"CGNNNNNNNNNNGTATGCGTACTNNNNNNNCT"),
+        ] {
+            let mut masked = *sequence;
+            let status = unsafe { dust_mask(masked.as_mut_ptr(), masked.len(), 64, 10, 1, hard) };
+            assert_eq!(status, DustStatus::DustOk);
+            assert_eq!(&masked, expected);
+        }
+    }
+}
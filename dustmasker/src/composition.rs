@@ -0,0 +1,137 @@
+//! Base composition of sequences, which can be gathered while they are scanned
+
+use crate::sdust::Interval;
+use alloc::vec::Vec;
+
+/// Counts of the bases of a sequence, which can be updated with successive
+/// chunks of it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Composition {
+    /// Number of characters of the sequence
+    pub length: usize,
+    /// Number of G and C bases, in either case
+    pub gc_count: usize,
+    /// Number of A and T bases, in either case
+    pub at_count: usize,
+    /// Number of N characters, in either case
+    pub n_count: usize,
+    /// Number of runs of consecutive N characters
+    pub n_runs: usize,
+    /// Length of the longest run of N characters
+    pub longest_n_run: usize,
+    /// Length of the run of N characters at the end of the bases counted so far
+    current_n_run: usize,
+}
+
+impl Composition {
+    /// The composition of `sequence`
+    pub fn of(sequence: &[u8]) -> Self {
+        let mut composition = Composition::default();
+        composition.update(sequence);
+        composition
+    }
+
+    /// Count the bases of `chunk`, which follows the bases counted so far
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.length += chunk.len();
+        for &base in chunk {
+            let lower = base | 0x20;
+            self.gc_count += ((lower == b'g') | (lower == b'c')) as usize;
+            self.at_count += ((lower == b'a') | (lower == b't')) as usize;
+            if lower == b'n' {
+                if self.current_n_run == 0 {
+                    self.n_runs += 1;
+                }
+                self.n_count += 1;
+                self.current_n_run += 1;
+                self.longest_n_run = self.longest_n_run.max(self.current_n_run);
+            } else {
+                self.current_n_run = 0;
+            }
+        }
+    }
+
+    /// The fraction of G and C bases among the A, C, G and T bases, or 0 if
+    /// there are none
+    pub fn gc_content(&self) -> f64 {
+        match self.gc_count + self.at_count {
+            0 => 0.0,
+            n_nucleotides => self.gc_count as f64 / n_nucleotides as f64,
+        }
+    }
+}
+
+/// The runs of consecutive ambiguous characters of a sequence (see
+/// `ambiguous_runs`), which can be updated with successive chunks of it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmbiguousRuns {
+    runs: Vec<Interval>,
+    /// Number of characters read so far
+    length: usize,
+}
+
+impl AmbiguousRuns {
+    /// Find the runs of `chunk`, which follows the characters read so far, and
+    /// extend the last run if it reaches the end of the previous chunk
+    pub fn update(&mut self, chunk: &[u8]) {
+        for (offset, &base) in chunk.iter().enumerate() {
+            if !base.is_ascii() || matches!(base | 0x20, b'a' | b'c' | b'g' | b't') {
+                continue;
+            }
+            let position = self.length + offset;
+            match self.runs.last_mut() {
+                Some(last) if last.1 == position => last.1 += 1,
+                _ => self.runs.push((position, position + 1)),
+            }
+        }
+        self.length += chunk.len();
+    }
+
+    /// The runs found so far, in order
+    pub fn into_runs(self) -> Vec<Interval> {
+        self.runs
+    }
+
+    /// Number of bytes allocated on the heap to store the runs
+    pub fn allocated_bytes(&self) -> usize {
+        self.runs.capacity() * core::mem::size_of::<Interval>()
+    }
+}
+
+/// The runs of consecutive ambiguous characters of `sequence`: the ASCII
+/// characters other than A, C, G and T, in either case, such as N and the
+/// other IUPAC codes, which reset the scan of the algorithm. Non-ASCII
+/// characters also reset it, but are never part of a run.
+///
+/// ```
+/// use dustmasker::composition::ambiguous_runs;
+///
+/// assert_eq!(ambiguous_runs(b"NNACGTRYacgn"), [(0, 2), (6, 8), (11, 12)]);
+/// ```
+pub fn ambiguous_runs(sequence: &[u8]) -> Vec<Interval> {
+    let mut runs = AmbiguousRuns::default();
+    runs.update(sequence);
+    runs.into_runs()
+}
+
+/// What is gathered about a sequence while its low-complexity regions are
+/// identified
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanDetails {
+    /// The runs of consecutive ambiguous characters (see `ambiguous_runs`)
+    pub ambiguous_runs: Vec<Interval>,
+    /// The composition of the sequence, if it was requested
+    pub composition: Option<Composition>,
+}
+
+impl ScanDetails {
+    /// The details of `sequence`, read in a pass of its own, with its
+    /// composition if `composition` is true
+    pub fn of(sequence: &[u8], composition: bool) -> Self {
+        ScanDetails {
+            ambiguous_runs: ambiguous_runs(sequence),
+            composition: composition.then(|| Composition::of(sequence)),
+        }
+    }
+}
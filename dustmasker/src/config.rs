@@ -0,0 +1,172 @@
+//! Validated parameters of the symmetric DUST algorithm
+
+use crate::masker::SymmetricDustMasker;
+use crate::{InputError, MIN_SEQUENCE_LENGTH, MIN_WINDOW_SIZE};
+
+/// Parameters of the symmetric DUST algorithm, which are checked when they
+/// are built with a [`DustConfigBuilder`]:
+///
+/// ```
+/// use dustmasker::config::DustConfig;
+///
+/// let config = DustConfig::builder()
+///     .window_size(64)
+///     .score_threshold(20)
+///     .build()
+///     .unwrap();
+/// assert!(DustConfig::builder().window_size(2).build().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DustConfig {
+    window_size: usize,
+    score_threshold: usize,
+    threads: usize,
+    spill_threshold: Option<usize>,
+}
+
+impl Default for DustConfig {
+    /// The default parameters of `DustMasker`
+    fn default() -> Self {
+        DustConfig {
+            window_size: 64,
+            score_threshold: 20,
+            threads: 1,
+            spill_threshold: None,
+        }
+    }
+}
+
+impl DustConfig {
+    /// A builder starting from the default parameters
+    pub fn builder() -> DustConfigBuilder {
+        DustConfigBuilder {
+            config: DustConfig::default(),
+            level: None,
+        }
+    }
+
+    /// The length of the window, at least 3
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The score threshold for subwindows
+    pub fn score_threshold(&self) -> usize {
+        self.score_threshold
+    }
+
+    /// The score threshold as a mean score per triplet, a tenth of
+    /// `score_threshold` (see [`score_threshold_from_level`])
+    pub fn level(&self) -> f64 {
+        self.score_threshold as f64 / 10.0
+    }
+
+    /// Number of threads used to process long sequences, 0 meaning one per
+    /// available core
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Number of intervals above which they are spilled to a temporary file,
+    /// if any (see `spill`)
+    pub fn spill_threshold(&self) -> Option<usize> {
+        self.spill_threshold
+    }
+
+    /// Check that `sequence` is long enough to be processed
+    pub fn check_sequence(&self, sequence: &[u8]) -> Result<(), InputError> {
+        if sequence.len() < MIN_SEQUENCE_LENGTH {
+            return Err(InputError::SequenceLengthError(sequence.len()));
+        }
+        Ok(())
+    }
+
+    /// The masker that applies these parameters
+    pub fn masker(&self) -> SymmetricDustMasker {
+        SymmetricDustMasker {
+            window_size: self.window_size,
+            score_threshold: self.score_threshold,
+            threads: self.threads,
+        }
+    }
+}
+
+/// Builder of a [`DustConfig`], which checks the parameters in `build`
+#[derive(Debug, Clone)]
+pub struct DustConfigBuilder {
+    config: DustConfig,
+    /// The score threshold given as a level, converted when it is built
+    level: Option<f64>,
+}
+
+impl DustConfigBuilder {
+    pub fn window_size(mut self, window_size: usize) -> Self {
+        self.config.window_size = window_size;
+        self
+    }
+
+    pub fn score_threshold(mut self, score_threshold: usize) -> Self {
+        self.config.score_threshold = score_threshold;
+        self
+    }
+
+    /// Set the score threshold from a mean score per triplet, replacing the
+    /// `score_threshold` (see [`score_threshold_from_level`])
+    pub fn level(mut self, level: f64) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        self
+    }
+
+    pub fn spill_threshold(mut self, spill_threshold: Option<usize>) -> Self {
+        self.config.spill_threshold = spill_threshold;
+        self
+    }
+
+    /// The parameters, if they are valid
+    pub fn build(mut self) -> Result<DustConfig, InputError> {
+        if self.config.window_size < MIN_WINDOW_SIZE {
+            return Err(InputError::WindowSizeError(self.config.window_size));
+        }
+        if let Some(level) = self.level {
+            self.config.score_threshold = score_threshold_from_level(level)?;
+        }
+        Ok(self.config)
+    }
+}
+
+/// The `score_threshold` equivalent to `level`, the mean score per triplet
+/// above which a region is masked.
+///
+/// The score threshold is an integer holding 10 times the level, which is the
+/// value taken by the `-level` option of NCBI dustmasker: its default of 20
+/// stands for a level of 2.0. As the scores of the regions are compared with
+/// the score threshold exactly, the level must be a non-negative multiple of
+/// 0.1, up to floating point rounding.
+///
+/// ```
+/// use dustmasker::config::score_threshold_from_level;
+///
+/// assert_eq!(score_threshold_from_level(2.0).unwrap(), 20);
+/// assert_eq!(score_threshold_from_level(2.3).unwrap(), 23);
+/// assert!(score_threshold_from_level(2.05).is_err());
+/// ```
+pub fn score_threshold_from_level(level: f64) -> Result<usize, InputError> {
+    let scaled = level * 10.0;
+    if !(0.0..usize::MAX as f64).contains(&scaled) {
+        return Err(InputError::LevelError(level));
+    }
+    let score_threshold = (scaled + 0.5) as usize;
+    let difference = scaled - score_threshold as f64;
+    // Levels like 2.3 aren't exactly representable, so their product by 10
+    // is only close to an integer
+    let tolerance = 1e-9 * scaled.max(1.0);
+    if difference > tolerance || difference < -tolerance {
+        return Err(InputError::LevelError(level));
+    }
+    Ok(score_threshold)
+}
@@ -0,0 +1,126 @@
+//! Counting of masked bases without storing the low-complexity regions
+
+#[cfg(feature = "std")]
+use crate::parallel;
+use crate::sdust::ENCODING_BLOCK_SIZE;
+use crate::stream::StreamingDust;
+#[cfg(feature = "std")]
+use alloc::{vec, vec::Vec};
+
+/// Count the bases of `sequence` within low-complexity regions, without storing
+/// the regions
+#[cfg(feature = "std")]
+pub fn count_masked_bases(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+    n_threads: usize,
+) -> usize {
+    let mut n_masked_bases = 0;
+    parallel::process_with(
+        sequence,
+        window_size,
+        score_threshold,
+        n_threads,
+        // Intervals starting past the end of the sequence are empty
+        |start, end| n_masked_bases += end.saturating_sub(start),
+    );
+    n_masked_bases
+}
+
+/// Count the bases within low-complexity regions in each consecutive bin of
+/// `bin_size` bases of `sequence` (the last bin can be shorter), without
+/// storing the regions
+#[cfg(feature = "std")]
+pub fn bin_masked_bases(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+    n_threads: usize,
+    bin_size: usize,
+) -> Vec<usize> {
+    let mut bins = vec![0; sequence.len().div_ceil(bin_size)];
+    parallel::process_with(
+        sequence,
+        window_size,
+        score_threshold,
+        n_threads,
+        |start, end| {
+            // Intervals starting past the end of the sequence are empty
+            let mut position = start;
+            while position < end {
+                let bin_end = core::cmp::min((position / bin_size + 1) * bin_size, end);
+                bins[position / bin_size] += bin_end - position;
+                position = bin_end;
+            }
+        },
+    );
+    bins
+}
+
+/// Whether more than `max_masked_bases` bases of `sequence` are within
+/// low-complexity regions. The scan stops as soon as the intervals that were
+/// found guarantee it, so sequences that are mostly masked are only partially
+/// processed.
+pub fn exceeds_masked_bases(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+    max_masked_bases: usize,
+) -> bool {
+    let mut stream = StreamingDust::new(window_size, score_threshold);
+    let mut n_masked_bases = 0;
+    for chunk in sequence.chunks(ENCODING_BLOCK_SIZE) {
+        stream.feed(chunk, |start, end| n_masked_bases += end - start);
+        if n_masked_bases > max_masked_bases {
+            return true;
+        }
+    }
+    stream.finish(|start, end| n_masked_bases += end.saturating_sub(start));
+    n_masked_bases > max_masked_bases
+}
+
+/// The score threshold with which the fraction of masked bases of `sequence`
+/// is the closest to `masked_fraction`, the higher one among ties.
+///
+/// The number of masked bases never increases with the threshold, and no base
+/// is masked with a threshold of `5 * (window_size - 2)`, so the threshold is
+/// found with a binary search that counts the masked bases of a few thresholds.
+#[cfg(feature = "std")]
+pub fn calibrate_score_threshold(
+    sequence: &[u8],
+    window_size: usize,
+    masked_fraction: f64,
+    n_threads: usize,
+) -> usize {
+    let count =
+        |score_threshold| count_masked_bases(sequence, window_size, score_threshold, n_threads);
+    let target = masked_fraction * sequence.len() as f64;
+    // The lowest threshold that masks at most the target lies in `low..=high`
+    let (mut low, mut high) = (0, 5 * window_size.saturating_sub(2));
+    let mut high_count = 0;
+    // The count of the highest threshold known to mask more than the target
+    let mut above = None;
+    while low < high {
+        let middle = (low + high) / 2;
+        let n_masked_bases = count(middle);
+        if n_masked_bases as f64 <= target {
+            (high, high_count) = (middle, n_masked_bases);
+        } else {
+            low = middle + 1;
+            above = Some((middle, n_masked_bases));
+        }
+    }
+    if high == 0 {
+        return 0;
+    }
+    let previous_count = match above {
+        Some((score_threshold, n_masked_bases)) if score_threshold == high - 1 => n_masked_bases,
+        _ => count(high - 1),
+    };
+    if (previous_count as f64 - target).abs() < (target - high_count as f64).abs() {
+        high - 1
+    } else {
+        high
+    }
+}
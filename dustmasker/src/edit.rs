@@ -0,0 +1,157 @@
+//! Update of the low-complexity regions of a sequence after a local edit
+
+use crate::intervals::{self, Intervals};
+use crate::parallel::merge_intervals;
+use crate::sdust::{clip_interval, is_nucleotide, Processor};
+use crate::spill::SpillingIntervals;
+use std::io;
+use std::ops::Range;
+
+/// Compute the intervals of `sequence`, which was obtained by replacing the
+/// bases within `edit` of a previous sequence by `replacement_length` bases,
+/// given the `previous` intervals of that sequence.
+///
+/// Like in a parallel run (see `parallel::process_in_chunks`), the scan state
+/// at a position only depends on the preceding `window_size` bases if they are
+/// unambiguous. Only the region between two such positions that surround the
+/// edit is scanned, and the previous intervals are reused on both sides of it.
+/// The region boundaries are also placed outside of the previous intervals,
+/// so that the intervals that were merged across them can be split apart.
+pub fn patch(
+    sequence: &[u8],
+    previous: &Intervals,
+    edit: Range<usize>,
+    replacement_length: usize,
+    window_size: usize,
+    score_threshold: usize,
+    spill_threshold: Option<usize>,
+) -> io::Result<Intervals> {
+    let overlap = 2 * window_size;
+    let edit_end = edit.start + replacement_length;
+    // Previous positions after the edit are shifted by the change in length
+    let to_previous = |position: usize| position - edit_end + edit.end;
+    let to_current = |position: usize| position - edit.end + edit_end;
+
+    // Intervals starting before `left` are saved before the scan reaches the edit
+    let mut left = edit.start.saturating_sub(window_size + 1);
+    while left >= overlap {
+        let preceding = &sequence[left - overlap..left];
+        if let Some(i) = preceding.iter().rposition(|&b| !is_nucleotide(b)) {
+            left = left - overlap + i;
+        } else if let Some((start, _)) = previous.find_touching(left) {
+            left = start;
+        } else {
+            break;
+        }
+    }
+    if left < overlap {
+        left = 0;
+    }
+
+    // Intervals starting from `right` are the same as before the edit
+    let mut right = edit_end + overlap;
+    while right < sequence.len() {
+        let preceding = &sequence[right - overlap..right];
+        if let Some(i) = preceding.iter().rposition(|&b| !is_nucleotide(b)) {
+            right = right - overlap + i + 1 + overlap;
+        } else if let Some((_, end)) = previous.find_touching(to_previous(right)) {
+            right = to_current(end) + 1;
+        } else {
+            break;
+        }
+    }
+    let right = std::cmp::min(right, sequence.len());
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        "rescanning bases {left}..{right} after editing {}..{}",
+        edit.start,
+        edit.end
+    );
+    let mut scanned = Vec::with_capacity(intervals::expected_count(right - left));
+    let keep = if right == sequence.len() {
+        left..usize::MAX
+    } else {
+        left..right
+    };
+    // The scan is never interrupted
+    let _ = Processor::new().process_range(
+        sequence,
+        window_size,
+        score_threshold,
+        left.saturating_sub(overlap)..std::cmp::min(right + window_size + 1, sequence.len()),
+        keep,
+        |interval| scanned.push(interval),
+        &mut |_| false,
+    );
+
+    let before = previous
+        .iter()
+        .take_while(|&(start, _)| start < left)
+        .map(|(start, end)| start..end);
+    // Nothing is reused after the edit when the scan reaches the end
+    let reused_from = if right < sequence.len() {
+        to_previous(right)
+    } else {
+        usize::MAX
+    };
+    let after = previous
+        .iter()
+        .filter(|&(start, _)| start >= reused_from)
+        .map(|(start, end)| to_current(start)..to_current(end));
+    let mut intervals =
+        SpillingIntervals::new(Intervals::for_sequence(sequence.len()), spill_threshold);
+    merge_intervals(before.chain(scanned).chain(after), |interval| {
+        let (start, end) = clip_interval(interval, sequence.len());
+        intervals.push(start, end);
+    });
+    intervals.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_sequence, serial_intervals, Rng};
+
+    fn patched_intervals(rng: &mut Rng, spill_threshold: Option<usize>) {
+        let length = 100 + rng.below(20_000);
+        let sequence = random_sequence(rng, length);
+        let previous: Intervals = serial_intervals(&sequence, 64, 20).into_iter().collect();
+        let start = rng.below(sequence.len());
+        let end = (start + rng.below(300)).min(sequence.len());
+        let replacement_length = rng.below(300);
+        let replacement = random_sequence(rng, replacement_length);
+        let edited = [&sequence[..start], &replacement, &sequence[end..]].concat();
+        let patched = patch(
+            &edited,
+            &previous,
+            start..end,
+            replacement_length,
+            64,
+            20,
+            spill_threshold,
+        )
+        .unwrap();
+        assert_eq!(
+            patched.iter().collect::<Vec<_>>(),
+            serial_intervals(&edited, 64, 20),
+            "editing {start}..{end} with {replacement_length} bases",
+        );
+    }
+
+    #[test]
+    fn edits_match_a_new_scan() {
+        let mut rng = Rng::new(6);
+        for _ in 0..200 {
+            patched_intervals(&mut rng, None);
+        }
+    }
+
+    #[test]
+    fn spilled_edits_match_a_new_scan() {
+        let mut rng = Rng::new(7);
+        for _ in 0..20 {
+            patched_intervals(&mut rng, Some(0));
+        }
+    }
+}
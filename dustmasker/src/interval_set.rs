@@ -0,0 +1,302 @@
+//! Set operations between the low-complexity regions of a sequence and other
+//! regions of it, such as the ones found by tandem repeat finders
+
+use crate::sdust::Interval;
+use alloc::vec::Vec;
+
+/// Sort `intervals` by start, merging the ones that overlap or are adjacent
+/// and dropping the empty ones, so that they can be combined with the other
+/// functions of this module
+pub fn normalize(intervals: impl IntoIterator<Item = Interval>) -> Vec<Interval> {
+    let mut intervals: Vec<Interval> = intervals
+        .into_iter()
+        .filter(|&(start, end)| start < end)
+        .collect();
+    intervals.sort_unstable();
+    let mut normalized: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match normalized.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => normalized.push((start, end)),
+        }
+    }
+    normalized
+}
+
+/// The bases within `a` or `b`
+pub fn union(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    combine(a, b, |in_a, in_b| in_a || in_b)
+}
+
+/// The bases within both `a` and `b`
+pub fn intersection(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    combine(a, b, |in_a, in_b| in_a && in_b)
+}
+
+/// The bases within `a` but not within `b`
+pub fn difference(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    combine(a, b, |in_a, in_b| in_a && !in_b)
+}
+
+/// The parts of `intervals` within the bases from `start` to `end`, with their
+/// positions relative to `start`
+pub fn slice(
+    intervals: impl IntoIterator<Item = Interval>,
+    start: usize,
+    end: usize,
+) -> Vec<Interval> {
+    intervals
+        .into_iter()
+        .map(|(a, b)| (a.clamp(start, end) - start, b.clamp(start, end) - start))
+        .filter(|&(a, b)| a < b)
+        .collect()
+}
+
+/// The bases within only one of two sets of regions and within both, as
+/// returned by [`compare`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comparison {
+    /// Regions of the bases within `a` but not within `b`
+    pub only_a: Vec<Interval>,
+    /// Regions of the bases within `b` but not within `a`
+    pub only_b: Vec<Interval>,
+    /// Regions of the bases within both `a` and `b`
+    pub shared: Vec<Interval>,
+}
+
+impl Comparison {
+    /// Number of bases within `a` but not within `b`
+    pub fn n_only_a(&self) -> usize {
+        n_bases(&self.only_a)
+    }
+
+    /// Number of bases within `b` but not within `a`
+    pub fn n_only_b(&self) -> usize {
+        n_bases(&self.only_b)
+    }
+
+    /// Number of bases within both `a` and `b`
+    pub fn n_shared(&self) -> usize {
+        n_bases(&self.shared)
+    }
+
+    /// The Jaccard index of the bases within `a` and `b`: the number of
+    /// bases within both divided by the number of bases within either, or 1 if
+    /// both are empty
+    pub fn jaccard(&self) -> f64 {
+        let n_shared = self.n_shared();
+        match n_shared + self.n_only_a() + self.n_only_b() {
+            0 => 1.0,
+            n_union => n_shared as f64 / n_union as f64,
+        }
+    }
+}
+
+/// Compare the bases within `a` and within `b`, which must be normalized (see
+/// [`normalize`])
+pub fn compare(a: &[Interval], b: &[Interval]) -> Comparison {
+    Comparison {
+        only_a: difference(a, b),
+        only_b: difference(b, a),
+        shared: intersection(a, b),
+    }
+}
+
+/// Precision, recall and F1 score of predicted regions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Scores {
+    /// Fraction of the predictions that are true, or 0 if there are none
+    pub precision: f64,
+    /// Fraction of the truth that is predicted, or 0 if there is none
+    pub recall: f64,
+    /// Harmonic mean of the precision and the recall, or 0 if both are 0
+    pub f1: f64,
+}
+
+impl Scores {
+    fn new(
+        n_true_predictions: usize,
+        n_predictions: usize,
+        n_predicted_truths: usize,
+        n_truths: usize,
+    ) -> Self {
+        let ratio = |numerator: usize, denominator: usize| match denominator {
+            0 => 0.0,
+            denominator => numerator as f64 / denominator as f64,
+        };
+        let precision = ratio(n_true_predictions, n_predictions);
+        let recall = ratio(n_predicted_truths, n_truths);
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+        Scores {
+            precision,
+            recall,
+            f1,
+        }
+    }
+}
+
+/// Agreement of predicted regions with the true ones, as returned by
+/// [`evaluate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Evaluation {
+    /// Scores of the predicted bases: the precision is the fraction of
+    /// predicted bases that are within true regions, and the recall is the
+    /// fraction of bases of the true regions that are predicted
+    pub base: Scores,
+    /// Scores of the regions: the precision is the fraction of predicted
+    /// regions that overlap a true region, and the recall is the fraction of
+    /// true regions that overlap a predicted region
+    pub interval: Scores,
+}
+
+/// Evaluate the `predicted` regions against the `truth`, which must be
+/// normalized (see [`normalize`])
+pub fn evaluate(predicted: &[Interval], truth: &[Interval]) -> Evaluation {
+    let n_shared = n_bases(&intersection(predicted, truth));
+    Evaluation {
+        base: Scores::new(n_shared, n_bases(predicted), n_shared, n_bases(truth)),
+        interval: Scores::new(
+            n_overlapping(predicted, truth),
+            predicted.len(),
+            n_overlapping(truth, predicted),
+            truth.len(),
+        ),
+    }
+}
+
+/// Number of the `intervals` that overlap any of the `others`, both being
+/// normalized
+fn n_overlapping(intervals: &[Interval], others: &[Interval]) -> usize {
+    let mut j = 0;
+    intervals
+        .iter()
+        .filter(|&&(start, end)| {
+            while others
+                .get(j)
+                .is_some_and(|&(_, other_end)| other_end <= start)
+            {
+                j += 1;
+            }
+            others
+                .get(j)
+                .is_some_and(|&(other_start, _)| other_start < end)
+        })
+        .count()
+}
+
+/// Number of bases within non-overlapping `intervals`
+fn n_bases(intervals: &[Interval]) -> usize {
+    intervals.iter().map(|(start, end)| end - start).sum()
+}
+
+/// The bases for which `keep` returns true given whether they are within `a`
+/// and within `b`, which must be normalized (see [`normalize`]). The result is
+/// normalized as well.
+fn combine(a: &[Interval], b: &[Interval], keep: impl Fn(bool, bool) -> bool) -> Vec<Interval> {
+    // Whether a base is within `a` and within `b` only changes at the
+    // boundaries of their intervals
+    let mut boundaries: Vec<usize> = a
+        .iter()
+        .chain(b)
+        .flat_map(|&(start, end)| [start, end])
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    let (mut i, mut j) = (0, 0);
+    let mut combined: Vec<Interval> = Vec::new();
+    for segment in boundaries.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        while a.get(i).is_some_and(|&(_, a_end)| a_end <= start) {
+            i += 1;
+        }
+        while b.get(j).is_some_and(|&(_, b_end)| b_end <= start) {
+            j += 1;
+        }
+        let in_a = a.get(i).is_some_and(|&(a_start, _)| a_start <= start);
+        let in_b = b.get(j).is_some_and(|&(b_start, _)| b_start <= start);
+        if !keep(in_a, in_b) {
+            continue;
+        }
+        match combined.last_mut() {
+            Some(last) if last.1 == start => last.1 = end,
+            _ => combined.push((start, end)),
+        }
+    }
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+    use alloc::vec;
+
+    const LENGTH: usize = 200;
+
+    fn random_intervals(rng: &mut Rng) -> Vec<Interval> {
+        (0..rng.below(10))
+            .map(|_| {
+                let start = rng.below(LENGTH);
+                (start, (start + rng.below(40)).min(LENGTH))
+            })
+            .collect()
+    }
+
+    /// Whether each base is within `intervals`
+    fn bases(intervals: &[Interval]) -> Vec<bool> {
+        let mut bases = vec![false; LENGTH];
+        for &(start, end) in intervals {
+            bases[start..end].fill(true);
+        }
+        bases
+    }
+
+    /// The normalized intervals of the bases that are true
+    fn from_bases(bases: &[bool]) -> Vec<Interval> {
+        normalize(
+            bases
+                .iter()
+                .enumerate()
+                .filter(|(_, &within)| within)
+                .map(|(position, _)| (position, position + 1)),
+        )
+    }
+
+    #[test]
+    fn operations_match_the_bases() {
+        let mut rng = Rng::new(9);
+        for _ in 0..1_000 {
+            let a = normalize(random_intervals(&mut rng));
+            let b = normalize(random_intervals(&mut rng));
+            let (a_bases, b_bases) = (bases(&a), bases(&b));
+            let combined = |keep: fn(bool, bool) -> bool| {
+                let bases: Vec<bool> = a_bases
+                    .iter()
+                    .zip(&b_bases)
+                    .map(|(&in_a, &in_b)| keep(in_a, in_b))
+                    .collect();
+                from_bases(&bases)
+            };
+            assert_eq!(a, from_bases(&a_bases));
+            assert_eq!(union(&a, &b), combined(|in_a, in_b| in_a || in_b));
+            assert_eq!(intersection(&a, &b), combined(|in_a, in_b| in_a && in_b));
+            assert_eq!(difference(&a, &b), combined(|in_a, in_b| in_a && !in_b));
+        }
+    }
+
+    #[test]
+    fn normalized_intervals_are_sorted_and_disjoint() {
+        let mut rng = Rng::new(10);
+        for _ in 0..1_000 {
+            let intervals = random_intervals(&mut rng);
+            let normalized = normalize(intervals.iter().copied());
+            assert_eq!(bases(&normalized), bases(&intervals));
+            assert!(normalized.iter().all(|&(start, end)| start < end));
+            assert!(normalized.windows(2).all(|pair| pair[0].1 < pair[1].0));
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Compact storage of the low-complexity regions of a sequence
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use memmap2::Mmap;
+
+/// Expected number of bases per low-complexity interval, used to pre-allocate
+/// storage for the intervals of a sequence. This is a rough estimate, and the
+/// storage still grows as usual in sequences with more intervals.
+const EXPECTED_INTERVAL_SPACING: usize = 1024;
+
+/// Number of intervals expected in a sequence of `sequence_length` bases
+pub fn expected_count(sequence_length: usize) -> usize {
+    sequence_length / EXPECTED_INTERVAL_SPACING
+}
+
+/// Low-complexity intervals found in a sequence, stored as pairs of 32-bit
+/// integers while all positions fit in them (i.e., for sequences shorter than
+/// 4 Gb), which halves their memory usage. Other kinds of storage may be added,
+/// such as the mapped intervals of the `std` feature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Intervals {
+    Compact(Vec<[u32; 2]>),
+    Wide(Vec<[u64; 2]>),
+    /// Intervals spilled to a temporary file (see `spill`)
+    #[cfg(feature = "std")]
+    Mapped(MappedPairs),
+}
+
+/// Pairs of 64-bit integers in a memory-mapped file, which can only be built
+/// by `spill` once all the intervals were written to it
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct MappedPairs(Mmap);
+
+#[cfg(feature = "std")]
+impl MappedPairs {
+    /// Wrap `mmap`, which must hold whole pairs starting at an aligned address
+    pub(crate) fn new(mmap: Mmap) -> std::io::Result<Self> {
+        let pairs = mmap.as_ptr().cast::<[u64; 2]>();
+        if !mmap.len().is_multiple_of(size_of::<[u64; 2]>()) || !pairs.is_aligned() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the mapped file doesn't hold whole pairs of 64-bit integers",
+            ));
+        }
+        Ok(MappedPairs(mmap))
+    }
+
+    fn pairs(&self) -> &[[u64; 2]] {
+        let len = self.0.len() / size_of::<[u64; 2]>();
+        // SAFETY: any bytes are valid integers, and `new` checked that the map
+        // is aligned and only holds whole pairs
+        unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast(), len) }
+    }
+}
+
+/// The intervals as a slice of pairs of positions
+pub enum Pairs<'a> {
+    Compact(&'a [[u32; 2]]),
+    Wide(&'a [[u64; 2]]),
+}
+
+impl Default for Intervals {
+    fn default() -> Self {
+        Intervals::Compact(Vec::new())
+    }
+}
+
+impl Intervals {
+    /// Create an empty set of intervals with room for the ones expected in a
+    /// sequence of `sequence_length` bases, stored in 64-bit integers right away
+    /// if the positions can't fit in 32 bits
+    pub fn for_sequence(sequence_length: usize) -> Self {
+        let capacity = expected_count(sequence_length);
+        if u32::try_from(sequence_length).is_ok() {
+            Intervals::Compact(Vec::with_capacity(capacity))
+        } else {
+            Intervals::Wide(Vec::with_capacity(capacity))
+        }
+    }
+
+    /// Append an interval, switching to 64-bit storage if it doesn't fit in 32
+    /// bits. Mapped intervals are read-only, so they are only built once all
+    /// the intervals were collected (see `spill`).
+    pub(crate) fn push(&mut self, start: usize, end: usize) {
+        if let Intervals::Compact(compact) = self {
+            match (u32::try_from(start), u32::try_from(end)) {
+                (Ok(start), Ok(end)) => {
+                    compact.push([start, end]);
+                    return;
+                }
+                _ => {
+                    let wide = compact
+                        .iter()
+                        .map(|&[start, end]| [start as u64, end as u64])
+                        .collect();
+                    *self = Intervals::Wide(wide);
+                }
+            }
+        }
+        match self {
+            Intervals::Wide(wide) => wide.push([start as u64, end as u64]),
+            Intervals::Compact(_) => unreachable!(),
+            #[cfg(feature = "std")]
+            Intervals::Mapped(_) => unreachable!("intervals can't be appended to a mapped file"),
+        }
+    }
+
+    pub fn pairs(&self) -> Pairs<'_> {
+        match self {
+            Intervals::Compact(compact) => Pairs::Compact(compact),
+            Intervals::Wide(wide) => Pairs::Wide(wide),
+            #[cfg(feature = "std")]
+            Intervals::Mapped(mapped) => Pairs::Wide(mapped.pairs()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self.pairs() {
+            Pairs::Compact(compact) => compact.len(),
+            Pairs::Wide(wide) => wide.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of bytes allocated on the heap, including the room reserved for
+    /// more intervals. Mapped intervals are not counted (see `mapped_bytes`).
+    pub fn allocated_bytes(&self) -> usize {
+        match self {
+            Intervals::Compact(compact) => compact.capacity() * size_of::<[u32; 2]>(),
+            Intervals::Wide(wide) => wide.capacity() * size_of::<[u64; 2]>(),
+            #[cfg(feature = "std")]
+            Intervals::Mapped(_) => 0,
+        }
+    }
+
+    /// Number of bytes of the file the intervals are mapped from, which the
+    /// operating system pages in and out of memory as they are read
+    pub fn mapped_bytes(&self) -> usize {
+        match self {
+            #[cfg(feature = "std")]
+            Intervals::Mapped(mapped) => mapped.0.len(),
+            _ => 0,
+        }
+    }
+
+    /// The `(start, end)` positions of the interval at `index`
+    pub fn get(&self, index: usize) -> Option<(usize, usize)> {
+        match self.pairs() {
+            Pairs::Compact(compact) => compact
+                .get(index)
+                .map(|&[start, end]| (start as usize, end as usize)),
+            Pairs::Wide(wide) => wide
+                .get(index)
+                .map(|&[start, end]| (start as usize, end as usize)),
+        }
+    }
+
+    /// The index of the first interval ending after `position`, or the number
+    /// of intervals if they all end before it
+    pub fn first_ending_after(&self, position: usize) -> usize {
+        // Intervals are sorted and don't overlap, so their ends are sorted too
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.get(middle) {
+                Some((_, end)) if end <= position => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+        low
+    }
+
+    /// The interval that contains `position` or ends right at it, if any
+    pub fn find_touching(&self, position: usize) -> Option<(usize, usize)> {
+        // Intervals are sorted and don't overlap, so a binary search finds the
+        // last one starting before `position`
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.get(middle) {
+                Some((start, _)) if start < position => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+        let (start, end) = self.get(low.checked_sub(1)?)?;
+        (start < position && position <= end).then_some((start, end))
+    }
+
+    /// Iterate over the `(start, end)` positions of the intervals
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, usize)> + '_ {
+        let (compact, wide): (&[[u32; 2]], &[[u64; 2]]) = match self.pairs() {
+            Pairs::Compact(compact) => (compact, &[]),
+            Pairs::Wide(wide) => (&[], wide),
+        };
+        compact
+            .iter()
+            .map(|&[start, end]| (start as usize, end as usize))
+            .chain(
+                wide.iter()
+                    .map(|&[start, end]| (start as usize, end as usize)),
+            )
+    }
+}
+
+/// Intervals are collected in memory, in the order they are given
+impl FromIterator<(usize, usize)> for Intervals {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let mut intervals = Intervals::default();
+        for (start, end) in iter {
+            intervals.push(start, end);
+        }
+        intervals
+    }
+}
+
+/// Intervals are serialized as a sequence of `(start, end)` pairs, regardless
+/// of how they are stored
+#[cfg(feature = "serde")]
+impl serde::Serialize for Intervals {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Intervals {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PairsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for PairsVisitor {
+            type Value = Intervals;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a sequence of (start, end) pairs")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Intervals, A::Error> {
+                let mut intervals = Intervals::default();
+                while let Some((start, end)) = seq.next_element::<(usize, usize)>()? {
+                    intervals.push(start, end);
+                }
+                Ok(intervals)
+            }
+        }
+
+        deserializer.deserialize_seq(PairsVisitor)
+    }
+}
@@ -0,0 +1,132 @@
+//! Iteration over the k-mers of a sequence that don't overlap its
+//! low-complexity regions
+
+use crate::sdust::{is_nucleotide, Interval};
+
+/// Largest k-mer size, so that the 2-bit codes of the k-mers fit in 64 bits
+pub const MAX_KMER_SIZE: usize = 32;
+
+/// The 2-bit code of a nucleotide: A=0, C=1, G=2 and T=3, in either case
+#[inline]
+pub(crate) fn nucleotide_code(base: u8) -> u64 {
+    match base | 0x20 {
+        b'a' => 0,
+        b'c' => 1,
+        b'g' => 2,
+        _ => 3,
+    }
+}
+
+/// Position of an iteration over the k-mers of a sequence that don't overlap
+/// its low-complexity regions, which doesn't borrow the sequence nor the
+/// regions, so that they can be passed to each call of `advance`.
+///
+/// Only k-mers made of A, C, G and T bases are returned, and each one of them
+/// is encoded with 2 bits per base (A=0, C=1, G=2 and T=3), the first base being
+/// the most significant.
+#[derive(Debug, Clone)]
+pub struct KmerCursor {
+    kmer_size: usize,
+    /// Position of the next base to be read
+    position: usize,
+    /// Index of the first region that doesn't end before `position`
+    interval_index: usize,
+    /// Code of the last bases read
+    code: u64,
+    /// Number of consecutive unmasked nucleotides read before `position`
+    run: usize,
+}
+
+impl KmerCursor {
+    /// A cursor at the start of a sequence, for k-mers of `kmer_size` bases.
+    ///
+    /// # Panics
+    ///
+    /// If `kmer_size` is not between 1 and `MAX_KMER_SIZE`.
+    pub fn new(kmer_size: usize) -> Self {
+        assert!((1..=MAX_KMER_SIZE).contains(&kmer_size));
+        KmerCursor {
+            kmer_size,
+            position: 0,
+            interval_index: 0,
+            code: 0,
+            run: 0,
+        }
+    }
+
+    /// The start position and the code of the next k-mer of `sequence` that
+    /// doesn't overlap any of its regions, where `interval(i)` returns the
+    /// `i`-th region, which must be sorted by start
+    pub fn advance(
+        &mut self,
+        sequence: &[u8],
+        interval: impl Fn(usize) -> Option<Interval>,
+    ) -> Option<(usize, u64)> {
+        let mask = u64::MAX >> (64 - 2 * self.kmer_size);
+        while self.position < sequence.len() {
+            let mut next_interval = interval(self.interval_index);
+            while let Some((_, end)) = next_interval {
+                if end > self.position {
+                    break;
+                }
+                self.interval_index += 1;
+                next_interval = interval(self.interval_index);
+            }
+            // Skip the masked bases at once
+            if let Some((start, end)) = next_interval {
+                if start <= self.position {
+                    self.position = end;
+                    self.run = 0;
+                    continue;
+                }
+            }
+            let base = sequence[self.position];
+            self.position += 1;
+            if !is_nucleotide(base) {
+                self.run = 0;
+                continue;
+            }
+            self.code = ((self.code << 2) | nucleotide_code(base)) & mask;
+            self.run += 1;
+            if self.run >= self.kmer_size {
+                return Some((self.position - self.kmer_size, self.code));
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the start positions and 2-bit codes of the k-mers of a
+/// sequence that don't overlap any of its low-complexity regions (see
+/// [`KmerCursor`])
+#[derive(Debug, Clone)]
+pub struct MaskedKmers<'a> {
+    sequence: &'a [u8],
+    intervals: &'a [Interval],
+    cursor: KmerCursor,
+}
+
+impl<'a> MaskedKmers<'a> {
+    /// The k-mers of `kmer_size` bases of `sequence` that don't overlap any of
+    /// `intervals`, which must be sorted by start like the ones returned by
+    /// the algorithm
+    pub fn new(sequence: &'a [u8], intervals: &'a [Interval], kmer_size: usize) -> Self {
+        MaskedKmers {
+            sequence,
+            intervals,
+            cursor: KmerCursor::new(kmer_size),
+        }
+    }
+}
+
+impl Iterator for MaskedKmers<'_> {
+    type Item = (usize, u64);
+
+    fn next(&mut self) -> Option<(usize, u64)> {
+        let intervals = self.intervals;
+        self.cursor
+            .advance(self.sequence, |index| intervals.get(index).copied())
+    }
+}
+
+impl core::iter::FusedIterator for MaskedKmers<'_> {}
@@ -0,0 +1,179 @@
+//! A fast implementation of the symmetric DUST algorithm, which identifies
+//! low-complexity regions in nucleotide sequences. It is the core of the
+//! `pydustmasker` Python package, whose bindings are a separate crate, so this
+//! one doesn't link to Python:
+//!
+//! - [`sdust::SymmetricDust::process_with`] finds the low-complexity regions of
+//!   a sequence in a single thread, and [`parallel::process_with`] splits long
+//!   sequences in chunks processed by multiple threads.
+//! - [`sdust::SymmetricDust::intervals`] iterates over the low-complexity
+//!   regions of a sequence, scanning it as they are requested.
+//! - [`stream::StreamingDust`] processes a sequence received in chunks.
+//! - [`twobit::process_with`] processes 2-bit packed sequences.
+//! - [`count`] counts masked bases without storing the regions, and
+//!   [`sweep::sweep`] counts them for a grid of parameters in a single scan.
+//! - [`summary::Summary`] accumulates the masking of many sequences.
+//! - [`kmers::MaskedKmers`] iterates over the k-mers of a sequence that don't
+//!   overlap its low-complexity regions.
+//! - [`composition::Composition`] holds the GC, AT and N counts of a sequence,
+//!   which [`sdust::SymmetricDust::process_with_composition`] gathers while it
+//!   is scanned.
+//! - [`edit::patch`] updates the regions of a sequence after a local edit.
+//! - [`interval_set`] combines the regions with other regions of a sequence.
+//! - [`strand::reverse_complement`] reverse complements a sequence, and
+//!   [`strand::reverse_intervals`] converts its regions to the coordinates of
+//!   the minus strand.
+//! - [`alphabet::Alphabet`] maps the characters of a sequence to custom
+//!   symbols, used by [`sdust::SymmetricDust::process_with_alphabet`], and
+//!   [`sdust::SymmetricDust::process_with_kmers`] compares k-mers of 2 to 5
+//!   symbols instead of triplets.
+//! - [`sdust::SymmetricDust::process_with_overrides`] uses other score
+//!   thresholds within regions of a sequence ([`sdust::ThresholdOverride`]),
+//!   in a single pass.
+//! - [`simulate::simulate`] generates random sequences with planted
+//!   low-complexity regions, which can be compared with the regions found with
+//!   [`interval_set::evaluate`].
+//! - [`metrics::window_metrics`] computes the DUST score, the entropy and the GC
+//!   content of sliding windows of a sequence, and [`metrics::repeat_unit`]
+//!   finds the unit repeated in a low-complexity region.
+//! - [`config::DustConfig`] holds the parameters of the algorithm, checked
+//!   when they are built with [`config::DustConfig::builder`].
+//! - [`masker::Masker`] is the interface shared by the masking algorithms,
+//!   implemented for symmetric DUST by [`masker::SymmetricDustMasker`].
+//! - `bio`, enabled with the `bio` feature, converts the regions to
+//!   [rust-bio](https://docs.rs/bio) intervals and interval trees.
+//! - `capi`, enabled with the `capi` feature, exports the algorithm through a C
+//!   ABI, declared in `include/dustmasker.h`.
+//!
+//! With the `serde` feature, the parameters ([`masker::SymmetricDustMasker`])
+//! and the regions ([`intervals::Intervals`]) can be serialized and
+//! deserialized.
+//!
+//! Regions are reported as the start and end positions of the bases they span,
+//! with the same semantics as the `intervals` attribute of `DustMasker`. Inputs
+//! can be checked with [`validate_inputs`] beforehand, as the algorithm
+//! requires a window size of at least 3.
+//!
+//! The [`parallel`], [`spill`], [`edit`] and [`metrics`] modules require the `std` feature,
+//! enabled by default. Without it, the crate is `no_std` and only requires an
+//! allocator, so that it can be compiled to targets such as
+//! `wasm32-unknown-unknown`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod alphabet;
+#[cfg(feature = "bio")]
+pub mod bio;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod composition;
+pub mod config;
+pub mod count;
+#[cfg(feature = "std")]
+pub mod edit;
+pub mod interval_set;
+pub mod intervals;
+pub mod kmers;
+pub mod masker;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod parallel;
+pub mod sdust;
+pub mod simulate;
+#[cfg(feature = "std")]
+pub mod spill;
+pub mod strand;
+pub mod stream;
+pub mod summary;
+pub mod sweep;
+#[cfg(test)]
+mod testing;
+pub mod twobit;
+
+use thiserror::Error;
+
+/// Minimum length of the sequences accepted by `DustMasker`
+pub const MIN_SEQUENCE_LENGTH: usize = 4;
+/// Minimum window size supported by the algorithm
+pub const MIN_WINDOW_SIZE: usize = 3;
+
+/// Reasons for rejecting the inputs
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("sequence is too short, it must be at least 4 characters long")]
+    SequenceLengthError(usize),
+    #[error("invalid window size '{0}', must be at least '3'")]
+    WindowSizeError(usize),
+    #[error("packed sequence holds {0} bases, fewer than the sequence length '{1}'")]
+    PackedLengthError(usize, usize),
+    #[error("invalid N block '{0}..{1}', blocks must be sorted, non-overlapping and within the sequence")]
+    NBlockError(usize, usize),
+    #[error("invalid alphabet size '{0}', must be between '1' and '6'")]
+    AlphabetSizeError(usize),
+    #[error("character '{0}' stands for more than one symbol of the alphabet")]
+    AlphabetCharacterError(char),
+    #[error("invalid k-mer size '{0}', must be between '2' and '5' and at most the window size")]
+    KmerSizeError(usize),
+    #[error("invalid level '{0}', must be a non-negative multiple of '0.1'")]
+    LevelError(f64),
+    #[error("invalid {0} range, must not be empty nor include '0'")]
+    SimulationRangeError(&'static str),
+    #[error("'{0}' regions of up to '{1}' bases don't fit in a sequence of length '{2}'")]
+    SimulationLengthError(usize, usize, usize),
+    #[error("invalid mutation rate '{0}', must be between '0' and '1'")]
+    MutationRateError(f64),
+    #[error("invalid step '{0}', must be positive")]
+    StepError(usize),
+    #[error("invalid threshold override '{0}..{1}', overrides must not be empty nor overlap")]
+    ThresholdOverrideError(usize, usize),
+}
+
+/// Error returned by the scans that were stopped early by their `interrupt`
+/// callback (see [`masker::Masker::process_with_interrupt`])
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("the scan was cancelled")]
+pub struct Cancelled;
+
+/// Check that `sequence` and `window_size` are accepted by `DustMasker`
+pub fn validate_inputs(sequence: &[u8], window_size: usize) -> Result<(), InputError> {
+    config::DustConfig::builder()
+        .window_size(window_size)
+        .build()?
+        .check_sequence(sequence)
+}
+
+/// Check that `overrides` are accepted by
+/// [`sdust::SymmetricDust::process_with_overrides`]: they must not be empty nor
+/// overlap, in any order
+pub fn validate_threshold_overrides(
+    overrides: &[sdust::ThresholdOverride],
+) -> Result<(), InputError> {
+    let mut regions: alloc::vec::Vec<(usize, usize)> = overrides
+        .iter()
+        .map(|threshold_override| (threshold_override.start, threshold_override.end))
+        .collect();
+    regions.sort_unstable();
+    let mut previous_end = 0;
+    for (start, end) in regions {
+        if start >= end || start < previous_end {
+            return Err(InputError::ThresholdOverrideError(start, end));
+        }
+        previous_end = end;
+    }
+    Ok(())
+}
+
+/// Check that `kmer_size` is accepted by
+/// [`sdust::SymmetricDust::process_with_kmers`] for windows of `window_size`
+/// bases
+pub fn validate_kmer_size(kmer_size: usize, window_size: usize) -> Result<(), InputError> {
+    if !(sdust::MIN_KMER_SIZE..=sdust::MAX_KMER_SIZE).contains(&kmer_size)
+        || kmer_size > window_size
+    {
+        return Err(InputError::KmerSizeError(kmer_size));
+    }
+    Ok(())
+}
@@ -0,0 +1,508 @@
+//! Common interface of the algorithms that identify low-complexity regions
+
+use crate::alphabet::Alphabet;
+use crate::composition::{self, Composition, ScanDetails};
+use crate::interval_set;
+#[cfg(feature = "std")]
+use crate::parallel;
+use crate::sdust::{Interval, SymmetricDust, ThresholdOverride};
+use crate::Cancelled;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// An algorithm that identifies low-complexity regions in nucleotide
+/// sequences, together with its parameters. Implementations only need to scan
+/// a sequence, and can then be used wherever a `Masker` trait object is
+/// accepted, such as in the Python bindings.
+pub trait Masker: Send + Sync {
+    /// Short name of the algorithm
+    fn name(&self) -> &'static str;
+
+    /// Identify the low-complexity regions in `sequence`, passing the start and
+    /// end of each one of them to `sink`, in order and without overlaps
+    fn process_with(&self, sequence: &[u8], sink: &mut dyn FnMut(usize, usize));
+
+    /// Like `process_with`, also returning the composition of `sequence`. By
+    /// default, the sequence is read again once it is scanned, while
+    /// implementations can gather the composition in the same pass.
+    fn process_with_composition(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+    ) -> Composition {
+        self.process_with(sequence, sink);
+        Composition::of(sequence)
+    }
+
+    /// Like `process_with`, periodically calling `interrupt` with the number
+    /// of bases scanned so far and stopping the scan if it returns true, so
+    /// that the progress of the scan of a long sequence can be reported and
+    /// the scan cancelled. The regions that were passed to `sink` until then
+    /// are final, but the remaining ones are never found. By default,
+    /// `interrupt` is only called before the scan starts.
+    fn process_with_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        if interrupt(0) {
+            return Err(Cancelled);
+        }
+        self.process_with(sequence, sink);
+        Ok(())
+    }
+
+    /// Like `process_with_composition`, calling `interrupt` like
+    /// `process_with_interrupt`
+    fn process_with_composition_and_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<Composition, Cancelled> {
+        self.process_with_interrupt(sequence, sink, interrupt)?;
+        Ok(Composition::of(sequence))
+    }
+
+    /// Like `process_with_interrupt`, also returning the runs of ambiguous
+    /// characters of `sequence` and, if `composition` is true, its composition.
+    /// By default, the sequence is read again once it is scanned, while
+    /// implementations can gather them in the same pass.
+    fn process_with_details_and_interrupt(
+        &self,
+        sequence: &[u8],
+        composition: bool,
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        self.process_with_interrupt(sequence, sink, interrupt)?;
+        Ok(ScanDetails::of(sequence, composition))
+    }
+
+    /// The low-complexity regions in `sequence`
+    fn intervals(&self, sequence: &[u8]) -> Vec<Interval> {
+        let mut intervals = Vec::new();
+        self.process_with(sequence, &mut |start, end| intervals.push((start, end)));
+        intervals
+    }
+}
+
+/// The symmetric DUST algorithm (see `sdust::SymmetricDust`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymmetricDustMasker {
+    /// The length of the window, at least 3
+    pub window_size: usize,
+    /// The score threshold for subwindows
+    pub score_threshold: usize,
+    /// Number of threads used to process long sequences, 0 meaning one per
+    /// available core (see `parallel::process_with`). Sequences are always
+    /// processed in a single thread without the `std` feature.
+    pub threads: usize,
+}
+
+impl Masker for SymmetricDustMasker {
+    fn name(&self) -> &'static str {
+        "sdust"
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sdust",
+            level = "debug",
+            skip_all,
+            fields(length = sequence.len(), window_size = self.window_size, score_threshold = self.score_threshold)
+        )
+    )]
+    fn process_with(&self, sequence: &[u8], sink: &mut dyn FnMut(usize, usize)) {
+        #[cfg(feature = "tracing")]
+        let start_time = std::time::Instant::now();
+        #[cfg(feature = "std")]
+        parallel::process_with(
+            sequence,
+            self.window_size,
+            self.score_threshold,
+            self.threads,
+            sink,
+        );
+        #[cfg(not(feature = "std"))]
+        SymmetricDust::process_with(sequence, self.window_size, self.score_threshold, sink);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?start_time.elapsed(), "scanned {} bases", sequence.len());
+    }
+
+    /// The composition is gathered in the same pass as the scan when the
+    /// sequence is processed in a single thread
+    fn process_with_composition(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+    ) -> Composition {
+        if cfg!(feature = "std") && self.threads != 1 {
+            self.process_with(sequence, sink);
+            return Composition::of(sequence);
+        }
+        SymmetricDust::process_with_composition(
+            sequence,
+            self.window_size,
+            self.score_threshold,
+            sink,
+        )
+    }
+
+    /// `interrupt` is called every `sdust::INTERRUPT_INTERVAL` bases in a
+    /// single thread, and every 10 milliseconds otherwise (see
+    /// `parallel::process_with_interrupt`)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sdust",
+            level = "debug",
+            skip_all,
+            fields(length = sequence.len(), window_size = self.window_size, score_threshold = self.score_threshold)
+        )
+    )]
+    fn process_with_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        #[cfg(feature = "tracing")]
+        let start_time = std::time::Instant::now();
+        #[cfg(feature = "std")]
+        let result = parallel::process_with_interrupt(
+            sequence,
+            self.window_size,
+            self.score_threshold,
+            self.threads,
+            sink,
+            interrupt,
+        );
+        #[cfg(not(feature = "std"))]
+        let result = SymmetricDust::process_with_interrupt(
+            sequence,
+            self.window_size,
+            self.score_threshold,
+            sink,
+            interrupt,
+        );
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(()) => {
+                tracing::debug!(elapsed = ?start_time.elapsed(), "scanned {} bases", sequence.len())
+            }
+            Err(Cancelled) => {
+                tracing::debug!(elapsed = ?start_time.elapsed(), "cancelled the scan")
+            }
+        }
+        result
+    }
+
+    fn process_with_composition_and_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<Composition, Cancelled> {
+        if cfg!(feature = "std") && self.threads != 1 {
+            self.process_with_interrupt(sequence, sink, interrupt)?;
+            return Ok(Composition::of(sequence));
+        }
+        SymmetricDust::new(self.window_size, self.score_threshold)
+            .scan_with_composition_and_interrupt(sequence, sink, interrupt)
+    }
+
+    /// The details are gathered in the same pass as the scan when the sequence
+    /// is processed in a single thread
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sdust",
+            level = "debug",
+            skip_all,
+            fields(length = sequence.len(), window_size = self.window_size, score_threshold = self.score_threshold)
+        )
+    )]
+    fn process_with_details_and_interrupt(
+        &self,
+        sequence: &[u8],
+        composition: bool,
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        if cfg!(feature = "std") && self.threads != 1 {
+            self.process_with_interrupt(sequence, sink, interrupt)?;
+            return Ok(ScanDetails::of(sequence, composition));
+        }
+        #[cfg(feature = "tracing")]
+        let start_time = std::time::Instant::now();
+        let result = SymmetricDust::process_with_details_and_interrupt(
+            sequence,
+            self.window_size,
+            self.score_threshold,
+            composition,
+            sink,
+            interrupt,
+        );
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(_) => {
+                tracing::debug!(elapsed = ?start_time.elapsed(), "scanned {} bases", sequence.len())
+            }
+            Err(Cancelled) => {
+                tracing::debug!(elapsed = ?start_time.elapsed(), "cancelled the scan")
+            }
+        }
+        result
+    }
+}
+
+/// The symmetric DUST algorithm for sequences whose characters are mapped to
+/// the symbols of a custom alphabet (see `alphabet::Alphabet`), comparing
+/// k-mers of any supported size. Sequences are always processed in a single
+/// thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlphabetDustMasker {
+    pub alphabet: Alphabet,
+    /// The length of the window, at least 3
+    pub window_size: usize,
+    /// The score threshold for subwindows
+    pub score_threshold: usize,
+    /// The number of symbols of the compared k-mers, 3 in the original
+    /// algorithm (see `validate_kmer_size`)
+    pub kmer_size: usize,
+}
+
+impl Masker for AlphabetDustMasker {
+    fn name(&self) -> &'static str {
+        "sdust"
+    }
+
+    fn process_with(&self, sequence: &[u8], sink: &mut dyn FnMut(usize, usize)) {
+        SymmetricDust::process_with_kmers(
+            sequence,
+            self.window_size,
+            self.score_threshold,
+            &self.alphabet,
+            self.kmer_size,
+            sink,
+        );
+    }
+
+    fn process_with_composition(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+    ) -> Composition {
+        let mut dust = SymmetricDust::new(self.window_size, self.score_threshold);
+        dust.set_encoding(Some(self.alphabet.clone()), self.kmer_size);
+        dust.scan_with_composition(sequence, sink)
+    }
+
+    /// `interrupt` is called every `sdust::INTERRUPT_INTERVAL` bases
+    fn process_with_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        let mut dust = SymmetricDust::new(self.window_size, self.score_threshold);
+        dust.set_encoding(Some(self.alphabet.clone()), self.kmer_size);
+        dust.scan_with_interrupt(sequence, sink, interrupt)
+    }
+
+    fn process_with_composition_and_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<Composition, Cancelled> {
+        let mut dust = SymmetricDust::new(self.window_size, self.score_threshold);
+        dust.set_encoding(Some(self.alphabet.clone()), self.kmer_size);
+        dust.scan_with_composition_and_interrupt(sequence, sink, interrupt)
+    }
+
+    fn process_with_details_and_interrupt(
+        &self,
+        sequence: &[u8],
+        composition: bool,
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        let mut dust = SymmetricDust::new(self.window_size, self.score_threshold);
+        dust.set_encoding(Some(self.alphabet.clone()), self.kmer_size);
+        dust.scan_with_details_and_interrupt(sequence, sink, interrupt, composition)
+    }
+}
+
+/// The symmetric DUST algorithm with score thresholds that depend on the
+/// position in the sequence (see `sdust::ThresholdOverride`), applied in a
+/// single pass. Sequences are always processed in a single thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionalDustMasker {
+    /// The length of the window, at least 3
+    pub window_size: usize,
+    /// The score threshold for subwindows outside the overrides
+    pub score_threshold: usize,
+    /// The regions that use other score thresholds, which must not overlap
+    /// (see `validate_threshold_overrides`)
+    pub overrides: Vec<ThresholdOverride>,
+}
+
+impl RegionalDustMasker {
+    /// The state of a scan with the thresholds of the masker
+    fn dust(&self) -> SymmetricDust {
+        let mut dust = SymmetricDust::new(self.window_size, self.score_threshold);
+        dust.set_threshold_overrides(&self.overrides);
+        dust
+    }
+}
+
+impl Masker for RegionalDustMasker {
+    fn name(&self) -> &'static str {
+        "sdust"
+    }
+
+    fn process_with(&self, sequence: &[u8], sink: &mut dyn FnMut(usize, usize)) {
+        self.dust().scan(sequence, sink);
+    }
+
+    fn process_with_composition(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+    ) -> Composition {
+        self.dust().scan_with_composition(sequence, sink)
+    }
+
+    /// `interrupt` is called every `sdust::INTERRUPT_INTERVAL` bases
+    fn process_with_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        self.dust().scan_with_interrupt(sequence, sink, interrupt)
+    }
+
+    fn process_with_composition_and_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<Composition, Cancelled> {
+        self.dust()
+            .scan_with_composition_and_interrupt(sequence, sink, interrupt)
+    }
+
+    fn process_with_details_and_interrupt(
+        &self,
+        sequence: &[u8],
+        composition: bool,
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        self.dust()
+            .scan_with_details_and_interrupt(sequence, sink, interrupt, composition)
+    }
+}
+
+/// Another masker whose regions are merged with the runs of ambiguous
+/// characters of the sequence (see `composition::ambiguous_runs`), so that the
+/// bases that can't be trusted are reported as a single set of regions. The
+/// regions are only passed to the sink once the whole sequence is scanned.
+#[derive(Clone)]
+pub struct AmbiguousRunsMasker {
+    /// The masker that identifies the low-complexity regions
+    pub masker: Arc<dyn Masker>,
+}
+
+impl AmbiguousRunsMasker {
+    /// Passes the union of `intervals` and the runs of ambiguous characters
+    /// `runs` to `sink`
+    fn merge(runs: &[Interval], intervals: &[Interval], sink: &mut dyn FnMut(usize, usize)) {
+        for (start, end) in interval_set::union(intervals, runs) {
+            sink(start, end);
+        }
+    }
+}
+
+impl Masker for AmbiguousRunsMasker {
+    fn name(&self) -> &'static str {
+        self.masker.name()
+    }
+
+    fn process_with(&self, sequence: &[u8], sink: &mut dyn FnMut(usize, usize)) {
+        let intervals = self.masker.intervals(sequence);
+        Self::merge(&composition::ambiguous_runs(sequence), &intervals, sink);
+    }
+
+    fn process_with_composition(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+    ) -> Composition {
+        let mut intervals = Vec::new();
+        let composition = self
+            .masker
+            .process_with_composition(sequence, &mut |start, end| intervals.push((start, end)));
+        Self::merge(&composition::ambiguous_runs(sequence), &intervals, sink);
+        composition
+    }
+
+    fn process_with_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        let mut intervals = Vec::new();
+        self.masker.process_with_interrupt(
+            sequence,
+            &mut |start, end| intervals.push((start, end)),
+            interrupt,
+        )?;
+        Self::merge(&composition::ambiguous_runs(sequence), &intervals, sink);
+        Ok(())
+    }
+
+    fn process_with_composition_and_interrupt(
+        &self,
+        sequence: &[u8],
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<Composition, Cancelled> {
+        let mut intervals = Vec::new();
+        let composition = self.masker.process_with_composition_and_interrupt(
+            sequence,
+            &mut |start, end| intervals.push((start, end)),
+            interrupt,
+        )?;
+        Self::merge(&composition::ambiguous_runs(sequence), &intervals, sink);
+        Ok(composition)
+    }
+
+    /// The runs gathered by the scan of the other masker are merged with its
+    /// regions, so the sequence isn't read again
+    fn process_with_details_and_interrupt(
+        &self,
+        sequence: &[u8],
+        composition: bool,
+        sink: &mut dyn FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        let mut intervals = Vec::new();
+        let details = self.masker.process_with_details_and_interrupt(
+            sequence,
+            composition,
+            &mut |start, end| intervals.push((start, end)),
+            interrupt,
+        )?;
+        Self::merge(&details.ambiguous_runs, &intervals, sink);
+        Ok(details)
+    }
+}
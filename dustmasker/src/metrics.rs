@@ -0,0 +1,227 @@
+//! Complexity metrics of sequences, such as low-complexity regions, and of
+//! sliding windows over them, regardless of whether the windows are masked, to
+//! summarize sequences or to be used as features
+
+use crate::kmers::nucleotide_code;
+use crate::sdust::is_nucleotide;
+use crate::{InputError, MIN_WINDOW_SIZE};
+
+/// The metrics of each window of a sequence, as returned by [`window_metrics`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowMetrics {
+    /// The DUST score of each window: the number of pairs of identical
+    /// triplets divided by the number of triplets minus one, on the scale of
+    /// the level (a tenth of the score threshold), or 0 if the window has fewer
+    /// than two triplets
+    pub dust: Vec<f64>,
+    /// The Shannon entropy, in bits, of the frequencies of A, C, G and T in each
+    /// window, from 0 to 2, or 0 if the window has none of them
+    pub entropy: Vec<f64>,
+    /// The fraction of G and C among the A, C, G and T bases of each window, or
+    /// 0 if the window has none of them
+    pub gc: Vec<f64>,
+}
+
+/// Counts of the bases and triplets within the positions `start..end` of a
+/// sequence
+struct WindowCounts<'a> {
+    sequence: &'a [u8],
+    start: usize,
+    end: usize,
+    bases: [usize; 4],
+    triplets: [usize; 64],
+    n_triplets: usize,
+    /// Number of pairs of identical triplets
+    score: usize,
+}
+
+impl<'a> WindowCounts<'a> {
+    fn new(sequence: &'a [u8]) -> Self {
+        WindowCounts {
+            sequence,
+            start: 0,
+            end: 0,
+            bases: [0; 4],
+            triplets: [0; 64],
+            n_triplets: 0,
+            score: 0,
+        }
+    }
+
+    /// The code of the triplet starting at `position`, if its three bases are
+    /// nucleotides
+    fn triplet(&self, position: usize) -> Option<usize> {
+        let bases = &self.sequence[position..position + 3];
+        bases.iter().all(|&base| is_nucleotide(base)).then(|| {
+            bases.iter().fold(0, |code, &base| {
+                (code << 2) | nucleotide_code(base) as usize
+            })
+        })
+    }
+
+    fn push(&mut self) {
+        let base = self.sequence[self.end];
+        self.end += 1;
+        if is_nucleotide(base) {
+            self.bases[nucleotide_code(base) as usize] += 1;
+        }
+        if self.end - self.start >= 3 {
+            if let Some(triplet) = self.triplet(self.end - 3) {
+                self.score += self.triplets[triplet];
+                self.triplets[triplet] += 1;
+                self.n_triplets += 1;
+            }
+        }
+    }
+
+    fn pop(&mut self) {
+        let base = self.sequence[self.start];
+        if is_nucleotide(base) {
+            self.bases[nucleotide_code(base) as usize] -= 1;
+        }
+        if self.end - self.start >= 3 {
+            if let Some(triplet) = self.triplet(self.start) {
+                self.triplets[triplet] -= 1;
+                self.score -= self.triplets[triplet];
+                self.n_triplets -= 1;
+            }
+        }
+        self.start += 1;
+    }
+
+    /// Move the window to `start..end`, which must not start before the
+    /// current window
+    fn slide(&mut self, start: usize, end: usize) {
+        if start >= self.end {
+            *self = WindowCounts::new(self.sequence);
+            self.start = start;
+            self.end = start;
+        }
+        while self.end < end {
+            self.push();
+        }
+        while self.start < start {
+            self.pop();
+        }
+    }
+
+    fn dust(&self) -> f64 {
+        match self.n_triplets {
+            0 | 1 => 0.0,
+            n_triplets => self.score as f64 / (n_triplets - 1) as f64,
+        }
+    }
+
+    fn entropy(&self) -> f64 {
+        let n_nucleotides: usize = self.bases.iter().sum();
+        self.bases
+            .iter()
+            .filter(|&&count| count > 0)
+            .fold(0.0, |entropy, &count| {
+                let frequency = count as f64 / n_nucleotides as f64;
+                entropy + frequency * (1.0 / frequency).log2()
+            })
+    }
+
+    fn gc(&self) -> f64 {
+        let [a, c, g, t] = self.bases;
+        match a + c + g + t {
+            0 => 0.0,
+            n_nucleotides => (c + g) as f64 / n_nucleotides as f64,
+        }
+    }
+}
+
+/// The complexity metrics of the windows of `window_size` bases of `sequence`
+/// that start every `step` bases, from its first base, until a window would
+/// extend past the end of the sequence. Sequences shorter than the window have
+/// no windows. Characters other than A, C, G and T, in either case, aren't
+/// counted, and neither are the triplets that include them.
+///
+/// ```
+/// use dustmasker::metrics::window_metrics;
+///
+/// let metrics = window_metrics(b"AAAAAAAAACGTACGT", 8, 8).unwrap();
+/// assert_eq!(metrics.dust, [3.0, 0.4]);
+/// assert_eq!(metrics.entropy, [0.0, 2.0]);
+/// ```
+pub fn window_metrics(
+    sequence: &[u8],
+    window_size: usize,
+    step: usize,
+) -> Result<WindowMetrics, InputError> {
+    if window_size < MIN_WINDOW_SIZE {
+        return Err(InputError::WindowSizeError(window_size));
+    }
+    if step == 0 {
+        return Err(InputError::StepError(step));
+    }
+    let n_windows = match sequence.len().checked_sub(window_size) {
+        Some(last_start) => last_start / step + 1,
+        None => 0,
+    };
+    let mut metrics = WindowMetrics {
+        dust: Vec::with_capacity(n_windows),
+        entropy: Vec::with_capacity(n_windows),
+        gc: Vec::with_capacity(n_windows),
+    };
+    let mut counts = WindowCounts::new(sequence);
+    for start in (0..n_windows).map(|i| i * step) {
+        counts.slide(start, start + window_size);
+        metrics.dust.push(counts.dust());
+        metrics.entropy.push(counts.entropy());
+        metrics.gc.push(counts.gc());
+    }
+    Ok(metrics)
+}
+
+/// The DUST score of the whole `sequence`, on the same scale as the scores of
+/// [`WindowMetrics::dust`]
+pub fn dust_score(sequence: &[u8]) -> f64 {
+    let mut counts = WindowCounts::new(sequence);
+    counts.slide(0, sequence.len());
+    counts.dust()
+}
+
+/// Length of the longest unit returned by [`repeat_unit`]
+pub const MAX_UNIT_LENGTH: usize = 6;
+
+/// The unit whose tandem repetition best describes `sequence`, such as a
+/// low-complexity region, or `None` if no unit describes it.
+///
+/// Each period from 1 to [`MAX_UNIT_LENGTH`] bases that is repeated at least
+/// twice in the sequence is scored by the fraction of bases that are equal,
+/// regardless of case, to the base one period before. The unit is the first
+/// period of the sequence, of the length with the highest fraction (the
+/// shortest among ties) if at least 3/4 of the bases match, which tolerates
+/// imperfect repeats. Multiples of a unit longer than one base are not
+/// considered, as they repeat it with fewer mismatches at its edges.
+///
+/// ```
+/// use dustmasker::metrics::repeat_unit;
+///
+/// assert_eq!(repeat_unit(b"CACACACTCACA"), Some(&b"CA"[..]));
+/// assert_eq!(repeat_unit(b"ACGTTGCA"), None);
+/// ```
+pub fn repeat_unit(sequence: &[u8]) -> Option<&[u8]> {
+    let mut best: Option<(usize, usize)> = None;
+    for unit_length in (1..=MAX_UNIT_LENGTH).take_while(|&length| 2 * length <= sequence.len()) {
+        if best.is_some_and(|(best_length, _)| best_length > 1 && unit_length % best_length == 0) {
+            continue;
+        }
+        let n_matches = sequence[unit_length..]
+            .iter()
+            .zip(sequence)
+            .filter(|(a, b)| a.eq_ignore_ascii_case(b))
+            .count();
+        let n_compared = sequence.len() - unit_length;
+        // Compare the fractions of matches without dividing them
+        let is_better = best.is_none_or(|(best_length, best_matches)| {
+            n_matches * (sequence.len() - best_length) > best_matches * n_compared
+        });
+        if 4 * n_matches >= 3 * n_compared && is_better {
+            best = Some((unit_length, n_matches));
+        }
+    }
+    best.map(|(unit_length, _)| &sequence[..unit_length])
+}
@@ -0,0 +1,411 @@
+//! Multi-threaded processing of long sequences
+
+use crate::intervals;
+use crate::sdust::{clip_interval, is_nucleotide, Interval, Processor, SymmetricDust};
+use crate::Cancelled;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Minimum number of bases processed by each chunk of a parallel run
+const MIN_CHUNK_SIZE: usize = 1 << 16;
+/// Time between two calls of the `interrupt` callback of a parallel run
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Identify low-complexity regions in `sequence` using a pool of `n_threads`
+/// threads (0 means one per available core), passing the start and end of
+/// each one of them to `sink`. The output is identical to the one of
+/// `SymmetricDust::process_with`. Sequences are processed in a single thread
+/// on Emscripten targets (e.g., Pyodide), which don't support threads.
+pub fn process_with(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+    n_threads: usize,
+    sink: impl FnMut(usize, usize),
+) {
+    // The scan is never interrupted
+    let _ = process_in_chunks(
+        sequence,
+        window_size,
+        score_threshold,
+        n_threads,
+        MIN_CHUNK_SIZE,
+        sink,
+        None,
+    );
+}
+
+/// Like `process_with`, calling `interrupt` with the number of bases scanned
+/// so far and stopping the scan if it returns true. In a single thread, it is
+/// called every `sdust::INTERRUPT_INTERVAL` bases, like by
+/// `SymmetricDust::process_with_interrupt`. Otherwise, it is called from the
+/// calling thread every 10 milliseconds while the chunks are processed, with
+/// the number of bases scanned by all the threads, which counts the overlaps
+/// of the chunks but never exceeds the length of the sequence, and no region
+/// is passed to `sink` if the scan is stopped.
+pub fn process_with_interrupt(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+    n_threads: usize,
+    sink: impl FnMut(usize, usize),
+    interrupt: &mut dyn FnMut(usize) -> bool,
+) -> Result<(), Cancelled> {
+    process_in_chunks(
+        sequence,
+        window_size,
+        score_threshold,
+        n_threads,
+        MIN_CHUNK_SIZE,
+        sink,
+        Some(interrupt),
+    )
+}
+
+/// Identify the low-complexity regions of each one of `sequences`, such as
+/// many short contigs, distributing the sequences among a pool of `n_threads`
+/// threads (0 means one per available core). Each sequence is processed in a
+/// single thread, with the same output as `SymmetricDust::process_with`, and
+/// the regions of the sequences are returned in their order.
+pub fn process_batch<S: AsRef<[u8]> + Sync>(
+    sequences: &[S],
+    window_size: usize,
+    score_threshold: usize,
+    n_threads: usize,
+) -> Vec<Vec<Interval>> {
+    let process = |sequence: &S| {
+        let mut intervals = Vec::new();
+        SymmetricDust::process_with(
+            sequence.as_ref(),
+            window_size,
+            score_threshold,
+            |start, end| intervals.push((start, end)),
+        );
+        intervals
+    };
+    let n_threads = thread_count(n_threads);
+    if n_threads == 1 {
+        return sequences.iter().map(process).collect();
+    }
+    match thread_pool(n_threads) {
+        Some(pool) => pool.install(|| sequences.par_iter().map(process).collect()),
+        None => sequences.iter().map(process).collect(),
+    }
+}
+
+/// The number of threads of a pool of `n_threads` threads, where 0 means one
+/// per available core. Threads can't be spawned in Pyodide, so everything runs
+/// in the caller there.
+fn thread_count(n_threads: usize) -> usize {
+    if cfg!(target_os = "emscripten") {
+        1
+    } else if n_threads == 0 {
+        rayon::current_num_threads()
+    } else {
+        n_threads
+    }
+}
+
+/// The pool of `n_threads` threads, which is built the first time it is
+/// requested and then shared by all the scans, so that they don't spawn and
+/// join their threads. `None` if the threads can't be spawned.
+fn thread_pool(n_threads: usize) -> Option<Arc<ThreadPool>> {
+    static POOLS: Mutex<BTreeMap<usize, Arc<ThreadPool>>> = Mutex::new(BTreeMap::new());
+    let mut pools = POOLS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(pool) = pools.get(&n_threads) {
+        return Some(Arc::clone(pool));
+    }
+    let pool = Arc::new(
+        ThreadPoolBuilder::new()
+            .num_threads(n_threads)
+            .build()
+            .ok()?,
+    );
+    pools.insert(n_threads, Arc::clone(&pool));
+    Some(pool)
+}
+
+/// The state of the scan at a given position is fully determined by the
+/// `window_size` bases that precede it, as long as they are all unambiguous.
+/// Each chunk is therefore scanned from `2 * window_size` bases before its
+/// start, which reproduces the intervals that the serial scan saves within
+/// the chunk, and until the intervals starting before its end were flushed.
+/// Chunk boundaries are only placed after a long enough run of unambiguous
+/// bases, and the chunk results are stitched by merging overlapping intervals
+/// at the seams, exactly like the serial scan does.
+pub(crate) fn process_in_chunks(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+    n_threads: usize,
+    min_chunk_size: usize,
+    mut sink: impl FnMut(usize, usize),
+    interrupt: Option<&mut dyn FnMut(usize) -> bool>,
+) -> Result<(), Cancelled> {
+    // The window never spans more than the sequence, which also keeps the
+    // overlaps of huge windows from overflowing
+    let window_size = window_size.min(sequence.len());
+    let overlap = 2 * window_size;
+    let n_threads = thread_count(n_threads);
+    // Split into a few chunks per thread to balance sequences with uneven content
+    let chunk_size = std::cmp::max(
+        sequence.len().div_ceil(4 * n_threads),
+        std::cmp::max(min_chunk_size, 4 * overlap),
+    );
+    let boundaries = split_points(sequence, chunk_size, overlap);
+    // Nothing is logged from the worker threads, as the Python bindings may
+    // hold the GIL while they run
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        n_threads,
+        "split {} bases into {} chunks",
+        sequence.len(),
+        boundaries.len() - 1
+    );
+    if n_threads == 1 || boundaries.len() <= 2 {
+        return match interrupt {
+            Some(interrupt) => SymmetricDust::process_with_interrupt(
+                sequence,
+                window_size,
+                score_threshold,
+                sink,
+                interrupt,
+            ),
+            None => {
+                SymmetricDust::process_with(sequence, window_size, score_threshold, sink);
+                Ok(())
+            }
+        };
+    }
+    // Set once `interrupt` returns true, which stops the scan of all chunks
+    let stop = AtomicBool::new(false);
+    // Number of bases scanned by all the chunks, reported to `interrupt`
+    let scanned = AtomicUsize::new(0);
+    let process_chunk = |processor: &mut Processor, chunk: &[usize]| {
+        let scan_start = chunk[0].saturating_sub(overlap);
+        let scan_end = std::cmp::min(chunk[1] + window_size + 1, sequence.len());
+        // Perfect intervals can start past the end of the sequence, these must
+        // be kept by the last chunk
+        let keep = if chunk[1] == sequence.len() {
+            chunk[0]..usize::MAX
+        } else {
+            chunk[0]..chunk[1]
+        };
+        let mut results = Vec::with_capacity(intervals::expected_count(chunk[1] - chunk[0]));
+        let mut position = scan_start;
+        processor.process_range(
+            sequence,
+            window_size,
+            score_threshold,
+            scan_start..scan_end,
+            keep,
+            |interval| results.push(interval),
+            &mut |next_position| {
+                scanned.fetch_add(next_position - position, Ordering::Relaxed);
+                position = next_position;
+                stop.load(Ordering::Relaxed)
+            },
+        )?;
+        scanned.fetch_add(scan_end - position, Ordering::Relaxed);
+        Ok(results)
+    };
+    let process_chunks = || -> Result<Vec<Vec<Range<usize>>>, Cancelled> {
+        match thread_pool(n_threads) {
+            Some(pool) => pool.install(|| {
+                boundaries
+                    .par_windows(2)
+                    .map_init(Processor::new, process_chunk)
+                    .collect()
+            }),
+            None => {
+                let mut processor = Processor::new();
+                boundaries
+                    .windows(2)
+                    .map(|chunk| process_chunk(&mut processor, chunk))
+                    .collect()
+            }
+        }
+    };
+    let chunk_results = match interrupt {
+        None => process_chunks(),
+        // The chunks are processed by another thread, so that the calling
+        // thread, which may be the only one allowed to call `interrupt`, can
+        // call it while they are processed
+        Some(interrupt) => {
+            let caller = std::thread::current();
+            std::thread::scope(|scope| {
+                let worker = scope.spawn(|| {
+                    let chunk_results = process_chunks();
+                    caller.unpark();
+                    chunk_results
+                });
+                while !worker.is_finished() {
+                    let position = scanned.load(Ordering::Relaxed).min(sequence.len());
+                    if !stop.load(Ordering::Relaxed) && interrupt(position) {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    std::thread::park_timeout(POLL_INTERVAL);
+                }
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+        }
+    }?;
+    merge_intervals(chunk_results.into_iter().flatten(), |interval| {
+        let (start, end) = clip_interval(interval, sequence.len());
+        sink(start, end);
+    });
+    Ok(())
+}
+
+/// Find the positions where the sequence can be split into chunks of at least
+/// `chunk_size` bases, each preceded by `overlap` unambiguous bases. The first
+/// and last positions are always the start and the end of the sequence.
+fn split_points(sequence: &[u8], chunk_size: usize, overlap: usize) -> Vec<usize> {
+    let mut boundaries = vec![0];
+    let mut position = chunk_size;
+    while position + chunk_size <= sequence.len() {
+        let mut run_length = 0;
+        let mut i = position - overlap;
+        while i < sequence.len() && run_length < overlap {
+            if is_nucleotide(sequence[i]) {
+                run_length += 1;
+            } else {
+                run_length = 0;
+            }
+            i += 1;
+        }
+        if run_length < overlap || i + chunk_size > sequence.len() {
+            break;
+        }
+        boundaries.push(i);
+        position = i + chunk_size;
+    }
+    boundaries.push(sequence.len());
+    boundaries
+}
+
+/// Merge each interval with the previous one if they overlap, like the scan
+/// does with the perfect intervals, passing the merged intervals to `sink`
+pub(crate) fn merge_intervals(
+    intervals: impl IntoIterator<Item = Range<usize>>,
+    mut sink: impl FnMut(Range<usize>),
+) {
+    let mut last: Option<Range<usize>> = None;
+    for interval in intervals {
+        match &mut last {
+            Some(last) if interval.start <= last.end => {
+                last.end = std::cmp::max(last.end, interval.end);
+            }
+            _ => {
+                if let Some(last) = last.replace(interval) {
+                    sink(last);
+                }
+            }
+        }
+    }
+    if let Some(last) = last {
+        sink(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_sequence, serial_intervals, Rng};
+
+    /// Small chunks put many seams within short sequences
+    const TEST_CHUNK_SIZE: usize = 256;
+
+    fn chunked_intervals(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        interrupt: Option<&mut dyn FnMut(usize) -> bool>,
+    ) -> Result<Vec<Interval>, Cancelled> {
+        let mut intervals = Vec::new();
+        process_in_chunks(
+            sequence,
+            window_size,
+            score_threshold,
+            4,
+            TEST_CHUNK_SIZE,
+            |start, end| intervals.push((start, end)),
+            interrupt,
+        )?;
+        Ok(intervals)
+    }
+
+    #[test]
+    fn chunks_match_the_serial_scan() {
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            let length = 2_000 + rng.below(20_000);
+            let sequence = random_sequence(&mut rng, length);
+            for (window_size, score_threshold) in [(3, 1), (16, 10), (64, 20)] {
+                let expected = serial_intervals(&sequence, window_size, score_threshold);
+                let chunked = chunked_intervals(&sequence, window_size, score_threshold, None);
+                assert_eq!(chunked.unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn interrupted_chunks_match_the_serial_scan() {
+        let mut rng = Rng::new(2);
+        let sequence = random_sequence(&mut rng, 50_000);
+        let mut last_scanned = 0;
+        let mut interrupt = |scanned: usize| {
+            assert!(scanned >= last_scanned && scanned <= sequence.len());
+            last_scanned = scanned;
+            false
+        };
+        let chunked = chunked_intervals(&sequence, 64, 20, Some(&mut interrupt));
+        assert_eq!(chunked.unwrap(), serial_intervals(&sequence, 64, 20));
+        // The callback is called as soon as the chunks start being processed
+        let sequence = random_sequence(&mut rng, 2_000_000);
+        let chunked = chunked_intervals(&sequence, 64, 20, Some(&mut |_| true));
+        assert_eq!(chunked, Err(Cancelled));
+    }
+
+    #[test]
+    fn batches_match_the_serial_scan() {
+        let mut rng = Rng::new(3);
+        let sequences: Vec<Vec<u8>> = (0..100)
+            .map(|_| {
+                let length = 4 + rng.below(2_000);
+                random_sequence(&mut rng, length)
+            })
+            .collect();
+        let expected: Vec<Vec<Interval>> = sequences
+            .iter()
+            .map(|sequence| serial_intervals(sequence, 64, 20))
+            .collect();
+        for n_threads in [1, 3] {
+            assert_eq!(process_batch(&sequences, 64, 20, n_threads), expected);
+        }
+    }
+
+    #[test]
+    fn split_points_follow_unambiguous_runs() {
+        let mut rng = Rng::new(4);
+        let sequence = random_sequence(&mut rng, 100_000);
+        let boundaries = split_points(&sequence, 1_000, 128);
+        assert_eq!(boundaries.first(), Some(&0));
+        assert_eq!(boundaries.last(), Some(&sequence.len()));
+        for pair in boundaries.windows(2) {
+            assert!(pair[1] - pair[0] >= 1_000 || pair[1] == sequence.len());
+        }
+        for &boundary in &boundaries[1..boundaries.len() - 1] {
+            assert!(sequence[boundary - 128..boundary]
+                .iter()
+                .all(|&base| is_nucleotide(base)));
+        }
+    }
+}
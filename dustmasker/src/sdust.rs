@@ -0,0 +1,1121 @@
+//! The symmetric DUST algorithm
+//!
+//! Code adapted from: <https://crates.io/crates/sdust>
+
+use crate::alphabet::Alphabet;
+use crate::composition::{AmbiguousRuns, Composition, ScanDetails};
+use crate::Cancelled;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+
+/// Default length of the k-mers compared by the algorithm, the triplets of the
+/// paper
+pub const DEFAULT_KMER_SIZE: usize = 3;
+/// Smallest supported k-mer size
+pub const MIN_KMER_SIZE: usize = 2;
+/// Largest supported k-mer size, so that the k-mers of the largest alphabet fit
+/// in 16 bits
+pub const MAX_KMER_SIZE: usize = 5;
+/// Number of bases scanned between two calls of the `interrupt` callbacks,
+/// which takes a few milliseconds
+pub const INTERRUPT_INTERVAL: usize = 1 << 20;
+/// Number of bases encoded per batch before being fed to the scan
+pub(crate) const ENCODING_BLOCK_SIZE: usize = 64;
+/// Initial capacity of the ring buffer of the window, in triplets
+const MIN_WINDOW_CAPACITY: usize = 64;
+/// Code assigned to any character outside the alphabet (by default, other than
+/// A/C/G/T in either case)
+pub(crate) const AMBIGUOUS: u8 = u8::MAX;
+
+/// Whether `base` is A, C, G or T (in either case)
+#[inline]
+pub(crate) fn is_nucleotide(base: u8) -> bool {
+    let lower = base | 0x20;
+    (lower == b'a') | (lower == b'c') | (lower == b'g') | (lower == b't')
+}
+
+/// Encode `sequence` into `codes`, mapping A/C/G/T (in either case) to a 2-bit
+/// code and everything else to `AMBIGUOUS`.
+///
+/// The code of a base is taken from bits 1-2 of its ASCII value (A=0, C=1, T=2,
+/// G=3), which is unique for the four nucleotides. The algorithm only compares
+/// triplets for equality, so the particular assignment doesn't matter. The loop
+/// is branch-free and lookup-free so that it is auto-vectorized (SSE2/AVX2 on
+/// x86_64, NEON on aarch64), while remaining plain scalar code elsewhere.
+#[inline]
+pub(crate) fn encode(sequence: &[u8], codes: &mut [u8]) {
+    for (code, &base) in codes.iter_mut().zip(sequence) {
+        *code = if is_nucleotide(base) {
+            ((base | 0x20) >> 1) & 3
+        } else {
+            AMBIGUOUS
+        };
+    }
+}
+
+/// Read `slice[index]`. With the `unchecked` feature, the bounds check is only
+/// performed in debug builds.
+///
+/// All the indices used in the scan are in bounds by construction: triplets
+/// are built from codes smaller than the alphabet size, so they are smaller than
+/// the number of counts, and ring buffer positions are masked to its
+/// power-of-two capacity.
+#[inline(always)]
+fn at<T: Copy>(slice: &[T], index: usize) -> T {
+    #[cfg(feature = "unchecked")]
+    {
+        debug_assert!(index < slice.len());
+        // SAFETY: callers only pass indices that are in bounds (see above)
+        unsafe { *slice.get_unchecked(index) }
+    }
+    #[cfg(not(feature = "unchecked"))]
+    slice[index]
+}
+
+/// Mutable counterpart of `at`
+#[inline(always)]
+fn at_mut<T>(slice: &mut [T], index: usize) -> &mut T {
+    #[cfg(feature = "unchecked")]
+    {
+        debug_assert!(index < slice.len());
+        // SAFETY: callers only pass indices that are in bounds (see `at`)
+        unsafe { slice.get_unchecked_mut(index) }
+    }
+    #[cfg(not(feature = "unchecked"))]
+    &mut slice[index]
+}
+
+/// The triplets of the current window, stored in a ring buffer whose capacity
+/// is a power of two so that positions wrap around with a bit mask. The buffer
+/// doubles when it is full, so it never holds more than twice the triplets of
+/// the window (at most `window_size - k + 1`), which may be much fewer than the
+/// window size in short sequences. Triplets are k-mers of up to
+/// `MAX_KMER_SIZE` codes, stored in 16 bits each.
+#[derive(Debug, Default)]
+struct TripletWindow {
+    triplets: Vec<u16>,
+    /// Position of the oldest triplet in `triplets`
+    head: usize,
+    len: usize,
+}
+
+impl TripletWindow {
+    /// Empty the window, keeping the buffer for the next scan
+    fn reset(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Double the capacity of the buffer, moving the triplets to its start
+    #[cold]
+    fn grow(&mut self) {
+        let capacity = (2 * self.triplets.len()).max(MIN_WINDOW_CAPACITY);
+        let mut triplets = Vec::with_capacity(capacity);
+        triplets.extend((0..self.len).map(|index| self.get(index) as u16));
+        triplets.resize(capacity, 0);
+        self.triplets = triplets;
+        self.head = 0;
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn mask(&self) -> usize {
+        self.triplets.len() - 1
+    }
+
+    #[inline]
+    fn push_back(&mut self, triplet: usize) {
+        if self.len == self.triplets.len() {
+            self.grow();
+        }
+        let position = (self.head + self.len) & self.mask();
+        *at_mut(&mut self.triplets, position) = triplet as u16;
+        self.len += 1;
+    }
+
+    /// Remove the oldest triplet, which must exist
+    #[inline]
+    fn pop_front(&mut self) -> usize {
+        let triplet = at(&self.triplets, self.head) as usize;
+        self.head = (self.head + 1) & self.mask();
+        self.len -= 1;
+        triplet
+    }
+
+    /// The `index`-th oldest triplet
+    #[inline]
+    fn get(&self, index: usize) -> usize {
+        at(&self.triplets, (self.head + index) & self.mask()) as usize
+    }
+}
+
+/// Number of occurrences of a triplet in the window. The window holds fewer
+/// than `window_size` triplets, so 32 bits are enough for any practical window
+/// size, and the count arrays used by the hot loops fit in fewer cache lines.
+type Count = u32;
+
+/// Counts of the triplets of the window, sized for the alphabet and k-mer size
+/// of the scan, along with the table used to build the triplets
+#[derive(Debug, Default)]
+struct TripletCounts {
+    /// The triplet made of the last `k - 1` codes of each triplet followed by
+    /// a code 0, so that the next triplet is found by adding the next code
+    prefixes: Vec<u16>,
+    /// `c_v` in the paper
+    cv: Vec<Count>,
+    /// `c_w` in the paper
+    cw: Vec<Count>,
+    /// Copy of `cv` updated by `find_perfect`
+    scratch: Vec<Count>,
+}
+
+impl TripletCounts {
+    /// Zero the counts of the triplets of `kmer_size` codes of an alphabet of
+    /// `alphabet_size` symbols
+    fn reset(&mut self, alphabet_size: usize, kmer_size: usize) {
+        let num_triplets = alphabet_size.pow(kmer_size as u32);
+        let num_prefixes = alphabet_size.pow(kmer_size as u32 - 1);
+        self.prefixes.clear();
+        self.prefixes.extend(
+            (0..num_triplets).map(|triplet| (triplet % num_prefixes * alphabet_size) as u16),
+        );
+        for counts in [&mut self.cv, &mut self.cw, &mut self.scratch] {
+            counts.clear();
+            counts.resize(num_triplets, 0);
+        }
+    }
+}
+
+/// A perfect interval, as defined in the paper: a region whose score is above
+/// the threshold and at least as high as the one of any region it contains.
+/// Low-complexity regions are the union of the perfect intervals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerfectInterval {
+    pub start: usize,
+    /// The end of the interval, which can be past the end of the sequence
+    pub finish: usize,
+    /// Number of pairs of identical triplets in the interval
+    pub score: usize,
+    /// Number of triplets in the interval minus one, so that its score is
+    /// `score / l`
+    pub l: usize,
+}
+
+/// The state of the window after a base was added to it, reported by
+/// `SymmetricDust::trace_window`. The names follow the paper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowState {
+    /// Position of the base that was added
+    pub position: usize,
+    /// Number of triplets in the window
+    pub n_triplets: usize,
+    /// Score of the whole window, `rw` in the paper
+    pub rw: usize,
+    /// Score of the suffix of the window without repeated triplets beyond the
+    /// threshold, `rv` in the paper
+    pub rv: usize,
+    /// Number of triplets in that suffix, `L` in the paper
+    pub l: usize,
+    /// Whether the window score was high enough to look for perfect intervals
+    pub triggered: bool,
+}
+
+/// A score threshold that replaces the default one of a scan for the windows
+/// whose last base is within the bases from `start` to `end` (e.g., stricter
+/// thresholds for pericentromeric regions). Perfect intervals are scored with
+/// the threshold of the window they are found in, so the regions that span
+/// the bounds of an override depend on the thresholds on both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThresholdOverride {
+    pub start: usize,
+    pub end: usize,
+    /// The score threshold used within the region
+    pub score_threshold: usize,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// Buffers reused by the `SymmetricDust::process_with` calls of each thread
+    static PROCESSOR: RefCell<Processor> = RefCell::new(Processor::new());
+}
+
+/// Run `f` with the buffers of the current thread, or with new ones if they are
+/// already in use by an outer scan (e.g., when a sink creates another masker)
+#[cfg(feature = "std")]
+fn with_processor<R>(f: impl FnOnce(&mut Processor) -> R) -> R {
+    PROCESSOR.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut processor) => f(&mut processor),
+        Err(_) => f(&mut Processor::new()),
+    })
+}
+
+/// Owns the buffers used by the symmetric DUST scan, so that they can be reused
+/// across sequences instead of being allocated for each one of them.
+#[derive(Debug, Default)]
+pub struct Processor {
+    perfect_intervals: VecDeque<PerfectInterval>,
+    window: TripletWindow,
+    counts: TripletCounts,
+}
+
+impl Processor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Identify the low-complexity regions in `sequence`, passing the start and
+    /// end of each one of them to `sink` as soon as it is found. The intervals
+    /// are emitted in order and are never stored, so memory usage doesn't
+    /// depend on their number.
+    pub fn process_with(
+        &mut self,
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        sink: impl FnMut(usize, usize),
+    ) {
+        // The scan is never interrupted
+        let _ =
+            self.process_with_interrupt(sequence, window_size, score_threshold, sink, &mut |_| {
+                false
+            });
+    }
+
+    /// Like `process_with`, calling `interrupt` with the number of bases
+    /// scanned so far every `INTERRUPT_INTERVAL` bases, and stopping the scan
+    /// if it returns true. The regions that were passed to `sink` until then
+    /// are final, but the remaining ones are never found.
+    pub fn process_with_interrupt(
+        &mut self,
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        mut sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        self.process_range(
+            sequence,
+            window_size,
+            score_threshold,
+            0..sequence.len(),
+            0..usize::MAX,
+            |interval| {
+                let (start, end) = clip_interval(interval, sequence.len());
+                sink(start, end)
+            },
+            interrupt,
+        )
+    }
+
+    /// Scan `sequence[scan]`, starting from an empty state at `scan.start`, and
+    /// pass to `sink` the merged intervals built from the perfect intervals that
+    /// start within `keep`. The positions are relative to the whole `sequence`,
+    /// and the remaining perfect intervals are only flushed if the scan reaches
+    /// its end. `interrupt` is called with the position of the next base to
+    /// scan every `INTERRUPT_INTERVAL` bases, and the scan stops if it returns
+    /// true.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn process_range(
+        &mut self,
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        scan: Range<usize>,
+        keep: Range<usize>,
+        mut sink: impl FnMut(Range<usize>),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        let mut obj = self.take_buffers(window_size, score_threshold, keep);
+        let result =
+            obj.feed_with_interrupt(scan.start, &sequence[scan.clone()], &mut sink, interrupt);
+        if result.is_ok() {
+            if scan.end == sequence.len() {
+                obj.finish(sequence.len(), &mut sink);
+            } else if let Some(last_res) = obj.last_result.take() {
+                sink(last_res);
+            }
+        }
+        self.restore_buffers(obj);
+        result
+    }
+
+    /// Like `process_with_interrupt`, also returning the runs of ambiguous
+    /// characters of `sequence` and, if `composition` is true, its composition,
+    /// which are gathered while it is scanned
+    pub fn process_with_details_and_interrupt(
+        &mut self,
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        composition: bool,
+        sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        let mut obj = self.take_buffers(window_size, score_threshold, 0..usize::MAX);
+        let result = obj.scan_with_details_and_interrupt(sequence, sink, interrupt, composition);
+        self.restore_buffers(obj);
+        result
+    }
+
+    /// The state of a new scan, which borrows the buffers until they are
+    /// given back with `restore_buffers`
+    fn take_buffers(
+        &mut self,
+        window_size: usize,
+        score_threshold: usize,
+        keep: Range<usize>,
+    ) -> SymmetricDust {
+        self.perfect_intervals.clear();
+        SymmetricDust::with_buffers(
+            window_size,
+            score_threshold,
+            keep,
+            core::mem::take(&mut self.perfect_intervals),
+            core::mem::take(&mut self.window),
+            core::mem::take(&mut self.counts),
+        )
+    }
+
+    fn restore_buffers(&mut self, obj: SymmetricDust) {
+        self.perfect_intervals = obj.perfect_intervals;
+        self.window = obj.window;
+        self.counts = obj.counts;
+    }
+}
+
+#[derive(Debug)]
+pub struct SymmetricDust {
+    /// The length of the window used by symmetric DUST algorithm
+    /// `W` in the paper
+    window_size: usize,
+    /// 10 times the score threshold used by symmetric DUST algorithm.
+    /// `T` in the paper
+    score_threshold: usize,
+    /// Only perfect intervals starting within this range are saved
+    keep: Range<usize>,
+    /// `P` in the paper
+    perfect_intervals: VecDeque<PerfectInterval>,
+    /// The last element of `res` in the paper, which can still be extended
+    last_result: Option<Range<usize>>,
+    /// The perfect intervals merged into `res`, if they are recorded
+    saved_perfect_intervals: Option<Vec<PerfectInterval>>,
+    /// The states of the window since the last time they were reported, if
+    /// they are recorded
+    window_states: Option<Vec<WindowState>>,
+    /// The composition of the bases fed so far, if it is gathered
+    composition: Option<Composition>,
+    /// The runs of ambiguous characters fed so far, if they are gathered
+    ambiguous_runs: Option<AmbiguousRuns>,
+    /// The positions from which the score threshold changes, with the new
+    /// threshold, sorted by decreasing position so that the next change is the
+    /// last one
+    threshold_changes: Vec<(usize, usize)>,
+    /// `w` in the paper
+    window: TripletWindow,
+    /// The alphabet of the sequence, if it isn't the default one
+    alphabet: Option<Alphabet>,
+    /// The number of codes of each triplet, `k`
+    kmer_size: usize,
+    // counts in the current window
+    counts: TripletCounts,
+    // runnings counts
+    rv: usize,
+    rw: usize,
+    /// `L` in the paper
+    biggest_num_triplets: usize,
+    /// Number of consecutive unambiguous bases read so far
+    l: usize,
+    /// The triplet made of the last `k` codes read
+    triplet: u16,
+}
+
+impl SymmetricDust {
+    /// Identify the low-complexity regions in `sequence`, passing the start and
+    /// end of each one of them to `sink` (see `Processor::process_with`). With
+    /// the `std` feature, the buffers of previous calls made from the same
+    /// thread are reused.
+    pub fn process_with(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        sink: impl FnMut(usize, usize),
+    ) {
+        #[cfg(feature = "std")]
+        with_processor(|processor| {
+            processor.process_with(sequence, window_size, score_threshold, sink)
+        });
+        #[cfg(not(feature = "std"))]
+        Processor::new().process_with(sequence, window_size, score_threshold, sink);
+    }
+
+    /// Like `process_with`, for a sequence whose characters are mapped to the
+    /// symbols of `alphabet` instead of A, C, G and T
+    pub fn process_with_alphabet(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        alphabet: &Alphabet,
+        sink: impl FnMut(usize, usize),
+    ) {
+        Self::process_with_kmers(
+            sequence,
+            window_size,
+            score_threshold,
+            alphabet,
+            DEFAULT_KMER_SIZE,
+            sink,
+        );
+    }
+
+    /// Like `process_with`, calling `interrupt` with the number of bases
+    /// scanned so far every `INTERRUPT_INTERVAL` bases, and stopping the scan
+    /// if it returns true (see `Processor::process_with_interrupt`)
+    pub fn process_with_interrupt(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        #[cfg(feature = "std")]
+        return with_processor(|processor| {
+            processor.process_with_interrupt(
+                sequence,
+                window_size,
+                score_threshold,
+                sink,
+                interrupt,
+            )
+        });
+        #[cfg(not(feature = "std"))]
+        Processor::new().process_with_interrupt(
+            sequence,
+            window_size,
+            score_threshold,
+            sink,
+            interrupt,
+        )
+    }
+
+    /// Like `process_with_alphabet`, comparing k-mers of `kmer_size` symbols
+    /// instead of triplets. `kmer_size` must be between `MIN_KMER_SIZE` and
+    /// `MAX_KMER_SIZE`, and at most `window_size`.
+    pub fn process_with_kmers(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        alphabet: &Alphabet,
+        kmer_size: usize,
+        sink: impl FnMut(usize, usize),
+    ) {
+        let mut dust = SymmetricDust::new(window_size, score_threshold);
+        dust.set_encoding(Some(alphabet.clone()), kmer_size);
+        dust.scan(sequence, sink);
+    }
+
+    /// Like `process_with`, also returning the composition of `sequence`,
+    /// which is gathered while it is scanned instead of reading it again
+    pub fn process_with_composition(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        sink: impl FnMut(usize, usize),
+    ) -> Composition {
+        SymmetricDust::new(window_size, score_threshold).scan_with_composition(sequence, sink)
+    }
+
+    /// Like `process_with_interrupt`, also returning the runs of ambiguous
+    /// characters of `sequence` and, if `composition` is true, its composition,
+    /// which are gathered while it is scanned (see
+    /// `Processor::process_with_details_and_interrupt`)
+    pub fn process_with_details_and_interrupt(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        composition: bool,
+        sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        #[cfg(feature = "std")]
+        return with_processor(|processor| {
+            processor.process_with_details_and_interrupt(
+                sequence,
+                window_size,
+                score_threshold,
+                composition,
+                sink,
+                interrupt,
+            )
+        });
+        #[cfg(not(feature = "std"))]
+        Processor::new().process_with_details_and_interrupt(
+            sequence,
+            window_size,
+            score_threshold,
+            composition,
+            sink,
+            interrupt,
+        )
+    }
+
+    /// Like `process_with`, using the score threshold of `overrides` instead of
+    /// `score_threshold` within their regions, in a single pass. The overrides
+    /// must not overlap (see `validate_threshold_overrides`).
+    pub fn process_with_overrides(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        overrides: &[ThresholdOverride],
+        sink: impl FnMut(usize, usize),
+    ) {
+        let mut dust = SymmetricDust::new(window_size, score_threshold);
+        dust.set_threshold_overrides(overrides);
+        dust.scan(sequence, sink);
+    }
+
+    /// Iterate over the low-complexity regions in `sequence`. The sequence is
+    /// scanned lazily, as the regions are requested, so the iteration can be
+    /// stopped early without scanning the rest of the sequence and without
+    /// storing the regions.
+    pub fn intervals(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+    ) -> IntervalIter<'_> {
+        IntervalIter {
+            sequence,
+            position: 0,
+            finished: false,
+            dust: SymmetricDust::new(window_size, score_threshold),
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// The perfect intervals in `sequence` that are merged into the
+    /// low-complexity regions, in order of start position. The union of each
+    /// run of overlapping or adjacent perfect intervals is one of the regions returned by
+    /// `process_with`, before being clipped to the end of the sequence.
+    pub fn perfect_intervals(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+    ) -> Vec<PerfectInterval> {
+        let mut dust = SymmetricDust::new(window_size, score_threshold);
+        dust.saved_perfect_intervals = Some(Vec::new());
+        dust.feed(0, sequence, &mut |_| {});
+        dust.finish(sequence.len(), &mut |_| {});
+        dust.saved_perfect_intervals.unwrap_or_default()
+    }
+
+    /// Scan `sequence`, passing to `callback` the state of the window after each
+    /// base is added to it, to compare the scan with other implementations of
+    /// the algorithm. Ambiguous bases and the k - 1 bases that follow them aren't
+    /// added to the window, so no state is reported for them.
+    pub fn trace_window(
+        sequence: &[u8],
+        window_size: usize,
+        score_threshold: usize,
+        mut callback: impl FnMut(&WindowState),
+    ) {
+        let mut dust = SymmetricDust::new(window_size, score_threshold);
+        dust.window_states = Some(Vec::with_capacity(ENCODING_BLOCK_SIZE));
+        // The states are reported after each block, so that they are never all
+        // stored at once
+        for (block_index, block) in sequence.chunks(ENCODING_BLOCK_SIZE).enumerate() {
+            dust.feed(block_index * ENCODING_BLOCK_SIZE, block, &mut |_| {});
+            if let Some(states) = &mut dust.window_states {
+                states.drain(..).for_each(|state| callback(&state));
+            }
+        }
+    }
+
+    /// Create the state of a scan of a whole sequence, which can then be fed
+    /// with successive chunks of it
+    pub(crate) fn new(window_size: usize, score_threshold: usize) -> Self {
+        Self::with_buffers(
+            window_size,
+            score_threshold,
+            0..usize::MAX,
+            VecDeque::new(),
+            TripletWindow::default(),
+            TripletCounts::default(),
+        )
+    }
+
+    fn with_buffers(
+        window_size: usize,
+        score_threshold: usize,
+        keep: Range<usize>,
+        perfect_intervals: VecDeque<PerfectInterval>,
+        mut window: TripletWindow,
+        mut counts: TripletCounts,
+    ) -> Self {
+        window.reset();
+        counts.reset(4, DEFAULT_KMER_SIZE);
+        SymmetricDust {
+            window_size,
+            score_threshold,
+            keep,
+            perfect_intervals,
+            last_result: None,
+            saved_perfect_intervals: None,
+            window_states: None,
+            composition: None,
+            ambiguous_runs: None,
+            threshold_changes: Vec::new(),
+            window,
+            alphabet: None,
+            kmer_size: DEFAULT_KMER_SIZE,
+            counts,
+            rv: 0,
+            rw: 0,
+            biggest_num_triplets: 0,
+            l: 0,
+            triplet: 0,
+        }
+    }
+
+    /// Number of bytes allocated on the heap by the buffers of the scan
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        fn bytes<T>(capacity: usize) -> usize {
+            capacity * size_of::<T>()
+        }
+        let counts = &self.counts;
+        bytes::<PerfectInterval>(self.perfect_intervals.capacity())
+            + self
+                .saved_perfect_intervals
+                .as_ref()
+                .map_or(0, |saved| bytes::<PerfectInterval>(saved.capacity()))
+            + self
+                .window_states
+                .as_ref()
+                .map_or(0, |states| bytes::<WindowState>(states.capacity()))
+            + self
+                .ambiguous_runs
+                .as_ref()
+                .map_or(0, AmbiguousRuns::allocated_bytes)
+            + bytes::<(usize, usize)>(self.threshold_changes.capacity())
+            + bytes::<u16>(self.window.triplets.capacity())
+            + bytes::<u16>(counts.prefixes.capacity())
+            + bytes::<Count>(
+                counts.cv.capacity() + counts.cw.capacity() + counts.scratch.capacity(),
+            )
+    }
+
+    /// Map the characters of the sequence to the symbols of `alphabet`, or to
+    /// A, C, G and T if it is `None`, and compare k-mers of `kmer_size` symbols.
+    /// This must be done before the scan starts.
+    pub(crate) fn set_encoding(&mut self, alphabet: Option<Alphabet>, kmer_size: usize) {
+        let alphabet_size = alphabet.as_ref().map_or(4, Alphabet::size);
+        self.counts.reset(alphabet_size, kmer_size);
+        self.kmer_size = kmer_size;
+        self.alphabet = alphabet;
+    }
+
+    /// Use the score threshold of `overrides` within their regions, which must
+    /// not overlap. This must be done before the scan starts.
+    pub(crate) fn set_threshold_overrides(&mut self, overrides: &[ThresholdOverride]) {
+        let mut overrides = overrides.to_vec();
+        overrides.sort_unstable_by_key(|threshold_override| threshold_override.start);
+        let default = self.score_threshold;
+        self.threshold_changes.clear();
+        for threshold_override in overrides {
+            self.threshold_changes
+                .push((threshold_override.start, threshold_override.score_threshold));
+            self.threshold_changes
+                .push((threshold_override.end, default));
+        }
+        self.threshold_changes.reverse();
+    }
+
+    /// Scan the whole `sequence`, passing the clipped regions to `sink`
+    pub(crate) fn scan(&mut self, sequence: &[u8], sink: impl FnMut(usize, usize)) {
+        // The scan is never interrupted
+        let _ = self.scan_with_interrupt(sequence, sink, &mut |_| false);
+    }
+
+    /// Like `scan`, calling `interrupt` with the number of bases scanned so far
+    /// every `INTERRUPT_INTERVAL` bases, and stopping the scan if it returns
+    /// true
+    pub(crate) fn scan_with_interrupt(
+        &mut self,
+        sequence: &[u8],
+        mut sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        let mut sink_interval = |interval| {
+            let (start, end) = clip_interval(interval, sequence.len());
+            sink(start, end)
+        };
+        self.feed_with_interrupt(0, sequence, &mut sink_interval, interrupt)?;
+        self.finish(sequence.len(), &mut sink_interval);
+        Ok(())
+    }
+
+    /// Like `scan`, also gathering the composition of `sequence` as it is
+    /// scanned
+    pub(crate) fn scan_with_composition(
+        &mut self,
+        sequence: &[u8],
+        sink: impl FnMut(usize, usize),
+    ) -> Composition {
+        // The scan is never interrupted
+        self.scan_with_composition_and_interrupt(sequence, sink, &mut |_| false)
+            .unwrap_or_default()
+    }
+
+    /// Like `scan_with_composition`, calling `interrupt` like
+    /// `scan_with_interrupt`
+    pub(crate) fn scan_with_composition_and_interrupt(
+        &mut self,
+        sequence: &[u8],
+        sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<Composition, Cancelled> {
+        self.composition = Some(Composition::default());
+        self.scan_with_interrupt(sequence, sink, interrupt)?;
+        Ok(self.composition.take().unwrap_or_default())
+    }
+
+    /// Like `scan_with_interrupt`, also gathering the runs of ambiguous
+    /// characters of `sequence` and, if `composition` is true, its composition
+    /// as it is scanned
+    pub(crate) fn scan_with_details_and_interrupt(
+        &mut self,
+        sequence: &[u8],
+        sink: impl FnMut(usize, usize),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+        composition: bool,
+    ) -> Result<ScanDetails, Cancelled> {
+        self.composition = composition.then(Composition::default);
+        self.ambiguous_runs = Some(AmbiguousRuns::default());
+        self.scan_with_interrupt(sequence, sink, interrupt)?;
+        Ok(ScanDetails {
+            ambiguous_runs: self
+                .ambiguous_runs
+                .take()
+                .map(AmbiguousRuns::into_runs)
+                .unwrap_or_default(),
+            composition: self.composition.take(),
+        })
+    }
+
+    /// Scan `chunk`, whose first base is found at position `start` of the
+    /// sequence, passing to `sink` the elements of `res` that become final
+    pub(crate) fn feed(&mut self, start: usize, chunk: &[u8], sink: &mut dyn FnMut(Range<usize>)) {
+        let mut codes = [AMBIGUOUS; ENCODING_BLOCK_SIZE];
+        for (block_index, block) in chunk.chunks(ENCODING_BLOCK_SIZE).enumerate() {
+            if let Some(composition) = &mut self.composition {
+                composition.update(block);
+            }
+            if let Some(runs) = &mut self.ambiguous_runs {
+                runs.update(block);
+            }
+            match &self.alphabet {
+                None => encode(block, &mut codes),
+                Some(alphabet) => alphabet.encode(block, &mut codes),
+            }
+            let block_start = start + block_index * ENCODING_BLOCK_SIZE;
+            self.feed_codes(block_start, &codes[..block.len()], sink);
+        }
+    }
+
+    /// Like `feed`, calling `interrupt` with the position of the next base
+    /// before each `INTERRUPT_INTERVAL` bases, and stopping if it returns true
+    fn feed_with_interrupt(
+        &mut self,
+        start: usize,
+        chunk: &[u8],
+        sink: &mut dyn FnMut(Range<usize>),
+        interrupt: &mut dyn FnMut(usize) -> bool,
+    ) -> Result<(), Cancelled> {
+        for (index, part) in chunk.chunks(INTERRUPT_INTERVAL).enumerate() {
+            let part_start = start + index * INTERRUPT_INTERVAL;
+            if interrupt(part_start) {
+                return Err(Cancelled);
+            }
+            self.feed(part_start, part, sink);
+        }
+        Ok(())
+    }
+
+    /// Like `feed`, but for bases that were already encoded as 2-bit codes, with
+    /// `AMBIGUOUS` marking any other character
+    pub(crate) fn feed_codes(
+        &mut self,
+        start: usize,
+        codes: &[u8],
+        sink: &mut dyn FnMut(Range<usize>),
+    ) {
+        for (offset, &b) in codes.iter().enumerate() {
+            self.step(start + offset, b, sink);
+        }
+    }
+
+    /// Handle the end of the sequence, found at position `end`, and pass the
+    /// remaining elements of `res` to `sink`
+    pub(crate) fn finish(&mut self, end: usize, sink: &mut dyn FnMut(Range<usize>)) {
+        // The end of the sequence is handled like an ambiguous base
+        self.step(end, AMBIGUOUS, sink);
+        if let Some(last_res) = self.last_result.take() {
+            sink(last_res);
+        }
+    }
+
+    /// Process the encoded base `b` found at position `i` of the sequence
+    fn step(&mut self, i: usize, b: u8, sink: &mut dyn FnMut(Range<usize>)) {
+        while let Some(&(position, score_threshold)) = self.threshold_changes.last() {
+            if position > i {
+                break;
+            }
+            self.score_threshold = score_threshold;
+            self.threshold_changes.pop();
+        }
+        // A/T/C/G
+        if b != AMBIGUOUS {
+            self.l += 1;
+            self.triplet = at(&self.counts.prefixes, self.triplet as usize) + b as u16;
+
+            // We have at least k chars, we can look at them
+            if self.l >= self.kmer_size {
+                let window_start = self.l.saturating_sub(self.window_size) + i + 1 - self.l;
+
+                self.save_masked_regions(window_start, sink);
+                self.shift_window(self.triplet as usize);
+                let triggered = self.rw * 10 > self.biggest_num_triplets * self.score_threshold;
+                if triggered {
+                    self.find_perfect(window_start);
+                }
+                if let Some(states) = &mut self.window_states {
+                    states.push(WindowState {
+                        position: i,
+                        n_triplets: self.window.len(),
+                        rw: self.rw,
+                        rv: self.rv,
+                        l: self.biggest_num_triplets,
+                        triggered,
+                    });
+                }
+            }
+        } else {
+            // A `N` resets the sequence
+            // When we are there (N or end of seq), we empty the intervals found so far
+            let mut window_start = (self.l + 1).saturating_sub(self.window_size) + i + 1 - self.l;
+            while !self.perfect_intervals.is_empty() {
+                window_start += 1;
+                self.save_masked_regions(window_start, sink);
+            }
+
+            self.l = 0;
+            self.triplet = 0;
+        }
+    }
+
+    /// Save all the intervals that are before the `window_start`
+    /// This can only insert one result at a time
+    fn save_masked_regions(&mut self, window_start: usize, sink: &mut dyn FnMut(Range<usize>)) {
+        if self.perfect_intervals.is_empty() {
+            return;
+        }
+
+        let back = self.perfect_intervals.back().unwrap();
+        if back.start >= window_start {
+            return;
+        }
+
+        if !self.keep.contains(&back.start) {
+            self.drop_masked_regions(window_start);
+            return;
+        }
+
+        if let Some(saved) = &mut self.saved_perfect_intervals {
+            saved.push(*back);
+        }
+
+        // If we already have a result, see if we can merge the last perfect interval with it
+        // if they are overlapping
+        match &mut self.last_result {
+            Some(last_res) if back.start <= last_res.end => {
+                last_res.end = core::cmp::max(last_res.end, back.finish);
+            }
+            _ => {
+                if let Some(last_res) = self.last_result.replace(back.start..back.finish) {
+                    sink(last_res);
+                }
+            }
+        }
+
+        self.drop_masked_regions(window_start);
+    }
+
+    /// Remove all the perfect intervals that are before the `window_start`
+    fn drop_masked_regions(&mut self, window_start: usize) {
+        while let Some(b) = self.perfect_intervals.back() {
+            if b.start < window_start {
+                self.perfect_intervals.pop_back();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Add a triplet to the window, shifting all the data to represent the new window
+    fn shift_window(&mut self, triplet: usize) {
+        let mut s;
+        if self.window.len() > self.window_size - self.kmer_size {
+            s = self.window.pop_front();
+            *at_mut(&mut self.counts.cw, s) -= 1;
+            self.rw -= at(&self.counts.cw, s) as usize;
+            if self.biggest_num_triplets > self.window.len() {
+                self.biggest_num_triplets -= 1;
+                *at_mut(&mut self.counts.cv, s) -= 1;
+                self.rv -= at(&self.counts.cv, s) as usize;
+            }
+        }
+
+        self.window.push_back(triplet);
+        self.biggest_num_triplets += 1;
+
+        self.rw += at(&self.counts.cw, triplet) as usize;
+        *at_mut(&mut self.counts.cw, triplet) += 1;
+        self.rv += at(&self.counts.cv, triplet) as usize;
+        *at_mut(&mut self.counts.cv, triplet) += 1;
+
+        if at(&self.counts.cv, triplet) as usize * 10 > 2 * self.score_threshold {
+            loop {
+                s = self
+                    .window
+                    .get(self.window.len() - self.biggest_num_triplets);
+                self.biggest_num_triplets -= 1;
+                *at_mut(&mut self.counts.cv, s) -= 1;
+                self.rv -= at(&self.counts.cv, s) as usize;
+
+                if s == triplet {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Find all the perfect intervals in the window
+    fn find_perfect(&mut self, window_start: usize) {
+        let c = &mut self.counts.scratch;
+        c.copy_from_slice(&self.counts.cv);
+        let mut r = self.rv;
+        let mut max_score = 0;
+        let mut max_l = 0;
+        let mut insertion_position = 0;
+
+        for i in (0..=self.window.len() - self.biggest_num_triplets - 1).rev() {
+            let triplet = self.window.get(i);
+            r += at(c, triplet) as usize;
+            *at_mut(c, triplet) += 1;
+            let new_score = r;
+            let new_l = self.window.len() - i - 1;
+            if new_score * 10 > self.score_threshold * new_l {
+                // Figure out where to insert the new interval. The intervals are
+                // sorted by decreasing start and the start of the new interval
+                // only decreases as we go, so the ones that were already seen in
+                // a previous iteration can be skipped.
+                while let Some(interval) = self.perfect_intervals.get(insertion_position) {
+                    if interval.start < i + window_start {
+                        break;
+                    }
+                    insertion_position += 1;
+                    if max_score == 0 || interval.score * max_l > max_score * interval.l {
+                        max_score = interval.score;
+                        max_l = interval.l;
+                    }
+                }
+
+                // And insert it
+                if max_score == 0 || new_score * max_l >= max_score * new_l {
+                    max_score = new_score;
+                    max_l = new_l;
+                    let new_perf = PerfectInterval {
+                        start: i + window_start,
+                        // The last triplet ends k - 1 bases after its start
+                        finish: self.window.len() + self.kmer_size - 1 + window_start,
+                        score: new_score,
+                        l: new_l,
+                    };
+
+                    // The score of the new interval is at least as high as the
+                    // one of any interval starting at the same position, so the
+                    // previous one can be replaced. It would be shadowed by the
+                    // new interval both when computing `max_score` and when
+                    // saving the results, and replacing it keeps `P` from
+                    // growing quadratically with the window size.
+                    match insertion_position.checked_sub(1) {
+                        Some(j) if self.perfect_intervals[j].start == new_perf.start => {
+                            self.perfect_intervals[j] = new_perf;
+                        }
+                        _ => {
+                            self.perfect_intervals.insert(insertion_position, new_perf);
+                            insertion_position += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The start and end positions of a low-complexity region
+pub type Interval = (usize, usize);
+
+/// Iterator over the low-complexity regions of a sequence, returned by
+/// `SymmetricDust::intervals`
+#[derive(Debug)]
+pub struct IntervalIter<'a> {
+    sequence: &'a [u8],
+    /// Position of the next base to be scanned
+    position: usize,
+    /// Whether the end of the sequence was handled
+    finished: bool,
+    dust: SymmetricDust,
+    /// Regions that were found but not yet returned
+    ready: VecDeque<Range<usize>>,
+}
+
+impl Iterator for IntervalIter<'_> {
+    type Item = Interval;
+
+    fn next(&mut self) -> Option<Interval> {
+        while self.ready.is_empty() && !self.finished {
+            let ready = &mut self.ready;
+            let mut sink = |interval| ready.push_back(interval);
+            if self.position < self.sequence.len() {
+                let end = core::cmp::min(self.position + ENCODING_BLOCK_SIZE, self.sequence.len());
+                self.dust
+                    .feed(self.position, &self.sequence[self.position..end], &mut sink);
+                self.position = end;
+            } else {
+                self.dust.finish(self.sequence.len(), &mut sink);
+                self.finished = true;
+            }
+        }
+        let interval = self.ready.pop_front()?;
+        Some(clip_interval(interval, self.sequence.len()))
+    }
+}
+
+impl core::iter::FusedIterator for IntervalIter<'_> {}
+
+/// Clip an interval to the end of the sequence and convert it to a tuple
+pub(crate) fn clip_interval(interval: Range<usize>, sequence_length: usize) -> Interval {
+    // The algorithm can sometimes give end ranges outside of the sequence
+    // https://github.com/lh3/sdust/issues/2
+    (
+        interval.start,
+        core::cmp::min(interval.end, sequence_length),
+    )
+}
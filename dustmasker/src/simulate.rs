@@ -0,0 +1,115 @@
+//! Simulation of random sequences with planted low-complexity regions, whose
+//! positions are known, to evaluate the algorithm and its parameters (see
+//! `interval_set::evaluate`)
+
+use crate::sdust::Interval;
+use crate::InputError;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+const NUCLEOTIDES: [u8; 4] = *b"ACGT";
+
+/// The SplitMix64 generator, which is enough to draw random bases and is
+/// reproducible across platforms
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty
+    fn in_range(&mut self, range: &RangeInclusive<usize>) -> usize {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as usize
+    }
+
+    /// Whether an event of probability `probability` happens
+    fn chance(&mut self, probability: f64) -> bool {
+        // The 53 most significant bits give a uniform number in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    fn nucleotide(&mut self) -> u8 {
+        NUCLEOTIDES[(self.next_u64() % 4) as usize]
+    }
+}
+
+/// A simulated sequence and the regions that were planted in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    pub sequence: Vec<u8>,
+    /// The start and end positions of the planted regions, sorted by start
+    pub planted: Vec<Interval>,
+}
+
+/// Simulate a random sequence of `length` bases in which `n_regions`
+/// low-complexity regions are planted without overlaps. Each region repeats a
+/// random unit of a length drawn from `unit_length` (1 for homopolymers, 2 for
+/// dinucleotide repeats, and so on) over a length drawn from `region_length`,
+/// and each one of its bases is then replaced with a random base with
+/// probability `mutation_rate`. The same `seed` always gives the same
+/// simulation.
+pub fn simulate(
+    length: usize,
+    n_regions: usize,
+    region_length: RangeInclusive<usize>,
+    unit_length: RangeInclusive<usize>,
+    mutation_rate: f64,
+    seed: u64,
+) -> Result<Simulation, InputError> {
+    if region_length.is_empty() || *region_length.start() == 0 {
+        return Err(InputError::SimulationRangeError("region length"));
+    }
+    if unit_length.is_empty() || *unit_length.start() == 0 {
+        return Err(InputError::SimulationRangeError("unit length"));
+    }
+    if !(0.0..=1.0).contains(&mutation_rate) {
+        return Err(InputError::MutationRateError(mutation_rate));
+    }
+    if n_regions.saturating_mul(*region_length.end()) > length {
+        return Err(InputError::SimulationLengthError(
+            n_regions,
+            *region_length.end(),
+            length,
+        ));
+    }
+    let mut rng = SplitMix64(seed);
+    let region_lengths: Vec<usize> = (0..n_regions)
+        .map(|_| rng.in_range(&region_length))
+        .collect();
+    // The bases outside the regions are split in `n_regions + 1` gaps at
+    // random cut points
+    let n_background = length - region_lengths.iter().sum::<usize>();
+    let mut cuts: Vec<usize> = (0..n_regions)
+        .map(|_| rng.in_range(&(0..=n_background)))
+        .collect();
+    cuts.sort_unstable();
+    let mut sequence = Vec::with_capacity(length);
+    let mut planted = Vec::with_capacity(n_regions);
+    let mut previous_cut = 0;
+    let mut unit = Vec::new();
+    for (&cut, &region_length) in cuts.iter().zip(&region_lengths) {
+        sequence.extend((previous_cut..cut).map(|_| rng.nucleotide()));
+        previous_cut = cut;
+        unit.clear();
+        unit.extend((0..rng.in_range(&unit_length)).map(|_| rng.nucleotide()));
+        let start = sequence.len();
+        for &base in unit.iter().cycle().take(region_length) {
+            let base = if rng.chance(mutation_rate) {
+                rng.nucleotide()
+            } else {
+                base
+            };
+            sequence.push(base);
+        }
+        planted.push((start, sequence.len()));
+    }
+    sequence.extend((previous_cut..n_background).map(|_| rng.nucleotide()));
+    Ok(Simulation { sequence, planted })
+}
@@ -0,0 +1,134 @@
+//! Storage of extreme numbers of regions in a temporary file
+
+use crate::intervals::{Intervals, MappedPairs};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Collects intervals in memory until there are more than a given number of
+/// them, then writes them to an anonymous temporary file instead, which is
+/// memory-mapped once all the intervals were collected. This keeps the memory
+/// usage bounded for sequences with extreme numbers of intervals, such as long
+/// satellite arrays, as the operating system can page the mapped intervals out.
+pub struct SpillingIntervals {
+    intervals: Intervals,
+    /// Number of intervals above which they are spilled, if any
+    threshold: Option<usize>,
+    file: Option<BufWriter<File>>,
+    /// First error found while writing, reported by `finish`
+    error: Option<io::Error>,
+}
+
+impl SpillingIntervals {
+    pub fn new(intervals: Intervals, threshold: Option<usize>) -> Self {
+        // The file system of Emscripten targets (e.g., Pyodide) is kept in
+        // memory, where spilling would only add copies
+        let threshold = if cfg!(target_os = "emscripten") {
+            None
+        } else {
+            threshold
+        };
+        SpillingIntervals {
+            intervals,
+            threshold,
+            file: None,
+            error: None,
+        }
+    }
+
+    pub fn push(&mut self, start: usize, end: usize) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Some(file) = &mut self.file {
+            if let Err(e) = write_pair(file, start, end) {
+                self.error = Some(e);
+            }
+            return;
+        }
+        self.intervals.push(start, end);
+        if self
+            .threshold
+            .is_some_and(|threshold| self.intervals.len() > threshold)
+        {
+            if let Err(e) = self.spill() {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// Move the intervals collected so far to a temporary file
+    fn spill(&mut self) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "spilling {} intervals to a temporary file",
+            self.intervals.len()
+        );
+        let mut file = BufWriter::new(tempfile::tempfile()?);
+        for (start, end) in std::mem::take(&mut self.intervals).iter() {
+            write_pair(&mut file, start, end)?;
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// The collected intervals, which are memory-mapped if they were spilled
+    pub fn finish(self) -> io::Result<Intervals> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        match self.file {
+            None => Ok(self.intervals),
+            Some(file) => {
+                let file = file.into_inner().map_err(|e| e.into_error())?;
+                // SAFETY: the file is anonymous, so it can't be modified by any
+                // other process, and it is never written to again
+                let mmap = unsafe { Mmap::map(&file)? };
+                Ok(Intervals::Mapped(MappedPairs::new(mmap)?))
+            }
+        }
+    }
+}
+
+fn write_pair(file: &mut impl Write, start: usize, end: usize) -> io::Result<()> {
+    file.write_all(&(start as u64).to_ne_bytes())?;
+    file.write_all(&(end as u64).to_ne_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(threshold: Option<usize>, pairs: &[(usize, usize)]) -> Intervals {
+        let mut intervals = SpillingIntervals::new(Intervals::default(), threshold);
+        for &(start, end) in pairs {
+            intervals.push(start, end);
+        }
+        intervals.finish().unwrap()
+    }
+
+    #[test]
+    fn spilled_intervals_are_mapped() {
+        let pairs: Vec<(usize, usize)> = (0..1_000).map(|i| (10 * i, 10 * i + 5)).collect();
+        let intervals = collect(Some(100), &pairs);
+        assert!(matches!(intervals, Intervals::Mapped(_)));
+        assert_eq!(
+            intervals.mapped_bytes(),
+            pairs.len() * size_of::<[u64; 2]>()
+        );
+        assert_eq!(intervals.allocated_bytes(), 0);
+        assert_eq!(intervals.iter().collect::<Vec<_>>(), pairs);
+        assert_eq!(intervals.get(999), Some((9_990, 9_995)));
+        assert_eq!(intervals.find_touching(13), Some((10, 15)));
+    }
+
+    #[test]
+    fn intervals_below_the_threshold_stay_in_memory() {
+        let pairs = [(0, 5), (10, 20)];
+        for threshold in [None, Some(2)] {
+            let intervals = collect(threshold, &pairs);
+            assert!(matches!(intervals, Intervals::Compact(_)));
+            assert_eq!(intervals.iter().collect::<Vec<_>>(), pairs);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Reverse complement of sequences and conversion of the low-complexity regions
+//! to the coordinates of the minus strand
+
+use crate::sdust::Interval;
+use alloc::vec::Vec;
+
+/// Complement of each character: the IUPAC nucleotide codes are complemented
+/// preserving their case, with U complemented to A, and every other character
+/// is kept as is
+const COMPLEMENTS: [u8; 256] = {
+    let mut complements = [0; 256];
+    let mut character = 0;
+    while character < 256 {
+        complements[character] = character as u8;
+        character += 1;
+    }
+    let pairs: [(u8, u8); 7] = [
+        (b'A', b'T'),
+        (b'C', b'G'),
+        (b'R', b'Y'),
+        (b'K', b'M'),
+        (b'B', b'V'),
+        (b'D', b'H'),
+        (b'U', b'A'),
+    ];
+    let mut i = 0;
+    while i < pairs.len() {
+        let (base, complement) = pairs[i];
+        // U is complemented to A, but A is still complemented to T
+        if base != b'U' {
+            complements[complement as usize] = base;
+            complements[(complement | 0x20) as usize] = base | 0x20;
+        }
+        complements[base as usize] = complement;
+        complements[(base | 0x20) as usize] = complement | 0x20;
+        i += 1;
+    }
+    complements
+};
+
+/// The complement of `base`, in the same case (see [`reverse_complement`])
+#[inline]
+pub fn complement(base: u8) -> u8 {
+    COMPLEMENTS[base as usize]
+}
+
+/// The reverse complement of `sequence`. The IUPAC nucleotide codes are
+/// complemented preserving their case (S, W and N are their own complements,
+/// and U is complemented to A), and any other character is kept as is.
+pub fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence
+        .iter()
+        .rev()
+        .map(|&base| complement(base))
+        .collect()
+}
+
+/// The position, on the minus strand, of the region `start..end` of a sequence
+/// of `length` bases, which spans the same bases of its reverse complement.
+/// `end` must be at most `length`.
+#[inline]
+pub fn reverse_interval(start: usize, end: usize, length: usize) -> Interval {
+    // Regions starting past the end of the sequence are empty, and are moved
+    // to its start
+    (length - end, length.saturating_sub(start))
+}
+
+/// The positions, on the minus strand, of the regions of a sequence of
+/// `length` bases, sorted by start like the regions of its reverse complement
+/// (see [`reverse_interval`])
+pub fn reverse_intervals(
+    intervals: impl DoubleEndedIterator<Item = Interval>,
+    length: usize,
+) -> Vec<Interval> {
+    intervals
+        .rev()
+        .map(|(start, end)| reverse_interval(start, end, length))
+        .collect()
+}
@@ -0,0 +1,99 @@
+//! Processing of sequences received in chunks
+
+use crate::sdust::{clip_interval, SymmetricDust};
+use alloc::collections::VecDeque;
+use core::ops::Range;
+
+/// Identify low-complexity regions in a sequence that is received in chunks,
+/// without holding the whole sequence in memory. Feeding the chunks one after
+/// the other produces the same intervals as processing the concatenated
+/// sequence with `SymmetricDust::process_with`.
+#[derive(Debug)]
+pub struct StreamingDust {
+    dust: SymmetricDust,
+    /// Number of bases fed so far
+    n_bases: usize,
+    /// Intervals that end past the bases fed so far. They are held back until
+    /// more bases arrive, as they must be clipped if the sequence ends first.
+    pending: VecDeque<Range<usize>>,
+}
+
+impl StreamingDust {
+    pub fn new(window_size: usize, score_threshold: usize) -> Self {
+        StreamingDust {
+            dust: SymmetricDust::new(window_size, score_threshold),
+            n_bases: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Number of bases fed so far
+    pub fn n_bases(&self) -> usize {
+        self.n_bases
+    }
+
+    /// Number of bytes allocated on the heap by the state of the scan and the
+    /// intervals held back, which doesn't grow with the number of bases fed
+    pub fn allocated_bytes(&self) -> usize {
+        self.dust.allocated_bytes() + self.pending.capacity() * size_of::<Range<usize>>()
+    }
+
+    /// Scan the next `chunk` of the sequence, passing the start and end of the
+    /// intervals that became final to `sink`
+    pub fn feed(&mut self, chunk: &[u8], mut sink: impl FnMut(usize, usize)) {
+        let pending = &mut self.pending;
+        self.dust.feed(self.n_bases, chunk, &mut |interval| {
+            pending.push_back(interval)
+        });
+        self.n_bases += chunk.len();
+        while let Some(interval) = self.pending.front() {
+            if interval.end > self.n_bases {
+                break;
+            }
+            let interval = self.pending.pop_front().unwrap();
+            sink(interval.start, interval.end);
+        }
+    }
+
+    /// Mark the end of the sequence, passing the start and end of the
+    /// remaining intervals to `sink`
+    pub fn finish(mut self, mut sink: impl FnMut(usize, usize)) {
+        let pending = &mut self.pending;
+        self.dust
+            .finish(self.n_bases, &mut |interval| pending.push_back(interval));
+        for interval in self.pending {
+            let (start, end) = clip_interval(interval, self.n_bases);
+            sink(start, end);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_sequence, serial_intervals, Rng};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn chunks_match_the_whole_sequence() {
+        let mut rng = Rng::new(5);
+        for _ in 0..50 {
+            let length = 4 + rng.below(10_000);
+            let sequence = random_sequence(&mut rng, length);
+            let mut stream = StreamingDust::new(64, 20);
+            let mut intervals = Vec::new();
+            let mut position = 0;
+            while position < sequence.len() {
+                // Chunks as short as a single base, or empty
+                let end = (position + rng.below(500)).min(sequence.len());
+                stream.feed(&sequence[position..end], |start, end| {
+                    intervals.push((start, end))
+                });
+                position = end;
+            }
+            assert_eq!(stream.n_bases(), sequence.len());
+            stream.finish(|start, end| intervals.push((start, end)));
+            assert_eq!(intervals, serial_intervals(&sequence, 64, 20));
+        }
+    }
+}
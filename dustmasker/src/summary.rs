@@ -0,0 +1,166 @@
+//! Summaries of the masking of many sequences, which are accumulated record by
+//! record without keeping their regions
+
+use alloc::collections::BinaryHeap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+
+/// The masking of one sequence
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordSummary {
+    pub name: String,
+    pub length: usize,
+    /// Number of bases within low-complexity regions
+    pub n_masked_bases: usize,
+    /// Number of low-complexity regions
+    pub n_intervals: usize,
+}
+
+impl RecordSummary {
+    /// The fraction of the bases of the sequence that are masked, or 0 if it is
+    /// empty
+    pub fn masked_fraction(&self) -> f64 {
+        match self.length {
+            0 => 0.0,
+            length => self.n_masked_bases as f64 / length as f64,
+        }
+    }
+
+    /// Compare the masked fractions of two records, and then their numbers of
+    /// masked bases, without rounding the fractions
+    fn cmp_masking(&self, other: &Self) -> Ordering {
+        let fraction = self.n_masked_bases as u128 * other.length.max(1) as u128;
+        let other_fraction = other.n_masked_bases as u128 * self.length.max(1) as u128;
+        fraction
+            .cmp(&other_fraction)
+            .then(self.n_masked_bases.cmp(&other.n_masked_bases))
+    }
+}
+
+/// A record ordered by its masking, for the heap of the most masked records
+#[derive(Debug, Clone)]
+struct ByMasking(RecordSummary);
+
+impl PartialEq for ByMasking {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ByMasking {}
+
+impl PartialOrd for ByMasking {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByMasking {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_masking(&other.0)
+    }
+}
+
+/// Totals and distribution of the masking of the records added so far, which
+/// only keeps the masked fraction of each record and the `top_n` most masked
+/// records
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    top_n: usize,
+    n_records: usize,
+    n_bases: usize,
+    n_masked_bases: usize,
+    n_intervals: usize,
+    masked_fractions: Vec<f64>,
+    /// The most masked records, the least masked of them being on top
+    most_masked: BinaryHeap<Reverse<ByMasking>>,
+}
+
+impl Summary {
+    /// An empty summary that keeps the `top_n` most masked records
+    pub fn new(top_n: usize) -> Self {
+        Summary {
+            top_n,
+            ..Summary::default()
+        }
+    }
+
+    /// Add the masking of a record
+    pub fn add(&mut self, record: RecordSummary) {
+        self.n_records += 1;
+        self.n_bases += record.length;
+        self.n_masked_bases += record.n_masked_bases;
+        self.n_intervals += record.n_intervals;
+        self.masked_fractions.push(record.masked_fraction());
+        if self.top_n == 0 {
+            return;
+        }
+        if self.most_masked.len() < self.top_n {
+            self.most_masked.push(Reverse(ByMasking(record)));
+        } else if self
+            .most_masked
+            .peek()
+            .is_some_and(|Reverse(least)| record.cmp_masking(&least.0) == Ordering::Greater)
+        {
+            self.most_masked.pop();
+            self.most_masked.push(Reverse(ByMasking(record)));
+        }
+    }
+
+    pub fn n_records(&self) -> usize {
+        self.n_records
+    }
+
+    pub fn n_bases(&self) -> usize {
+        self.n_bases
+    }
+
+    pub fn n_masked_bases(&self) -> usize {
+        self.n_masked_bases
+    }
+
+    pub fn n_intervals(&self) -> usize {
+        self.n_intervals
+    }
+
+    /// The fraction of the bases of all the records that are masked, or 0 if
+    /// there are none
+    pub fn masked_fraction(&self) -> f64 {
+        match self.n_bases {
+            0 => 0.0,
+            n_bases => self.n_masked_bases as f64 / n_bases as f64,
+        }
+    }
+
+    /// The masked fraction of each record, in the order they were added
+    pub fn masked_fractions(&self) -> &[f64] {
+        &self.masked_fractions
+    }
+
+    /// The `q`-quantile (between 0 and 1) of the masked fractions of the
+    /// records, interpolated linearly between the closest ones, or `None` if
+    /// there are no records
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let mut fractions = self.masked_fractions.clone();
+        fractions.sort_unstable_by(f64::total_cmp);
+        let position = q.clamp(0.0, 1.0) * fractions.len().checked_sub(1)? as f64;
+        // The position is not negative, so the cast rounds it down
+        let index = position as usize;
+        let lower = fractions[index];
+        let upper = fractions.get(index + 1).copied().unwrap_or(lower);
+        Some(lower + (upper - lower) * (position - index as f64))
+    }
+
+    /// The `top_n` most masked records, sorted by decreasing masked fraction
+    /// and then number of masked bases
+    pub fn most_masked(&self) -> Vec<RecordSummary> {
+        let mut records: Vec<RecordSummary> = self
+            .most_masked
+            .iter()
+            .map(|Reverse(ByMasking(record))| record.clone())
+            .collect();
+        records.sort_by(|a, b| b.cmp_masking(a));
+        records
+    }
+}
@@ -0,0 +1,74 @@
+//! Runs of the algorithm over a grid of parameters, which share the encoding of
+//! the sequence
+
+use crate::sdust::{clip_interval, encode, SymmetricDust, AMBIGUOUS, ENCODING_BLOCK_SIZE};
+use crate::{validate_inputs, InputError};
+use alloc::vec::Vec;
+
+/// The regions found with one combination of parameters, as returned by
+/// [`sweep`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SweepPoint {
+    pub window_size: usize,
+    pub score_threshold: usize,
+    /// Number of low-complexity regions
+    pub n_intervals: usize,
+    /// Number of bases within low-complexity regions
+    pub n_masked_bases: usize,
+}
+
+impl SweepPoint {
+    fn count(&mut self, (start, end): (usize, usize)) {
+        self.n_intervals += 1;
+        // Intervals starting past the end of the sequence are empty
+        self.n_masked_bases += end.saturating_sub(start);
+    }
+}
+
+/// Identify the low-complexity regions of `sequence` with each combination of
+/// `window_sizes` and `score_thresholds`, in that order, counting them without
+/// storing them. The sequence is scanned once: each block of it is encoded and
+/// then fed to the scan of every combination.
+pub fn sweep(
+    sequence: &[u8],
+    window_sizes: &[usize],
+    score_thresholds: &[usize],
+) -> Result<Vec<SweepPoint>, InputError> {
+    for &window_size in window_sizes {
+        validate_inputs(sequence, window_size)?;
+    }
+    let mut scans: Vec<(SymmetricDust, SweepPoint)> = window_sizes
+        .iter()
+        .flat_map(|&window_size| {
+            score_thresholds.iter().map(move |&score_threshold| {
+                let point = SweepPoint {
+                    window_size,
+                    score_threshold,
+                    ..SweepPoint::default()
+                };
+                (SymmetricDust::new(window_size, score_threshold), point)
+            })
+        })
+        .collect();
+    let length = sequence.len();
+    let mut codes = [AMBIGUOUS; ENCODING_BLOCK_SIZE];
+    for (block_index, block) in sequence.chunks(ENCODING_BLOCK_SIZE).enumerate() {
+        encode(block, &mut codes);
+        for (dust, point) in &mut scans {
+            dust.feed_codes(
+                block_index * ENCODING_BLOCK_SIZE,
+                &codes[..block.len()],
+                &mut |interval| point.count(clip_interval(interval, length)),
+            );
+        }
+    }
+    Ok(scans
+        .into_iter()
+        .map(|(mut dust, mut point)| {
+            dust.finish(length, &mut |interval| {
+                point.count(clip_interval(interval, length))
+            });
+            point
+        })
+        .collect())
+}
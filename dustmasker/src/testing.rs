@@ -0,0 +1,65 @@
+//! Random sequences shared by the tests of the modules
+
+use crate::sdust::{Interval, SymmetricDust};
+use alloc::vec::Vec;
+
+/// A small xorshift generator, so that the tests are reproducible without
+/// depending on a random number crate
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9e3779b97f4a7c15) | 1)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number below `n`, which must not be 0
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A random sequence of `length` bases, interspersed with tandem repeats of
+/// short units, lowercase stretches and runs of ambiguous bases, so that it
+/// has many low-complexity regions of various lengths
+pub(crate) fn random_sequence(rng: &mut Rng, length: usize) -> Vec<u8> {
+    let mut sequence = Vec::with_capacity(length);
+    while sequence.len() < length {
+        let run_length = 1 + rng.below(200);
+        match rng.below(10) {
+            0..=2 => {
+                let unit: Vec<u8> = (0..1 + rng.below(6))
+                    .map(|_| b"ACGT"[rng.below(4)])
+                    .collect();
+                sequence.extend(unit.iter().cycle().take(run_length));
+            }
+            3 => sequence.extend((0..run_length).map(|_| b"acgt"[rng.below(4)])),
+            4 if rng.below(4) == 0 => {
+                sequence.extend(core::iter::repeat_n(b'N', 1 + rng.below(20)))
+            }
+            _ => sequence.extend((0..run_length).map(|_| b"ACGT"[rng.below(4)])),
+        }
+    }
+    sequence.truncate(length);
+    sequence
+}
+
+/// The regions of `sequence` found by a serial scan, the reference of the
+/// other ways of processing it
+pub(crate) fn serial_intervals(
+    sequence: &[u8],
+    window_size: usize,
+    score_threshold: usize,
+) -> Vec<Interval> {
+    let mut intervals = Vec::new();
+    SymmetricDust::process_with(sequence, window_size, score_threshold, |start, end| {
+        intervals.push((start, end))
+    });
+    intervals
+}
@@ -0,0 +1,105 @@
+//! Processing of 2-bit packed sequences
+
+use crate::sdust::{clip_interval, SymmetricDust, AMBIGUOUS, ENCODING_BLOCK_SIZE};
+use crate::InputError;
+use core::ops::Range;
+
+/// Identify the low-complexity regions of a sequence of `length` bases packed
+/// with 2 bits per base, passing the start and end of each one of them to
+/// `sink`. Bases are packed four per byte, the first one in the most significant
+/// bits, as in the UCSC .2bit format. `n_blocks` are the sorted and
+/// non-overlapping regions made of ambiguous bases, which are not represented
+/// in the packed sequence.
+///
+/// The algorithm only compares triplets of bases, so the packed codes are
+/// scanned as they are, whatever base each one of them stands for. Returns an
+/// `InputError::PackedLengthError` if `packed` holds fewer than `length` bases.
+///
+/// ```
+/// use dustmasker::{twobit, InputError};
+///
+/// let result = twobit::process_with(&[0; 2], 12, &[], 64, 20, |_, _| {});
+/// assert!(matches!(result, Err(InputError::PackedLengthError(8, 12))));
+/// ```
+pub fn process_with(
+    packed: &[u8],
+    length: usize,
+    n_blocks: &[Range<usize>],
+    window_size: usize,
+    score_threshold: usize,
+    mut sink: impl FnMut(usize, usize),
+) -> Result<(), InputError> {
+    if packed.len() < length.div_ceil(4) {
+        return Err(InputError::PackedLengthError(4 * packed.len(), length));
+    }
+    let mut dust = SymmetricDust::new(window_size, score_threshold);
+    let mut sink_interval = |interval| {
+        let (start, end) = clip_interval(interval, length);
+        sink(start, end)
+    };
+    let mut n_blocks = n_blocks.iter().peekable();
+    let mut codes = [AMBIGUOUS; ENCODING_BLOCK_SIZE];
+    for block_start in (0..length).step_by(ENCODING_BLOCK_SIZE) {
+        let block_end = core::cmp::min(block_start + ENCODING_BLOCK_SIZE, length);
+        let block_codes = &mut codes[..block_end - block_start];
+        for (offset, code) in block_codes.iter_mut().enumerate() {
+            let position = block_start + offset;
+            *code = (packed[position / 4] >> (6 - 2 * (position % 4))) & 3;
+        }
+        while let Some(n_block) = n_blocks.peek() {
+            let start = core::cmp::max(n_block.start, block_start);
+            let end = core::cmp::min(n_block.end, block_end);
+            if start < end {
+                block_codes[start - block_start..end - block_start].fill(AMBIGUOUS);
+            }
+            if n_block.end > block_end {
+                break;
+            }
+            n_blocks.next();
+        }
+        dust.feed_codes(block_start, block_codes, &mut sink_interval);
+    }
+    dust.finish(length, &mut sink_interval);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_sequence, serial_intervals, Rng};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// Pack `sequence` with 2 bits per base, along with its runs of ambiguous
+    /// bases, which are packed as 0
+    fn pack(sequence: &[u8]) -> (Vec<u8>, Vec<Range<usize>>) {
+        let mut packed = vec![0; sequence.len().div_ceil(4)];
+        let mut n_blocks: Vec<Range<usize>> = Vec::new();
+        for (position, base) in sequence.iter().enumerate() {
+            match b"ACGT".iter().position(|&b| b == base.to_ascii_uppercase()) {
+                Some(code) => packed[position / 4] |= (code as u8) << (6 - 2 * (position % 4)),
+                None => match n_blocks.last_mut() {
+                    Some(n_block) if n_block.end == position => n_block.end += 1,
+                    _ => n_blocks.push(position..position + 1),
+                },
+            }
+        }
+        (packed, n_blocks)
+    }
+
+    #[test]
+    fn packed_sequences_match_the_decoded_ones() {
+        let mut rng = Rng::new(8);
+        for _ in 0..50 {
+            let length = 4 + rng.below(10_000);
+            let sequence = random_sequence(&mut rng, length);
+            let (packed, n_blocks) = pack(&sequence);
+            let mut intervals = Vec::new();
+            process_with(&packed, sequence.len(), &n_blocks, 64, 20, |start, end| {
+                intervals.push((start, end))
+            })
+            .unwrap();
+            assert_eq!(intervals, serial_intervals(&sequence, 64, 20));
+        }
+    }
+}
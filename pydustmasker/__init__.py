@@ -1,7 +1,79 @@
-from pydustmasker._pydustmasker import DustMasker
+from pydustmasker import (
+    aio,
+    analysis,
+    annotation,
+    errors,
+    fasta,
+    genes,
+    output,
+    plotting,
+)
+from pydustmasker._pydustmasker import (
+    CancelToken,
+    DustMasker,
+    MaskingSummary,
+    StreamingDustMasker,
+    cache_info,
+    calibrate,
+    clear_cache,
+    concatenate,
+    count_masked_bases,
+    exceeds_masked_fraction,
+    intervals_from_2bit,
+    mask_bulk,
+    perfect_intervals,
+    reverse_complement,
+    reverse_intervals,
+    set_cache_size,
+    simulate,
+    sweep,
+    trace_window,
+    window_metrics,
+)
 from pydustmasker._version import VERSION
+from pydustmasker.aio import mask_async
+from pydustmasker.analysis import analyze
+from pydustmasker.fasta import IndexedFasta
+from pydustmasker.genes import mask_for_gene_finding
+from pydustmasker.output import convert_intervals, write_bed, write_intervals
 
 __version__ = VERSION
 
 
-__all__ = ["DustMasker"]
+__all__ = [
+    "CancelToken",
+    "DustMasker",
+    "IndexedFasta",
+    "MaskingSummary",
+    "StreamingDustMasker",
+    "aio",
+    "analysis",
+    "analyze",
+    "annotation",
+    "cache_info",
+    "calibrate",
+    "clear_cache",
+    "concatenate",
+    "convert_intervals",
+    "count_masked_bases",
+    "errors",
+    "exceeds_masked_fraction",
+    "fasta",
+    "genes",
+    "intervals_from_2bit",
+    "mask_async",
+    "mask_bulk",
+    "mask_for_gene_finding",
+    "output",
+    "perfect_intervals",
+    "plotting",
+    "reverse_complement",
+    "reverse_intervals",
+    "set_cache_size",
+    "simulate",
+    "sweep",
+    "trace_window",
+    "window_metrics",
+    "write_bed",
+    "write_intervals",
+]
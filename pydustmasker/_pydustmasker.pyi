@@ -1,16 +1,244 @@
 from __future__ import annotations
 
-from collections.abc import Sequence
+from collections.abc import Callable, Iterable, Iterator, Sequence
+from typing import TYPE_CHECKING, Any, Literal, TypeVar, overload
+
+from typing_extensions import Buffer
+
+if TYPE_CHECKING:
+    from _typeshed import SupportsWrite
+    from matplotlib.figure import Figure
+
+T = TypeVar("T", str, int)
+
+class CancelToken:
+    cancelled: bool
+    def __init__(self) -> None: ...
+    def cancel(self) -> None: ...
+    def __repr__(self) -> str: ...
 
 class DustMasker:
     sequence: str
     window_size: int
     score_threshold: int
+    level: float
+    effective_window_size: int
+    kmer_size: int
     intervals: Sequence[tuple[int, int]]
     def __init__(
-        self, sequence: str, window_size: int = 64, score_threshold: int = 20
+        self,
+        sequence: str,
+        window_size: int = 64,
+        score_threshold: int = 20,
+        threads: int = 1,
+        spill_threshold: int | None = None,
+        alphabet: Sequence[str] | None = None,
+        kmer_size: int = 3,
+        level: float | None = None,
+        composition: bool = False,
+        bisulfite: Literal["CT", "GA"] | None = None,
+        callback: Callable[[list[tuple[int, int]]], object] | None = None,
+        batch_size: int = 1000,
+        cancel: CancelToken | None = None,
+        progress: Callable[[int, int], object] | None = None,
+        progress_interval: int = 10_000_000,
+        threshold_overrides: Sequence[tuple[int, int, int]] | None = None,
+        mask_ambiguous: bool = False,
+        allow_short: bool = False,
     ) -> None: ...
     @property
+    def composition(self) -> dict[str, int | float] | None: ...
+    @property
+    def interval_array(self) -> memoryview: ...
+    @property
+    def mask_array(self) -> memoryview: ...
+    @property
     def n_masked_bases(self) -> int: ...
-    def mask(self, hard: bool) -> str: ...
+    @property
+    def minus_strand_intervals(self) -> list[tuple[int, int]]: ...
+    @property
+    def ambiguous_intervals(self) -> list[tuple[int, int]]: ...
+    def mask(
+        self,
+        hard: bool = False,
+        *,
+        uppercase: bool = False,
+        convert: Literal["UT", "TU"] | None = None,
+        ambiguous_to_n: bool = False,
+    ) -> str: ...
+    def masked_view(
+        self,
+        hard: bool = False,
+        *,
+        uppercase: bool = False,
+        convert: Literal["UT", "TU"] | None = None,
+        ambiguous_to_n: bool = False,
+    ) -> MaskedSequence: ...
+    def plot_density(self, bin_size: int = 10_000) -> Figure: ...
+    def regions(self) -> list[Region]: ...
+    def post_process_regions(self, regions: list[Region]) -> list[Any]: ...
+    def post_process_intervals(
+        self, intervals: list[tuple[int, int]]
+    ) -> list[tuple[int, int]]: ...
+    @overload
+    def kmers(self, k: int, as_int: Literal[False] = False) -> KmerIterator[str]: ...
+    @overload
+    def kmers(self, k: int, as_int: Literal[True]) -> KmerIterator[int]: ...
+    def edit(self, start: int, end: int, replacement: str) -> DustMasker: ...
+    def union(self, other: DustMasker | Sequence[tuple[int, int]]) -> DustMasker: ...
+    def intersection(
+        self, other: DustMasker | Sequence[tuple[int, int]]
+    ) -> DustMasker: ...
+    def difference(
+        self, other: DustMasker | Sequence[tuple[int, int]]
+    ) -> DustMasker: ...
+    def __getitem__(self, index: slice) -> DustMasker: ...
+    def compare(
+        self, other: DustMasker | Sequence[tuple[int, int]]
+    ) -> dict[str, Any]: ...
+    def evaluate(
+        self, truth: DustMasker | Sequence[tuple[int, int]]
+    ) -> dict[str, dict[str, float]]: ...
+    def memory_usage(self) -> dict[str, int]: ...
+    def __repr__(self) -> str: ...
+    def _repr_html_(self) -> str: ...
+
+class KmerIterator(Iterator[T]):
+    def __iter__(self) -> KmerIterator[T]: ...
+    def __next__(self) -> T: ...
+
+class MaskedSequence:
+    def __len__(self) -> int: ...
+    @overload
+    def __getitem__(self, index: int) -> str: ...
+    @overload
+    def __getitem__(self, index: slice) -> str: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def chunks(self, size: int = 65536) -> Iterator[str]: ...
+    def write(self, file: SupportsWrite[str], chunk_size: int = 65536) -> int: ...
+
+class MaskingSummary:
+    n_records: int
+    n_bases: int
+    n_masked_bases: int
+    n_intervals: int
+    masked_fraction: float
+    masked_fractions: list[float]
+    most_masked: list[tuple[str, int, int, float]]
+    def __init__(self, top_n: int = 10) -> None: ...
+    def add(self, name: str, masker: DustMasker) -> None: ...
+    def quantiles(self, q: Sequence[float]) -> list[float]: ...
     def __repr__(self) -> str: ...
+
+class Region:
+    start: int
+    end: int
+    length: int
+    score: float
+    repeat_unit: str | None
+    def __iter__(self) -> Iterator[int]: ...
+
+class StreamingDustMasker:
+    window_size: int
+    score_threshold: int
+    level: float
+    n_bases: int
+    def __init__(
+        self,
+        window_size: int = 64,
+        score_threshold: int = 20,
+        level: float | None = None,
+    ) -> None: ...
+    def feed(self, chunk: str) -> list[tuple[int, int]]: ...
+    def finish(self) -> list[tuple[int, int]]: ...
+    def memory_usage(self) -> dict[str, int]: ...
+    def __repr__(self) -> str: ...
+
+def set_cache_size(max_entries: int) -> None: ...
+def clear_cache() -> None: ...
+def cache_info() -> dict[str, int]: ...
+@overload
+def count_masked_bases(
+    sequence: str,
+    window_size: int = 64,
+    score_threshold: int = 20,
+    threads: int = 1,
+    bin_size: None = None,
+) -> int: ...
+@overload
+def count_masked_bases(
+    sequence: str,
+    window_size: int = 64,
+    score_threshold: int = 20,
+    threads: int = 1,
+    *,
+    bin_size: int,
+) -> list[int]: ...
+def mask_bulk(
+    sequences: Iterable[str | bytes] | Buffer,
+    offsets: Buffer | Sequence[int] | None = None,
+    window_size: int = 64,
+    score_threshold: int = 20,
+    threads: int = 1,
+    allow_short: bool = False,
+) -> tuple[memoryview, memoryview]: ...
+def exceeds_masked_fraction(
+    sequence: str,
+    max_masked_fraction: float,
+    window_size: int = 64,
+    score_threshold: int = 20,
+) -> bool: ...
+def intervals_from_2bit(
+    packed: bytes,
+    length: int,
+    n_blocks: Sequence[tuple[int, int]] = ...,
+    window_size: int = 64,
+    score_threshold: int = 20,
+) -> list[tuple[int, int]]: ...
+def perfect_intervals(
+    sequence: str, window_size: int = 64, score_threshold: int = 20
+) -> list[tuple[int, int, int, int]]: ...
+@overload
+def trace_window(
+    sequence: str,
+    window_size: int = 64,
+    score_threshold: int = 20,
+    callback: None = None,
+) -> list[tuple[int, int, int, int, int, bool]]: ...
+@overload
+def trace_window(
+    sequence: str,
+    window_size: int = 64,
+    score_threshold: int = 20,
+    *,
+    callback: Callable[[tuple[int, int, int, int, int, bool]], object],
+) -> None: ...
+def reverse_complement(sequence: str) -> str: ...
+def reverse_intervals(
+    intervals: Sequence[tuple[int, int]], length: int
+) -> list[tuple[int, int]]: ...
+def concatenate(
+    maskers: Sequence[DustMasker], offsets: Sequence[int] | None = None
+) -> DustMasker: ...
+def simulate(
+    length: int,
+    n_regions: int = 10,
+    region_length: tuple[int, int] = (20, 200),
+    unit_length: tuple[int, int] = (1, 6),
+    mutation_rate: float = 0.0,
+    seed: int | None = None,
+) -> tuple[str, list[tuple[int, int]]]: ...
+def window_metrics(
+    sequence: str,
+    window_size: int = 64,
+    step: int = 1,
+    metrics: list[Literal["dust", "entropy", "gc"]] | None = None,
+) -> dict[str, memoryview]: ...
+def sweep(
+    sequences: str | Sequence[str],
+    window_sizes: Sequence[int] = [64],
+    score_thresholds: Sequence[int] = [20],
+) -> dict[str, list[Any]]: ...
+def calibrate(
+    sequence: str, masked_fraction: float, window_size: int = 64, threads: int = 1
+) -> DustMasker: ...
@@ -0,0 +1,64 @@
+"""Non-blocking wrappers for asyncio applications, such as web services.
+
+DustMasker releases the GIL while it scans a sequence, so running it in a
+thread of an executor lets the event loop serve other requests meanwhile.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import functools
+from concurrent.futures import Executor
+from typing import Any
+
+from pydustmasker._pydustmasker import CancelToken, DustMasker
+
+
+async def mask_async(
+    sequence: str, executor: Executor | None = None, **kwargs: Any
+) -> DustMasker:
+    """Identifies the low-complexity regions of a sequence without blocking the
+    event loop.
+
+    Parameters
+    ----------
+    sequence : str
+        A string representing the nucleotide sequence to be processed.
+    executor : concurrent.futures.Executor, optional
+        The executor whose threads process the sequence (e.g., a
+        `ThreadPoolExecutor` that bounds the number of sequences processed at
+        the same time). If not given, the default executor of the event loop is
+        used. Process pools aren't supported, as DustMasker can't be pickled.
+    **kwargs
+        The other parameters of `DustMasker` (e.g., `window_size` or
+        `threads`). A `callback` is called from the thread of the executor,
+        which holds the GIL during the whole scan. If the task awaiting the
+        result is cancelled, the scan is cancelled too, through the `cancel`
+        token if one is given.
+
+    Returns
+    -------
+    DustMasker
+        The same object as `DustMasker(sequence, **kwargs)`.
+
+    Raises
+    ------
+    ValueError, TypeError, OverflowError, OSError
+        The same exceptions as `DustMasker`, such as a `SequenceTooShortError`.
+    """
+    loop = asyncio.get_running_loop()
+    # Cancelling the task doesn't stop the thread, so the scan is cancelled
+    # through its token
+    if kwargs.get("cancel") is None:
+        kwargs["cancel"] = CancelToken()
+    cancel = kwargs["cancel"]
+    try:
+        return await loop.run_in_executor(
+            executor, functools.partial(DustMasker, sequence, **kwargs)
+        )
+    except asyncio.CancelledError:
+        cancel.cancel()
+        raise
+
+
+__all__ = ["mask_async"]
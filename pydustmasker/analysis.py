@@ -0,0 +1,151 @@
+"""Recommendation of parameters from the composition of a genome.
+
+The default parameters of DustMasker were chosen for genomes with a balanced
+composition. In AT-rich or GC-rich genomes, such as the ones of many protists
+and fungi, random triplets are more likely to be identical, so windows without
+repeats score higher and large parts of the genome may be masked. `analyze()`
+samples a genome, summarizes its composition and masking, and scales the score
+threshold to its composition.
+"""
+
+from __future__ import annotations
+
+from collections.abc import Sequence
+from typing import Any
+
+from pydustmasker._pydustmasker import sweep
+
+# The window sizes at which the masked fraction is reported, with the default
+# score threshold
+SCALES = (16, 32, 64, 128)
+DEFAULT_WINDOW_SIZE = 64
+DEFAULT_SCORE_THRESHOLD = 20
+
+
+def sample(sequences: Sequence[str], sample_length: int, n_samples: int) -> list[str]:
+    """Returns `n_samples` segments of `sample_length` bases evenly spaced along
+    the concatenated sequences, or the whole sequences if they are shorter.
+    Segments don't span two sequences, so they can be shorter at their ends."""
+    total_length = sum(len(sequence) for sequence in sequences)
+    if total_length <= sample_length * n_samples:
+        return list(sequences)
+    samples = []
+    spacing = total_length / n_samples
+    index, offset = 0, 0
+    for i in range(n_samples):
+        position = int(i * spacing)
+        while position >= offset + len(sequences[index]):
+            offset += len(sequences[index])
+            index += 1
+        start = position - offset
+        samples.append(sequences[index][start : start + sample_length])
+    return samples
+
+
+def composition_score_threshold(gc_content: float) -> int:
+    """Returns the score threshold equivalent to the default one for a genome
+    with a given GC content.
+
+    Assuming that A and T, and C and G, are equally frequent, two random
+    triplets are identical with probability `((gc² + (1 - gc)²) / 2)³` instead
+    of `(1/4)³` in a balanced genome, and the scores of random windows are
+    higher by the same factor, by which the threshold is scaled.
+    """
+    factor = (2 * (gc_content**2 + (1 - gc_content) ** 2)) ** 3
+    return round(DEFAULT_SCORE_THRESHOLD * factor)
+
+
+def analyze(
+    sequences: str | Sequence[str], sample_length: int = 10_000, n_samples: int = 100
+) -> dict[str, Any]:
+    """Summarizes the composition of a genome and recommends parameters for it.
+
+    Parameters
+    ----------
+    sequences : str or list of str
+        A nucleotide sequence, or multiple sequences (e.g., the records of a
+        FASTA file).
+    sample_length : int, default: 10000
+        The length of the segments sampled from the genome.
+    n_samples : int, default: 100
+        The number of segments, evenly spaced along the genome. Genomes shorter
+        than `sample_length * n_samples` are analyzed in full.
+
+    Returns
+    -------
+    dict
+        The number of bases of the genome (`n_bases`) and of the samples
+        (`n_sampled_bases`), the fraction of G and C among the A, C, G and T
+        bases (`gc_content`), the fraction of other characters, such as N
+        (`ambiguous_fraction`), the masked fraction with the default score
+        threshold and windows of 16, 32, 64 and 128 bases (`masked_fractions`),
+        the recommended `window_size` and `score_threshold`, the masked
+        fraction with them (`masked_fraction`), and a short `rationale`.
+
+    Raises
+    ------
+    ValueError
+        If the sample length or the number of samples are not positive, or if
+        the samples contain no A, C, G or T base.
+    """
+    if sample_length <= 0 or n_samples <= 0:
+        raise ValueError("the sample length and number of samples must be positive")
+    if isinstance(sequences, str):
+        sequences = [sequences]
+    # Sequences that are too short for DustMasker are too short to be sampled
+    samples = [
+        segment
+        for segment in sample(sequences, sample_length, n_samples)
+        if len(segment) >= 4
+    ]
+    n_sampled_bases = sum(len(segment) for segment in samples)
+    upper = "".join(samples).upper()
+    gc_count = upper.count("G") + upper.count("C")
+    at_count = upper.count("A") + upper.count("T")
+    if gc_count + at_count == 0:
+        raise ValueError("the samples contain no A, C, G or T base")
+    gc_content = gc_count / (gc_count + at_count)
+    score_threshold = composition_score_threshold(gc_content)
+    table = sweep(
+        samples,
+        window_sizes=list(SCALES),
+        score_thresholds=sorted({DEFAULT_SCORE_THRESHOLD, score_threshold}),
+    )
+    rows = list(
+        zip(table["window_size"], table["score_threshold"], table["masked_fraction"])
+    )
+    masked_fractions = {
+        window_size: fraction
+        for window_size, threshold, fraction in rows
+        if threshold == DEFAULT_SCORE_THRESHOLD
+    }
+    (masked_fraction,) = (
+        fraction
+        for window_size, threshold, fraction in rows
+        if window_size == DEFAULT_WINDOW_SIZE and threshold == score_threshold
+    )
+    if score_threshold == DEFAULT_SCORE_THRESHOLD:
+        rationale = (
+            f"The GC content of {gc_content:.0%} is close to balanced, so the "
+            f"default parameters are appropriate."
+        )
+    else:
+        rationale = (
+            f"With a GC content of {gc_content:.0%}, random triplets repeat more "
+            f"often than in a balanced genome, which raises the scores of windows "
+            f"without repeats. The score threshold is scaled accordingly to "
+            f"{score_threshold} (level {score_threshold / 10}), which masks "
+            f"{masked_fraction:.1%} of the sampled bases instead of "
+            f"{masked_fractions[DEFAULT_WINDOW_SIZE]:.1%}."
+        )
+    return {
+        "n_bases": sum(len(sequence) for sequence in sequences),
+        "n_sampled_bases": n_sampled_bases,
+        "gc_content": gc_content,
+        "ambiguous_fraction": 1 - (gc_count + at_count) / n_sampled_bases,
+        "masked_fractions": masked_fractions,
+        "window_size": DEFAULT_WINDOW_SIZE,
+        "score_threshold": score_threshold,
+        "masked_fraction": masked_fraction,
+        "rationale": rationale,
+    }
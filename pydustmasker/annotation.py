@@ -0,0 +1,226 @@
+"""Overlaps between the low-complexity regions identified by pydustmasker and
+the features of a GFF3 or GTF annotation, such as genes and exons.
+
+Each DustMasker holds the regions of a single sequence, so the functions of
+this module take a mapping from the sequence IDs of the annotation (its first
+column) to the DustMasker of each sequence. Features of sequences that aren't
+in the mapping are ignored.
+"""
+
+from __future__ import annotations
+
+import gzip
+import os
+from bisect import bisect_right
+from collections.abc import Iterable, Mapping
+from dataclasses import dataclass, field
+from typing import IO, TYPE_CHECKING
+from urllib.parse import unquote
+
+from pydustmasker.errors import FileFormatError
+
+if TYPE_CHECKING:
+    from pydustmasker._pydustmasker import DustMasker
+
+# Attributes holding the name of a feature, in order of preference
+NAME_ATTRIBUTES = ("Name", "ID", "gene_name", "gene_id", "transcript_id")
+
+
+@dataclass(frozen=True)
+class Feature:
+    """A feature of a GFF3 or GTF annotation.
+
+    Attributes
+    ----------
+    seqid : str
+        The ID of the sequence where the feature is located.
+    type : str
+        The type of the feature (e.g., 'gene' or 'exon').
+    start : int
+        The position of the first base of the feature, 0-based like the
+        positions of the low-complexity regions.
+    end : int
+        The position after the last base of the feature.
+    strand : str
+        The strand of the feature ('+', '-' or '.').
+    attributes : dict of str
+        The attributes of the feature, from the last column of the annotation.
+    """
+
+    seqid: str
+    type: str
+    start: int
+    end: int
+    strand: str
+    attributes: dict[str, str] = field(default_factory=dict, compare=False)
+
+    @property
+    def name(self) -> str | None:
+        """The `Name`, `ID`, `gene_name`, `gene_id` or `transcript_id`
+        attribute of the feature, whichever comes first, or None."""
+        for key in NAME_ATTRIBUTES:
+            if key in self.attributes:
+                return self.attributes[key]
+        return None
+
+
+def parse_attributes(column: str) -> dict[str, str]:
+    """Parses the attributes column of a GFF3 (`key=value`) or GTF (`key "value"`)
+    annotation."""
+    attributes = {}
+    for pair in column.strip().split(";"):
+        pair = pair.strip()
+        if not pair:
+            continue
+        key, equals, value = pair.partition("=")
+        if equals and " " not in key:
+            attributes[key] = unquote(value)
+        else:
+            key, _, value = pair.partition(" ")
+            attributes[key] = value.strip().strip('"')
+    return attributes
+
+
+def read_features(
+    path: str | os.PathLike[str] | IO[str], types: Iterable[str] | None = None
+) -> list[Feature]:
+    """Reads the features of a GFF3 or GTF annotation.
+
+    Parameters
+    ----------
+    path : str, path-like or file object
+        The path of the annotation, which can be gzip-compressed if its name
+        ends with '.gz', or a text file object.
+    types : iterable of str, optional
+        If given, only the features of these types (e.g., `["gene"]`) are read.
+
+    Returns
+    -------
+    list of Feature
+        The features, in the order of the annotation, with 0-based start
+        positions.
+
+    Raises
+    ------
+    FileFormatError
+        If a line doesn't have the 9 columns of the format.
+    """
+    if isinstance(path, (str, os.PathLike)):
+        opener = gzip.open if os.fspath(path).endswith(".gz") else open
+        with opener(path, "rt") as file:
+            return read_features(file, types)
+    types = set(types) if types is not None else None
+    features = []
+    for line_number, line in enumerate(path, 1):
+        if line.startswith("##FASTA"):
+            break
+        if line.startswith("#") or not line.strip():
+            continue
+        columns = line.rstrip("\n").split("\t")
+        if len(columns) != 9:
+            raise FileFormatError(
+                f"line {line_number} has {len(columns)} columns instead of 9",
+                line_number,
+            )
+        if types is not None and columns[2] not in types:
+            continue
+        features.append(
+            Feature(
+                seqid=columns[0],
+                type=columns[2],
+                start=int(columns[3]) - 1,
+                end=int(columns[4]),
+                strand=columns[6],
+                attributes=parse_attributes(columns[8]),
+            )
+        )
+    return features
+
+
+def feature_masked_fractions(
+    maskers: Mapping[str, DustMasker], features: Iterable[Feature]
+) -> list[tuple[Feature, float]]:
+    """Returns the fraction of the bases of each feature that are within
+    low-complexity regions.
+
+    Parameters
+    ----------
+    maskers : mapping of str to DustMasker
+        The DustMasker of each sequence, keyed by its ID in the annotation.
+    features : iterable of Feature
+        The features, such as the genes returned by
+        `read_features(path, types=["gene"])`.
+
+    Returns
+    -------
+    list of tuples
+        Each feature of the sequences in `maskers` and the fraction of its
+        bases that are masked, in the order of `features`. Empty features have
+        a fraction of 0.
+    """
+    interval_lists = {seqid: masker.intervals for seqid, masker in maskers.items()}
+    ends = {
+        seqid: [end for _, end in intervals]
+        for seqid, intervals in interval_lists.items()
+    }
+    fractions = []
+    for feature in features:
+        if feature.seqid not in maskers:
+            continue
+        intervals = interval_lists[feature.seqid]
+        n_masked = 0
+        # The intervals are sorted and don't overlap, so the first one that
+        # can overlap the feature is the first one ending after its start
+        index = bisect_right(ends[feature.seqid], feature.start)
+        while index < len(intervals) and intervals[index][0] < feature.end:
+            start, end = intervals[index]
+            n_masked += max(0, min(end, feature.end) - max(start, feature.start))
+            index += 1
+        length = feature.end - feature.start
+        fractions.append((feature, n_masked / length if length > 0 else 0.0))
+    return fractions
+
+
+def region_features(
+    maskers: Mapping[str, DustMasker], features: Iterable[Feature]
+) -> list[tuple[str, int, int, list[Feature]]]:
+    """Returns the features that overlap each low-complexity region.
+
+    Parameters
+    ----------
+    maskers : mapping of str to DustMasker
+        The DustMasker of each sequence, keyed by its ID in the annotation.
+    features : iterable of Feature
+        The features, such as the genes and exons returned by
+        `read_features(path, types=["gene", "exon"])`.
+
+    Returns
+    -------
+    list of tuples
+        The sequence ID, start and end positions of each low-complexity
+        region, in the order of `maskers`, and the features that overlap it,
+        sorted by start position. Regions that don't overlap any feature are
+        included with an empty list.
+    """
+    features_by_seqid: dict[str, list[Feature]] = {seqid: [] for seqid in maskers}
+    for feature in features:
+        if feature.seqid in features_by_seqid:
+            features_by_seqid[feature.seqid].append(feature)
+    annotated = []
+    for seqid, masker in maskers.items():
+        sequence_features = sorted(features_by_seqid[seqid], key=lambda f: f.start)
+        next_feature = 0
+        # Features starting before the current region that may overlap it
+        active: list[Feature] = []
+        for start, end in masker.intervals:
+            while (
+                next_feature < len(sequence_features)
+                and sequence_features[next_feature].start < end
+            ):
+                active.append(sequence_features[next_feature])
+                next_feature += 1
+            # The regions are sorted, so features ending before this one can't
+            # overlap the next ones either
+            active = [feature for feature in active if feature.end > start]
+            annotated.append((seqid, start, end, list(active)))
+    return annotated
@@ -0,0 +1,99 @@
+"""Exceptions raised by pydustmasker.
+
+The exceptions raised for invalid inputs subclass `PydustmaskerError` and
+`ValueError`, so they can be caught either specifically or together with other
+invalid arguments. The warnings emitted by pydustmasker are defined here as
+well, so that they can be filtered.
+"""
+
+
+class PydustmaskerError(ValueError):
+    """Base class of the exceptions raised for invalid inputs."""
+
+
+class SequenceTooShortError(PydustmaskerError):
+    """Raised when a sequence is shorter than the minimum of 4 bases.
+
+    Attributes
+    ----------
+    length : int
+        The length of the rejected sequence.
+    """
+
+    def __init__(self, message: str, length: int) -> None:
+        super().__init__(message)
+        self.length = length
+
+
+class InvalidWindowError(PydustmaskerError):
+    """Raised when the window size is smaller than the minimum of 3.
+
+    Attributes
+    ----------
+    window_size : int
+        The rejected window size.
+    """
+
+    def __init__(self, message: str, window_size: int) -> None:
+        super().__init__(message)
+        self.window_size = window_size
+
+
+class InvalidCharacterError(PydustmaskerError):
+    """Raised when a character can't be processed at a given position, such as
+    a non-ASCII character split by the region of an edit.
+
+    Attributes
+    ----------
+    position : int
+        The position of the character in the sequence.
+    char : str
+        The offending character.
+    """
+
+    def __init__(self, message: str, position: int, char: str) -> None:
+        super().__init__(message)
+        self.position = position
+        self.char = char
+
+
+class FileFormatError(PydustmaskerError):
+    """Raised when an input file is malformed.
+
+    Attributes
+    ----------
+    line : int
+        The number of the offending line, starting at 1.
+    """
+
+    def __init__(self, message: str, line: int) -> None:
+        super().__init__(message)
+        self.line = line
+
+
+class ScanCancelledError(Exception):
+    """Raised when the scan of a sequence is stopped by its `CancelToken`.
+
+    It isn't a `PydustmaskerError`, as the inputs were valid.
+    """
+
+
+class WindowSizeWarning(UserWarning):
+    """Emitted when the window size is larger than the sequence.
+
+    The whole sequence is then scored as a single window, which gives the same
+    regions as a window size equal to the length of the sequence. Short
+    sequences, such as viral contigs, are thus scored over fewer triplets than
+    the windows of longer sequences.
+    """
+
+
+__all__ = [
+    "FileFormatError",
+    "InvalidCharacterError",
+    "InvalidWindowError",
+    "PydustmaskerError",
+    "ScanCancelledError",
+    "SequenceTooShortError",
+    "WindowSizeWarning",
+]
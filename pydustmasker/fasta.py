@@ -0,0 +1,407 @@
+"""Random access to the records of FASTA files indexed with `samtools faidx`.
+
+Only the bases of the requested region are read, so a locus of a large
+reference can be masked again without reading the whole file. Files compressed
+with bgzip are supported through their `.gzi` index (created by `bgzip -i` or
+`samtools faidx`), and only the compressed blocks that hold the region are
+decompressed.
+
+The records of the FASTA objects of pyfastx and pyfaidx can be masked as well,
+with `mask_record` and `mask_records`, without converting them to strings
+beforehand.
+"""
+
+from __future__ import annotations
+
+import os
+import struct
+import zlib
+from bisect import bisect_right
+from collections.abc import Iterable, Iterator
+from dataclasses import dataclass
+from typing import IO, Any
+
+from pydustmasker._pydustmasker import DustMasker
+from pydustmasker.errors import FileFormatError
+
+# The first bytes of gzip (and bgzip) files
+GZIP_MAGIC = b"\x1f\x8b"
+# Makes zlib decompress gzip members, such as the blocks of bgzip files
+GZIP_WBITS = zlib.MAX_WBITS | 16
+# Number of compressed bytes read at once from bgzip files, about one block
+READ_SIZE = 1 << 16
+
+
+@dataclass(frozen=True)
+class FaiEntry:
+    """A record of a FASTA index (`.fai`).
+
+    Attributes
+    ----------
+    name : str
+        The name of the record.
+    length : int
+        The number of bases of the record.
+    offset : int
+        The position of its first base in the uncompressed file.
+    line_bases : int
+        The number of bases in each line, except the last one.
+    line_width : int
+        The number of bytes in each line, including the line terminator.
+    """
+
+    name: str
+    length: int
+    offset: int
+    line_bases: int
+    line_width: int
+
+    def byte_offset(self, position: int) -> int:
+        """The position in the uncompressed file of the base at `position`."""
+        line, column = divmod(position, self.line_bases)
+        return self.offset + line * self.line_width + column
+
+
+def read_fai(path: str | os.PathLike[str] | IO[str]) -> dict[str, FaiEntry]:
+    """Reads a FASTA index (`.fai`).
+
+    Parameters
+    ----------
+    path : str, path-like or file object
+        The path of the index, or a text file object.
+
+    Returns
+    -------
+    dict of str to FaiEntry
+        The records of the index, keyed by name, in the order of the file.
+
+    Raises
+    ------
+    FileFormatError
+        If a line doesn't have the 5 columns of the format, or they aren't
+        positive integers.
+    """
+    if isinstance(path, (str, os.PathLike)):
+        with open(path) as file:
+            return read_fai(file)
+    entries = {}
+    for line_number, line in enumerate(path, 1):
+        if not line.strip():
+            continue
+        columns = line.rstrip("\n").split("\t")
+        if len(columns) < 5:
+            raise FileFormatError(
+                f"line {line_number} has {len(columns)} columns instead of 5",
+                line_number,
+            )
+        try:
+            length, offset, line_bases, line_width = map(int, columns[1:5])
+        except ValueError:
+            raise FileFormatError(
+                f"line {line_number} has non-integer columns", line_number
+            ) from None
+        if line_bases <= 0 or line_width < line_bases:
+            raise FileFormatError(
+                f"line {line_number} has invalid line lengths", line_number
+            )
+        name = columns[0]
+        entries[name] = FaiEntry(name, length, offset, line_bases, line_width)
+    return entries
+
+
+def read_gzi(path: str | os.PathLike[str]) -> list[tuple[int, int]]:
+    """Reads the index of a bgzip-compressed file (`.gzi`).
+
+    Parameters
+    ----------
+    path : str or path-like
+        The path of the index.
+
+    Returns
+    -------
+    list of tuples
+        The position of each compressed block in the compressed file and of
+        its first byte in the uncompressed file, starting with the first block
+        at (0, 0).
+
+    Raises
+    ------
+    FileFormatError
+        If the index is truncated.
+    """
+    with open(path, "rb") as file:
+        data = file.read()
+    if len(data) < 8:
+        raise FileFormatError("the .gzi index is truncated", 1)
+    (n_blocks,) = struct.unpack_from("<Q", data)
+    if len(data) < 8 + 16 * n_blocks:
+        raise FileFormatError("the .gzi index is truncated", 1)
+    offsets = struct.unpack_from(f"<{2 * n_blocks}Q", data, 8)
+    return [(0, 0), *zip(offsets[::2], offsets[1::2])]
+
+
+class IndexedFasta:
+    """A FASTA file indexed with `samtools faidx`, whose regions can be read
+    and masked without reading the whole file.
+
+    Parameters
+    ----------
+    path : str or path-like
+        The path of the FASTA file, which can be compressed with bgzip.
+    fai_path : str or path-like, optional
+        The path of its index, `path` followed by '.fai' by default.
+    gzi_path : str or path-like, optional
+        The path of the index of the compressed blocks of a bgzip-compressed
+        file, `path` followed by '.gzi' by default. It is only read if the file
+        is compressed.
+
+    Attributes
+    ----------
+    entries : dict of str to FaiEntry
+        The records of the file, keyed by name.
+
+    Raises
+    ------
+    FileNotFoundError
+        If the file or one of its indexes doesn't exist. Files compressed with
+        gzip rather than bgzip can't be read, as they have no `.gzi` index.
+    FileFormatError
+        If an index is malformed.
+
+    Examples
+    --------
+    >>> with IndexedFasta("genome.fa.gz") as fasta:  # doctest: +SKIP
+    ...     masker = fasta.mask("chr1", 1_000_000, 1_010_000)
+    """
+
+    def __init__(
+        self,
+        path: str | os.PathLike[str],
+        fai_path: str | os.PathLike[str] | None = None,
+        gzi_path: str | os.PathLike[str] | None = None,
+    ) -> None:
+        path = os.fspath(path)
+        self.entries = read_fai(fai_path if fai_path is not None else path + ".fai")
+        self._file = open(path, "rb")
+        try:
+            compressed = self._file.read(2) == GZIP_MAGIC
+            self._blocks = (
+                read_gzi(gzi_path if gzi_path is not None else path + ".gzi")
+                if compressed
+                else None
+            )
+        except BaseException:
+            self._file.close()
+            raise
+        self._block_starts = [start for _, start in self._blocks or []]
+
+    def __enter__(self) -> IndexedFasta:
+        return self
+
+    def __exit__(self, *exc_info: object) -> None:
+        self.close()
+
+    def close(self) -> None:
+        """Closes the FASTA file."""
+        self._file.close()
+
+    @property
+    def names(self) -> list[str]:
+        """The names of the records, in the order of the file."""
+        return list(self.entries)
+
+    def __contains__(self, name: object) -> bool:
+        return name in self.entries
+
+    def _read(self, start: int, end: int) -> bytes:
+        """Reads the bytes from `start` to `end` of the uncompressed file."""
+        if self._blocks is None:
+            self._file.seek(start)
+            return self._file.read(end - start)
+        # Each block is a gzip member, so decompression starts at the block
+        # holding the first byte
+        index = bisect_right(self._block_starts, start) - 1
+        compressed_start, block_start = self._blocks[index]
+        self._file.seek(compressed_start)
+        chunks = []
+        n_bytes = 0
+        decompressor = zlib.decompressobj(GZIP_WBITS)
+        data = b""
+        while n_bytes < end - block_start:
+            if not data:
+                data = self._file.read(READ_SIZE)
+                if not data:
+                    break
+            chunk = decompressor.decompress(data)
+            chunks.append(chunk)
+            n_bytes += len(chunk)
+            # The data that follows a block belongs to the next one
+            if decompressor.eof:
+                data = decompressor.unused_data
+                decompressor = zlib.decompressobj(GZIP_WBITS)
+            else:
+                data = b""
+        return b"".join(chunks)[start - block_start : end - block_start]
+
+    def _entry_region(
+        self, name: str, start: int, end: int | None
+    ) -> tuple[FaiEntry, int, int]:
+        entry = self.entries[name]
+        end = entry.length if end is None else end
+        if not 0 <= start <= end <= entry.length:
+            raise ValueError(
+                f"invalid region '{start}..{end}' for a sequence of length "
+                f"'{entry.length}'"
+            )
+        return entry, start, end
+
+    def fetch(self, name: str, start: int = 0, end: int | None = None) -> str:
+        """Reads the bases of a region of a record.
+
+        Parameters
+        ----------
+        name : str
+            The name of the record.
+        start : int, default: 0
+            The position of the first base of the region, 0-based.
+        end : int, optional
+            The position after the last base of the region, the end of the
+            record by default.
+
+        Returns
+        -------
+        str
+            The bases of the region, without line terminators.
+
+        Raises
+        ------
+        KeyError
+            If the file has no record with this name.
+        ValueError
+            If the region isn't within the record.
+        """
+        entry, start, end = self._entry_region(name, start, end)
+        if start == end:
+            return ""
+        data = self._read(entry.byte_offset(start), entry.byte_offset(end - 1) + 1)
+        return data.replace(b"\n", b"").replace(b"\r", b"").decode("ascii")
+
+    def mask(
+        self, name: str, start: int = 0, end: int | None = None, **kwargs: Any
+    ) -> DustMasker:
+        """Identifies the low-complexity regions of a region of a record.
+
+        Parameters
+        ----------
+        name : str
+            The name of the record.
+        start : int, default: 0
+            The position of the first base of the region, 0-based.
+        end : int, optional
+            The position after the last base of the region, the end of the
+            record by default.
+        **kwargs
+            The other parameters of `DustMasker` (e.g., `window_size`).
+
+        Returns
+        -------
+        DustMasker
+            The DustMasker of the bases of the region, whose positions are
+            relative to `start`.
+
+        Raises
+        ------
+        KeyError
+            If the file has no record with this name.
+        ValueError
+            If the region isn't within the record, or the same exceptions as
+            `DustMasker`, such as a `SequenceTooShortError`.
+        """
+        return DustMasker(self.fetch(name, start, end), **kwargs)
+
+
+def record_sequence(record: Any) -> str:
+    """Returns the sequence of a record.
+
+    Parameters
+    ----------
+    record : str or object
+        A sequence, or a record of an indexing library: a `pyfastx.Sequence` or
+        a `pyfaidx.Sequence`, whose sequence is read from its `seq` attribute,
+        or a `pyfaidx.FastaRecord`, which is converted to a string.
+
+    Returns
+    -------
+    str
+        The sequence of the record.
+    """
+    if isinstance(record, str):
+        return record
+    sequence = getattr(record, "seq", record)
+    return sequence if isinstance(sequence, str) else str(sequence)
+
+
+def mask_record(record: Any, **kwargs: Any) -> DustMasker:
+    """Identifies the low-complexity regions of a record of an indexing library.
+
+    Parameters
+    ----------
+    record : str or object
+        A sequence or a record, such as a `pyfastx.Sequence` or a
+        `pyfaidx.FastaRecord` (see `record_sequence`).
+    **kwargs
+        The other parameters of `DustMasker` (e.g., `window_size`).
+
+    Returns
+    -------
+    DustMasker
+        The DustMasker of the sequence of the record.
+    """
+    return DustMasker(record_sequence(record), **kwargs)
+
+
+def mask_records(
+    fasta: Any, names: Iterable[str] | None = None, **kwargs: Any
+) -> Iterator[tuple[str, DustMasker]]:
+    """Identifies the low-complexity regions of the records of an indexed FASTA
+    file, reading them one at a time.
+
+    Parameters
+    ----------
+    fasta : pyfastx.Fasta, pyfaidx.Fasta, IndexedFasta or mapping
+        The indexed FASTA file, or any mapping from the names of the records to
+        their sequences or records (see `record_sequence`).
+    names : iterable of str, optional
+        The names of the records to mask, all of them by default, in the order
+        of the file.
+    **kwargs
+        The other parameters of `DustMasker` (e.g., `window_size`).
+
+    Yields
+    ------
+    tuple
+        The name of each record and its DustMasker. Each record is only read
+        once the previous one was masked, so that only one sequence is held in
+        memory at a time.
+
+    Raises
+    ------
+    KeyError
+        If the file has no record with one of the names.
+    """
+    if names is None:
+        names = fasta.names if isinstance(fasta, IndexedFasta) else fasta.keys()
+    for name in names:
+        record = fasta.fetch(name) if isinstance(fasta, IndexedFasta) else fasta[name]
+        yield name, mask_record(record, **kwargs)
+
+
+__all__ = [
+    "FaiEntry",
+    "IndexedFasta",
+    "mask_record",
+    "mask_records",
+    "read_fai",
+    "read_gzi",
+    "record_sequence",
+]
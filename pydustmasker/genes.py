@@ -0,0 +1,67 @@
+"""Preparation of contigs for gene finders, such as Pyrodigal.
+
+Low-complexity regions can be called as spurious genes, or disrupt the
+training of gene finders, so they are hard-masked with 'N' characters, which
+gene finders treat as unknown bases (see the `mask` parameter of Pyrodigal's
+`GeneFinder`, which prevents genes from spanning them).
+"""
+
+from __future__ import annotations
+
+from collections.abc import Iterable
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any
+
+from pydustmasker._pydustmasker import DustMasker
+from pydustmasker.fasta import record_sequence
+
+
+def mask_for_gene_finding(
+    sequences: Iterable[Any], workers: int | None = None, **kwargs: Any
+) -> list[bytes]:
+    """Hard-masks the low-complexity regions of many contigs, returning
+    sequences ready to be passed to a gene finder (e.g.,
+    `pyrodigal.GeneFinder.find_genes`).
+
+    Parameters
+    ----------
+    sequences : iterable of str or records
+        The sequences of the contigs, or records of an indexing library, such as
+        `pyfastx.Sequence` objects (see `fasta.record_sequence`).
+    workers : int, optional
+        The number of threads that mask the contigs at the same time, which is
+        chosen by `concurrent.futures.ThreadPoolExecutor` by default. Contigs
+        are masked in parallel, as DustMasker releases the GIL while it scans
+        a sequence.
+    **kwargs
+        The other parameters of `DustMasker` (e.g., `window_size`). Its
+        `threads` parameter splits each contig among threads, which is only
+        useful for long contigs processed by few workers.
+
+    Returns
+    -------
+    list of bytes
+        The contigs with their low-complexity regions replaced with 'N'
+        characters, as ASCII bytes, in the order of `sequences`.
+
+    Raises
+    ------
+    ValueError, TypeError, OverflowError
+        The same exceptions as `DustMasker`, such as a `SequenceTooShortError`.
+
+    Examples
+    --------
+    >>> gene_finder = pyrodigal.GeneFinder(meta=True, mask=True)  # doctest: +SKIP
+    >>> for contig in mask_for_gene_finding(contigs, workers=8):  # doctest: +SKIP
+    ...     genes = gene_finder.find_genes(contig)
+    """
+
+    def mask(sequence: Any) -> bytes:
+        masker = DustMasker(record_sequence(sequence), **kwargs)
+        return masker.mask(hard=True).encode()
+
+    with ThreadPoolExecutor(workers) as executor:
+        return list(executor.map(mask, sequences))
+
+
+__all__ = ["mask_for_gene_finding"]
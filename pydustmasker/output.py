@@ -0,0 +1,267 @@
+"""Export of the low-complexity regions in the coordinate system of other tools.
+
+The positions of the regions are 0-based and their ends are exclusive, like in
+BED files (the '0-based' coordinate system). Formats such as GFF, and the
+interval output of `dustmasker`, use 1-based positions with inclusive ends
+instead (the '1-based' coordinate system), where the region (23, 30) starts at
+24 and ends at 30.
+
+BED files can have more than three columns, which genome browsers use to
+display the regions: their names, scores, strands and colors.
+"""
+
+from __future__ import annotations
+
+import os
+from collections.abc import Iterable, Mapping
+from typing import IO, Any, Literal, Union
+
+from pydustmasker._pydustmasker import DustMasker, Region
+
+Coordinates = Literal["0-based", "1-based"]
+Intervals = Union[DustMasker, Iterable[Iterable[int]]]
+
+COORDINATE_SYSTEMS = ("0-based", "1-based")
+# Classes of regions by the length of their repeat unit
+REPEAT_CLASSES = {1: "homopolymer", 2: "dinucleotide", 3: "trinucleotide"}
+# Colors of the regions of each class in BED9 files, as red, green and blue values
+REPEAT_CLASS_COLORS = {
+    "homopolymer": "228,26,28",
+    "dinucleotide": "55,126,184",
+    "trinucleotide": "77,175,74",
+    "tandem_repeat": "152,78,163",
+    "low_complexity": "255,127,0",
+}
+
+
+def _start_offset(coordinates: str) -> int:
+    """The number added to the 0-based start of a region."""
+    if coordinates not in COORDINATE_SYSTEMS:
+        raise ValueError(
+            f"invalid coordinate system '{coordinates}', must be '0-based' or "
+            "'1-based'"
+        )
+    return int(coordinates == "1-based")
+
+
+def convert_intervals(
+    intervals: Intervals, coordinates: Coordinates = "0-based"
+) -> list[tuple[int, int]]:
+    """Returns the start and end positions of regions in a coordinate system.
+
+    Parameters
+    ----------
+    intervals : DustMasker or iterable
+        A DustMasker, whose low-complexity regions are converted, or the start
+        and end positions of regions, 0-based with exclusive ends (e.g., the
+        `Region` objects returned by `DustMasker.regions()`).
+    coordinates : {'0-based', '1-based'}, default: '0-based'
+        The coordinate system of the returned positions: 0-based with
+        exclusive ends, like in BED files, or 1-based with inclusive ends, like
+        in GFF files.
+
+    Returns
+    -------
+    list of tuples
+        The start and end positions of the regions.
+
+    Raises
+    ------
+    ValueError
+        If `coordinates` isn't a coordinate system.
+
+    Examples
+    --------
+    >>> masker = DustMasker("CGTATATATATAGTATGCGTACTGGGGGGGCT")
+    >>> convert_intervals(masker, "1-based")
+    [(24, 30)]
+    """
+    offset = _start_offset(coordinates)
+    if isinstance(intervals, DustMasker):
+        intervals = intervals.intervals
+    return [(start + offset, end) for start, end in intervals]
+
+
+def write_intervals(
+    file: str | os.PathLike[str] | IO[str],
+    maskers: Mapping[str, Intervals] | Iterable[tuple[str, Intervals]],
+    coordinates: Coordinates = "0-based",
+    format: Literal["table", "interval"] = "table",
+) -> int:
+    """Writes the low-complexity regions of many sequences to a file.
+
+    Parameters
+    ----------
+    file : str, path-like or file object
+        The path of the file, which is overwritten, or a text file object.
+    maskers : mapping or iterable of tuples
+        The DustMasker (or the regions) of each sequence, keyed by the name of
+        the sequence, or pairs of names and DustMaskers, such as the ones
+        yielded by `fasta.mask_records`.
+    coordinates : {'0-based', '1-based'}, default: '0-based'
+        The coordinate system of the written positions (see
+        `convert_intervals`).
+    format : {'table', 'interval'}, default: 'table'
+        The format of the file: a line with the name, start and end of each
+        region, separated by tabs (a BED file if the positions are 0-based),
+        or the interval format of `dustmasker`, where a line with '>' and the
+        name of each sequence is followed by a line with the start and end of
+        each region, separated by ' - '.
+
+    Returns
+    -------
+    int
+        The number of regions written.
+
+    Raises
+    ------
+    ValueError
+        If `coordinates` isn't a coordinate system, or `format` isn't a format.
+    """
+    _start_offset(coordinates)
+    if format not in ("table", "interval"):
+        raise ValueError(f"invalid format '{format}', must be 'table' or 'interval'")
+    if isinstance(file, (str, os.PathLike)):
+        with open(file, "w") as handle:
+            return write_intervals(handle, maskers, coordinates, format)
+    if isinstance(maskers, Mapping):
+        maskers = maskers.items()
+    n_intervals = 0
+    for name, intervals in maskers:
+        if format == "interval":
+            file.write(f">{name}\n")
+        for start, end in convert_intervals(intervals, coordinates):
+            if format == "interval":
+                file.write(f"{start} - {end}\n")
+            else:
+                file.write(f"{name}\t{start}\t{end}\n")
+            n_intervals += 1
+    return n_intervals
+
+
+def repeat_class(region: Region) -> str:
+    """Classifies a region by the repeat unit of its bases.
+
+    Parameters
+    ----------
+    region : Region
+        A region returned by `DustMasker.regions()`.
+
+    Returns
+    -------
+    str
+        'homopolymer', 'dinucleotide' or 'trinucleotide' if the region
+        repeats a unit of 1, 2 or 3 bases, 'tandem_repeat' if it repeats a
+        longer unit, and 'low_complexity' if it doesn't repeat any unit (e.g.,
+        regions enriched in a few bases).
+    """
+    if region.repeat_unit is None:
+        return "low_complexity"
+    return REPEAT_CLASSES.get(len(region.repeat_unit), "tandem_repeat")
+
+
+def write_bed(
+    file: str | os.PathLike[str] | IO[str],
+    maskers: Mapping[str, Any] | Iterable[tuple[str, Any]],
+    columns: Literal[3, 4, 5, 6, 9] = 3,
+    name: str | None = "low_complexity",
+    strand: str = ".",
+    score_scale: float = 100.0,
+    colors: Mapping[str, str] | None = None,
+) -> int:
+    """Writes the low-complexity regions of many sequences to a BED file.
+
+    Parameters
+    ----------
+    file : str, path-like or file object
+        The path of the file, which is overwritten, or a text file object.
+    maskers : mapping or iterable of tuples
+        The DustMasker of each sequence, keyed by the name of the sequence, or
+        pairs of names and DustMaskers. With 3 or 4 columns, the start and end
+        positions of the regions can be given instead, and with more columns,
+        the `Region` objects returned by `DustMasker.regions()`.
+    columns : {3, 4, 5, 6, 9}, default: 3
+        The number of columns of the file: the name of the sequence and the
+        positions of each region, followed by its name (BED4), its score
+        (BED5), its strand (BED6), and the positions of its thick part, which
+        spans the whole region, and its color (BED9).
+    name : str, optional
+        The name of the regions. If None, regions are named after their class
+        (see `repeat_class`).
+    strand : {'.', '+', '-'}, default: '.'
+        The strand of the regions.
+    score_scale : float, default: 100.0
+        The factor that converts the DUST score of each region to the integer
+        scores of BED files, which are limited to 1000. By default, regions
+        with scores of 10 or more are given a score of 1000.
+    colors : mapping of str to str, optional
+        The color of the regions of each class, as comma-separated red, green
+        and blue values (e.g., '255,0,0'), replacing the ones of
+        `REPEAT_CLASS_COLORS`.
+
+    Returns
+    -------
+    int
+        The number of regions written.
+
+    Raises
+    ------
+    ValueError
+        If the number of columns or the strand is invalid, or if scores or
+        classes are needed and the regions are given as positions.
+
+    Examples
+    --------
+    >>> write_bed("dust.bed", {"chr1": masker}, columns=9)  # doctest: +SKIP
+    """
+    if columns not in (3, 4, 5, 6, 9):
+        raise ValueError(
+            f"invalid number of columns '{columns}', must be 3, 4, 5, 6 or 9"
+        )
+    if strand not in (".", "+", "-"):
+        raise ValueError(f"invalid strand '{strand}', must be '.', '+' or '-'")
+    if isinstance(file, (str, os.PathLike)):
+        with open(file, "w") as handle:
+            return write_bed(
+                handle, maskers, columns, name, strand, score_scale, colors
+            )
+    colors = {**REPEAT_CLASS_COLORS, **(colors or {})}
+    # Scores and classes are computed from the bases of each region
+    needs_regions = columns >= 5 or (columns == 4 and name is None)
+    if isinstance(maskers, Mapping):
+        maskers = maskers.items()
+    n_intervals = 0
+    for sequence_name, masker in maskers:
+        if isinstance(masker, DustMasker):
+            regions = masker.regions() if needs_regions else masker.intervals
+        else:
+            regions = masker
+        for region in regions:
+            if needs_regions and not hasattr(region, "score"):
+                raise ValueError(
+                    f"the regions of '{sequence_name}' must be given as a DustMasker "
+                    f"or Region objects to write {columns} columns"
+                )
+            start, end = region
+            fields = [sequence_name, str(start), str(end)]
+            if columns >= 4:
+                fields.append(repeat_class(region) if name is None else name)
+            if columns >= 5:
+                fields.append(str(min(1000, round(region.score * score_scale))))
+            if columns >= 6:
+                fields.append(strand)
+            if columns == 9:
+                fields += [str(start), str(end), colors[repeat_class(region)]]
+            file.write("\t".join(fields) + "\n")
+            n_intervals += 1
+    return n_intervals
+
+
+__all__ = [
+    "COORDINATE_SYSTEMS",
+    "REPEAT_CLASS_COLORS",
+    "convert_intervals",
+    "repeat_class",
+    "write_bed",
+    "write_intervals",
+]
@@ -0,0 +1,95 @@
+"""Plots of the low-complexity regions identified by pydustmasker.
+
+The plots are drawn with matplotlib, which is not a dependency of pydustmasker
+and can be installed with the `plot` extra (`pip install pydustmasker[plot]`).
+"""
+
+from __future__ import annotations
+
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from matplotlib.figure import Figure
+
+    from pydustmasker._pydustmasker import DustMasker
+
+
+def masked_fractions(masker: DustMasker, bin_size: int = 10_000) -> list[float]:
+    """Returns the fraction of masked bases in consecutive bins of the sequence.
+
+    Parameters
+    ----------
+    masker : DustMasker
+        The low-complexity regions of a sequence.
+    bin_size : int, default: 10000
+        The number of bases of each bin. The last bin can be shorter.
+
+    Returns
+    -------
+    list of float
+        The fraction of bases of each bin that are within low-complexity
+        regions.
+
+    Raises
+    ------
+    ValueError
+        If the bin size is not positive.
+    """
+    if bin_size <= 0:
+        raise ValueError(f"invalid bin size '{bin_size}', must be positive")
+    # The positions of the intervals are byte offsets into the sequence
+    length = len(masker.mask_array)
+    n_bins = (length + bin_size - 1) // bin_size
+    masked = [0] * n_bins
+    for start, end in masker.intervals:
+        while start < end:
+            bin_index = start // bin_size
+            bin_end = min(end, (bin_index + 1) * bin_size)
+            masked[bin_index] += bin_end - start
+            start = bin_end
+    return [
+        n_masked / (min(length, (i + 1) * bin_size) - i * bin_size)
+        for i, n_masked in enumerate(masked)
+    ]
+
+
+def plot_density(masker: DustMasker, bin_size: int = 10_000) -> Figure:
+    """Plots the fraction of masked bases along the sequence.
+
+    Parameters
+    ----------
+    masker : DustMasker
+        The low-complexity regions of a sequence.
+    bin_size : int, default: 10000
+        The number of bases over which the masked fraction is computed.
+
+    Returns
+    -------
+    matplotlib.figure.Figure
+        A figure with the masked fraction of each bin, plotted at the position
+        where the bin starts.
+
+    Raises
+    ------
+    ImportError
+        If matplotlib is not installed.
+    ValueError
+        If the bin size is not positive.
+    """
+    try:
+        from matplotlib.figure import Figure
+    except ImportError as error:
+        raise ImportError(
+            "plot_density requires matplotlib, which can be installed with "
+            "`pip install pydustmasker[plot]`"
+        ) from error
+    fractions = masked_fractions(masker, bin_size)
+    positions = [i * bin_size for i in range(len(fractions))]
+    figure = Figure(figsize=(10, 3), layout="constrained")
+    ax = figure.add_subplot()
+    ax.step(positions, fractions, where="post")
+    ax.set_xlim(0, len(masker.mask_array))
+    ax.set_ylim(0, 1)
+    ax.set_xlabel("Position (bp)")
+    ax.set_ylabel("Masked fraction")
+    return figure
@@ -0,0 +1,129 @@
+use dustmasker::intervals::{Intervals, Pairs};
+use pyo3::{exceptions::PyBufferError, ffi, prelude::*};
+use std::{
+    ffi::CStr,
+    os::raw::{c_int, c_void},
+    ptr,
+    sync::Arc,
+};
+
+/// The Rust allocation exposed by a `Buffer`
+enum Data {
+    /// The flat start and end positions of the intervals, shared with the
+    /// `DustMasker` that found them
+    Intervals(Arc<Intervals>),
+    /// One boolean per base
+    Mask(Vec<u8>),
+    /// One floating-point value per element
+    Values(Vec<f64>),
+    /// One unsigned integer per element
+    Integers(Vec<u64>),
+}
+
+/// Exposes a Rust allocation through the buffer protocol, so that `memoryview`,
+/// NumPy or Arrow can read it in place, without copying it or creating a
+/// Python object per element. The allocation is kept alive as long as any view
+/// of it exists.
+#[pyclass(frozen)]
+pub struct Buffer {
+    data: Data,
+    /// Number of elements, pointed to by the `shape` of the views
+    n_items: isize,
+    /// Size of each element in bytes, pointed to by the `strides` of the views
+    itemsize: isize,
+}
+
+impl Buffer {
+    pub fn from_intervals(intervals: Arc<Intervals>) -> Self {
+        let itemsize = match intervals.pairs() {
+            Pairs::Compact(_) => std::mem::size_of::<u32>(),
+            Pairs::Wide(_) => std::mem::size_of::<u64>(),
+        };
+        Buffer {
+            n_items: 2 * intervals.len() as isize,
+            itemsize: itemsize as isize,
+            data: Data::Intervals(intervals),
+        }
+    }
+
+    pub fn from_mask(mask: Vec<u8>) -> Self {
+        Buffer {
+            n_items: mask.len() as isize,
+            itemsize: 1,
+            data: Data::Mask(mask),
+        }
+    }
+
+    pub fn from_values(values: Vec<f64>) -> Self {
+        Buffer {
+            n_items: values.len() as isize,
+            itemsize: std::mem::size_of::<f64>() as isize,
+            data: Data::Values(values),
+        }
+    }
+
+    pub fn from_integers(integers: Vec<u64>) -> Self {
+        Buffer {
+            n_items: integers.len() as isize,
+            itemsize: std::mem::size_of::<u64>() as isize,
+            data: Data::Integers(integers),
+        }
+    }
+
+    /// The address of the first element and the `struct` module format of the
+    /// elements
+    fn raw_parts(&self) -> (*const c_void, &'static CStr) {
+        match &self.data {
+            Data::Intervals(intervals) => match intervals.pairs() {
+                Pairs::Compact(compact) => (compact.as_ptr().cast(), c"I"),
+                Pairs::Wide(wide) => (wide.as_ptr().cast(), c"Q"),
+            },
+            Data::Mask(mask) => (mask.as_ptr().cast(), c"?"),
+            Data::Values(values) => (values.as_ptr().cast(), c"d"),
+            Data::Integers(integers) => (integers.as_ptr().cast(), c"Q"),
+        }
+    }
+}
+
+#[pymethods]
+impl Buffer {
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("view is null"));
+        }
+        if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("buffer is read-only"));
+        }
+        let buffer = slf.get();
+        let (buf, format) = buffer.raw_parts();
+        (*view).buf = buf as *mut c_void;
+        (*view).len = buffer.n_items * buffer.itemsize;
+        (*view).readonly = 1;
+        (*view).itemsize = buffer.itemsize;
+        (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+            format.as_ptr() as *mut _
+        } else {
+            ptr::null_mut()
+        };
+        (*view).ndim = 1;
+        // The shape and strides point to the `Buffer`, which outlives the view
+        (*view).shape = if (flags & ffi::PyBUF_ND) == ffi::PyBUF_ND {
+            &buffer.n_items as *const isize as *mut isize
+        } else {
+            ptr::null_mut()
+        };
+        (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+            &buffer.itemsize as *const isize as *mut isize
+        } else {
+            ptr::null_mut()
+        };
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        (*view).obj = slf.into_any().into_ptr();
+        Ok(())
+    }
+}
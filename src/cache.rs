@@ -0,0 +1,125 @@
+use dustmasker::intervals::Intervals;
+use pyo3::{prelude::*, types::PyString};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// Intervals found in a sequence with a given set of parameters
+struct Entry {
+    /// Kept to rule out hash collisions, without copying the sequence
+    sequence: Py<PyString>,
+    window_size: usize,
+    score_threshold: usize,
+    intervals: Arc<Intervals>,
+}
+
+/// Size-bounded cache of the intervals of the last processed sequences, keyed
+/// by a hash of the sequence and parameters. The oldest entries are evicted
+/// first once the cache is full, and it is disabled while its capacity is 0.
+#[derive(Default)]
+pub struct Cache {
+    capacity: usize,
+    entries: HashMap<u64, Entry>,
+    /// Keys in insertion order
+    order: VecDeque<u64>,
+    hits: usize,
+    misses: usize,
+}
+
+/// The cache shared by all the `DustMasker` instances
+pub fn global() -> MutexGuard<'static, Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn key(sequence: &str, window_size: usize, score_threshold: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (sequence, window_size, score_threshold).hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Cache {
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Set the maximum number of entries, evicting the oldest ones if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Remove all the entries and reset the statistics
+    pub fn clear(&mut self) {
+        *self = Cache {
+            capacity: self.capacity,
+            ..Default::default()
+        };
+    }
+
+    /// The intervals previously found in `sequence` with the same parameters
+    pub fn get(
+        &mut self,
+        sequence: &Bound<'_, PyString>,
+        window_size: usize,
+        score_threshold: usize,
+    ) -> PyResult<Option<Arc<Intervals>>> {
+        let sequence_str = sequence.to_str()?;
+        let key = key(sequence_str, window_size, score_threshold);
+        let intervals = match self.entries.get(&key) {
+            Some(entry)
+                if entry.window_size == window_size
+                    && entry.score_threshold == score_threshold
+                    && (entry.sequence.is(sequence)
+                        || entry.sequence.bind(sequence.py()).to_str()? == sequence_str) =>
+            {
+                Some(Arc::clone(&entry.intervals))
+            }
+            _ => None,
+        };
+        match intervals {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        Ok(intervals)
+    }
+
+    pub fn insert(
+        &mut self,
+        sequence: &Bound<'_, PyString>,
+        window_size: usize,
+        score_threshold: usize,
+        intervals: Arc<Intervals>,
+    ) -> PyResult<()> {
+        let key = key(sequence.to_str()?, window_size, score_threshold);
+        let entry = Entry {
+            sequence: sequence.clone().unbind(),
+            window_size,
+            score_threshold,
+            intervals,
+        };
+        // A colliding entry is replaced, keeping its position in the order
+        if self.entries.insert(key, entry).is_none() {
+            self.order.push_back(key);
+            self.evict();
+        }
+        Ok(())
+    }
+
+    fn evict(&mut self) {
+        while self.order.len() > self.capacity {
+            if let Some(key) = self.order.pop_front() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// The number of hits and misses since the cache was last cleared, its
+    /// capacity and its number of entries
+    pub fn info(&self) -> (usize, usize, usize, usize) {
+        (self.hits, self.misses, self.capacity, self.entries.len())
+    }
+}
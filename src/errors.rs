@@ -0,0 +1,75 @@
+//! Conversion of the errors to the exceptions of `pydustmasker.errors`
+
+// The code generated by `import_exception!` in PyO3 0.22 checks a feature of PyO3
+#![allow(unexpected_cfgs)]
+
+use dustmasker::{Cancelled, InputError};
+use pyo3::{exceptions::PyValueError, import_exception, prelude::*, PyErr};
+
+import_exception!(pydustmasker.errors, SequenceTooShortError);
+import_exception!(pydustmasker.errors, InvalidWindowError);
+import_exception!(pydustmasker.errors, InvalidCharacterError);
+import_exception!(pydustmasker.errors, ScanCancelledError);
+import_exception!(pydustmasker.errors, WindowSizeWarning);
+
+/// The exception raised for an invalid input, which is a `ValueError` unless
+/// `pydustmasker.errors` has a more specific one
+pub fn input(error: InputError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        InputError::SequenceLengthError(length) => {
+            SequenceTooShortError::new_err((message, length))
+        }
+        InputError::WindowSizeError(window_size) => {
+            InvalidWindowError::new_err((message, window_size))
+        }
+        InputError::PackedLengthError(..)
+        | InputError::NBlockError(..)
+        | InputError::AlphabetSizeError(..)
+        | InputError::AlphabetCharacterError(..)
+        | InputError::KmerSizeError(..)
+        | InputError::LevelError(..)
+        | InputError::SimulationRangeError(..)
+        | InputError::SimulationLengthError(..)
+        | InputError::MutationRateError(..)
+        | InputError::StepError(..)
+        | InputError::ThresholdOverrideError(..) => PyValueError::new_err(message),
+    }
+}
+
+/// The exception raised when a scan is cancelled
+pub fn cancelled(error: Cancelled) -> PyErr {
+    ScanCancelledError::new_err(error.to_string())
+}
+
+/// The error raised when `position` falls within a non-ASCII character of
+/// `sequence`, reporting the position where the character starts
+pub fn split_character(sequence: &str, position: usize) -> PyErr {
+    let char_start = (0..position)
+        .rev()
+        .find(|&i| sequence.is_char_boundary(i))
+        .unwrap_or(0);
+    let char = sequence[char_start..].chars().next().unwrap_or_default();
+    InvalidCharacterError::new_err((
+        format!("position '{position}' splits the non-ASCII character '{char}' at '{char_start}'"),
+        char_start,
+        char.to_string(),
+    ))
+}
+
+/// Warn that the window is larger than a sequence of `length` bases, which is
+/// then scored as a single window
+pub fn warn_window_size(py: Python<'_>, window_size: usize, length: usize) -> PyResult<()> {
+    if window_size <= length {
+        return Ok(());
+    }
+    // The message doesn't include the lengths, so that the default filter only
+    // shows it once for a batch of short sequences
+    PyErr::warn_bound(
+        py,
+        &py.get_type_bound::<WindowSizeWarning>(),
+        "window size is larger than the sequence, which is scored as a single \
+         window (see `DustMasker.effective_window_size`)",
+        1,
+    )
+}
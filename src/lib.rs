@@ -1,29 +1,44 @@
-mod sdust;
+// The code generated by PyO3 0.22 for methods returning `PyResult` triggers this lint
+#![allow(clippy::useless_conversion)]
 
-use crate::sdust::SymmetricDust;
-use pyo3::{exceptions::PyValueError, prelude::*};
-use thiserror::Error;
+mod buffer;
+mod cache;
+mod errors;
 
-const MIN_SEQUENCE_LENGTH: usize = 4;
-const MIN_WINDOW_SIZE: usize = 3;
-
-#[derive(Error, Debug)]
-pub enum InputError {
-    #[error("sequence is too short, it must be at least 4 characters long")]
-    SequenceLengthError(usize),
-    #[error("invalid window size '{0}', must be at least '3'")]
-    WindowSizeError(usize),
-}
-
-fn validate_inputs(sequence: &str, window_size: usize) -> Result<(), InputError> {
-    if sequence.len() < MIN_SEQUENCE_LENGTH {
-        return Err(InputError::SequenceLengthError(sequence.len()));
-    }
-    if window_size < MIN_WINDOW_SIZE {
-        return Err(InputError::WindowSizeError(window_size));
-    }
-    Ok(())
-}
+use buffer::Buffer;
+use dustmasker::alphabet::{bisulfite_score_threshold, Alphabet};
+use dustmasker::composition::{Composition, ScanDetails};
+use dustmasker::config::DustConfig;
+use dustmasker::interval_set;
+use dustmasker::intervals::Intervals;
+use dustmasker::kmers::{self, KmerCursor};
+use dustmasker::masker::{AlphabetDustMasker, AmbiguousRunsMasker, Masker, RegionalDustMasker};
+use dustmasker::metrics;
+use dustmasker::sdust::{
+    Interval, SymmetricDust, ThresholdOverride, WindowState, DEFAULT_KMER_SIZE,
+};
+use dustmasker::spill::SpillingIntervals;
+use dustmasker::stream::StreamingDust;
+use dustmasker::summary::{RecordSummary, Summary};
+use dustmasker::sweep::SweepPoint;
+use dustmasker::{
+    count, edit, parallel, strand, twobit, validate_inputs, validate_kmer_size,
+    validate_threshold_overrides, Cancelled, InputError, MIN_SEQUENCE_LENGTH,
+};
+use pyo3::{
+    buffer::PyBuffer,
+    exceptions::{PyIndexError, PyTypeError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::{PyBytes, PyDict, PyIterator, PyMemoryView, PySlice, PyString},
+};
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /// Identify and mask low-complexity regions in nucleotide sequences using the
 /// symmetric DUST algorithm from DustMasker.
@@ -38,7 +53,103 @@ fn validate_inputs(sequence: &str, window_size: usize) -> Result<(), InputError>
 ///     The length of the window used by symmetric DUST algorithm. The minimum
 ///     allowed value is 3.
 /// score_threshold : int, default: 20
-///     Score threshold for subwindows. The minimum allowed value is 0.
+///     Score threshold for subwindows, which is 10 times the mean score per
+///     triplet above which a region is masked, like the `-level` option of
+///     NCBI dustmasker. The minimum allowed value is 0.
+/// level : float, optional
+///     The mean score per triplet above which a region is masked (e.g., 2.0
+///     for dustmasker's `-level 20`). If given, it is converted to the
+///     equivalent `score_threshold`, which it replaces, so it must be a
+///     non-negative multiple of 0.1.
+/// threshold_overrides : list of tuples, optional
+///     Regions of the sequence where other score thresholds are used, given as
+///     their start and end positions and their score threshold (e.g., a
+///     stricter threshold within pericentromeric regions), which must not
+///     overlap. They are applied in a single scan, where each window is
+///     scored with the threshold of its last base, so the regions that span
+///     the bounds of an override depend on the thresholds on both sides.
+///     Sequences with overrides are processed in a single thread and their
+///     results are never cached. They can't be combined with `alphabet`,
+///     `bisulfite` or `kmer_size`.
+/// mask_ambiguous : bool, default: False
+///     If True, the runs of ambiguous characters (the ASCII characters other
+///     than A, C, G and T, such as N) are reported as low-complexity regions
+///     as well, merged with the adjacent ones, so that `intervals` holds all
+///     the bases that can't be trusted. Their results are never cached.
+/// allow_short : bool, default: False
+///     If True, sequences shorter than 4 characters, which can't hold a
+///     low-complexity region, have no regions instead of raising a
+///     `SequenceTooShortError`, so that stray tiny records don't abort the
+///     processing of a whole file.
+/// threads : int, default: 1
+///     Number of threads used to process the sequence. If 0, one thread per
+///     available CPU core is used. Long sequences are split into chunks that are
+///     processed in parallel, producing the same intervals as a single thread.
+///     Ignored in Pyodide, which doesn't support threads.
+/// spill_threshold : int, optional
+///     If given, once more than this number of low-complexity regions are found,
+///     their positions are written to a temporary file that is memory-mapped
+///     instead of being kept in memory. This bounds the memory usage for
+///     sequences with extreme numbers of low-complexity regions. The file is
+///     deleted once it is no longer used. Ignored in Pyodide, where files are
+///     kept in memory.
+/// alphabet : list of str, optional
+///     The symbols compared by the algorithm instead of 'A', 'C', 'G' and 'T',
+///     given as the ASCII characters that stand for each one of them (e.g.,
+///     `["Aa", "Cc", "Gg", "Tt", "Mm"]` for a methylation-aware alphabet). Up to
+///     6 symbols are supported, and characters that aren't part of any symbol
+///     are considered ambiguous. Sequences with a custom alphabet are always
+///     processed in a single thread and their results are never cached.
+/// bisulfite : {'CT', 'GA'}, optional
+///     If given, the sequence is a bisulfite-converted (or EM-seq) read. The
+///     bases that the conversion turns into each other are compared as a
+///     single symbol, C and T for reads of the original strands ('CT') or G
+///     and A for reads of the complementary strands ('GA'), and the score
+///     threshold is scaled by 27/8 to account for the reduced complexity of
+///     converted reads, which are otherwise massively masked for being T-rich
+///     (or A-rich). `score_threshold` and `level` keep the values given for
+///     unconverted sequences. It can't be combined with `alphabet`, and like
+///     custom alphabets, converted reads are processed in a single thread and
+///     their results are never cached.
+/// kmer_size : int, default: 3
+///     The length of the k-mers whose repetitions are scored, between 2 and 5
+///     and at most the window size. The original algorithm scores triplets,
+///     and other sizes can be used to experiment with dinucleotide or
+///     tetranucleotide complexity. Like custom alphabets, other sizes are
+///     always processed in a single thread and their results are never cached.
+/// composition : bool, default: False
+///     If True, the base composition of the sequence is stored in the
+///     `composition` attribute. It is gathered while the sequence is scanned,
+///     unless the sequence is processed with multiple threads.
+/// callback : callable, optional
+///     If given, it is called with lists of up to `batch_size` low-complexity
+///     regions, as tuples of their start and end positions, as they are found,
+///     so that the regions of long sequences can be streamed to a database or
+///     a queue during the scan. The regions are passed in order, and the
+///     regions of a sequence processed with multiple threads or found in the
+///     cache are passed once the scan ends. If the callback raises an
+///     exception, the remaining regions aren't passed to it and the exception
+///     is raised once the scan ends.
+/// batch_size : int, default: 1000
+///     The number of regions passed to each call of `callback`, except the
+///     last one. The minimum allowed value is 1.
+/// cancel : CancelToken, optional
+///     If given, the scan stops once the token is cancelled, for example from
+///     another thread. Regardless of it, the scan checks for signals, such as
+///     the one of Ctrl-C, every million bases, so that long sequences can be
+///     interrupted with a `KeyboardInterrupt`.
+/// progress : callable, optional
+///     If given, it is called with the number of bases scanned and the number
+///     of low-complexity regions found so far, roughly every
+///     `progress_interval` bases and once the scan ends, so that the progress
+///     of chromosome-scale sequences can be displayed. With multiple threads,
+///     it is called from the calling thread as the threads progress, but the
+///     regions are only counted once the whole sequence is scanned. If it
+///     raises an exception, the scan stops and the exception is raised.
+/// progress_interval : int, default: 10000000
+///     The number of bases scanned between two calls of `progress`. The
+///     minimum allowed value is 1, but the progress is checked every 1048576
+///     bases.
 ///
 /// Attributes
 /// ----------
@@ -46,49 +157,814 @@ fn validate_inputs(sequence: &str, window_size: usize) -> Result<(), InputError>
 ///     A string representing the nucleotide sequence that was provided as input.
 /// window_size : int
 ///     The length of the window used by symmetric DUST algorithm.
+/// effective_window_size : int
+///     The length of the windows that were scored, which is the length of the
+///     sequence if it is shorter than `window_size`. In that case, the whole
+///     sequence is scored as a single window, with the same results as a
+///     window size equal to its length, and a `WindowSizeWarning` is emitted.
 /// score_threshold : int
 ///     Score threshold for subwindows.
+/// level : float
+///     The mean score per triplet equivalent to the score threshold.
+/// kmer_size : int
+///     The length of the k-mers whose repetitions are scored.
 /// Intervals: list of tuples
 ///    A immutable list of tuples representing the start and end positions of
 ///    the low-complexity regions identified in the sequence.
+/// interval_array : memoryview
+///     The start and end positions of the low-complexity regions as a flat
+///     array of unsigned integers (start_1, end_1, start_2, end_2, …), which
+///     directly exposes the memory where they are stored, without copying it.
+///     Positions are stored as 32-bit integers (format 'I') for sequences
+///     shorter than 4 Gb and as 64-bit integers (format 'Q') otherwise or if
+///     they were spilled to a file. It can be converted to a NumPy array with
+///     `numpy.asarray`.
+/// mask_array : memoryview
+///     A boolean array (format '?') with one element per base of the sequence,
+///     which is True for bases within low-complexity regions.
 /// n_masked_bases : int
 ///     The total number of bases that were masked.
+/// minus_strand_intervals : list of tuples
+///     The low-complexity regions in the coordinates of the reverse complement
+///     of the sequence, sorted by start position.
+/// ambiguous_intervals : list of tuples
+///     The start and end positions of the runs of ambiguous characters of the
+///     sequence (the ASCII characters other than A, C, G and T, in either
+///     case, such as N), regardless of whether they are masked. They are found
+///     while the sequence is scanned, as the windows of the algorithm are
+///     reset at them. Non-ASCII characters also reset the windows, but are
+///     not reported.
+/// composition : dict or None
+///     If `composition` is True, the `length` of the sequence, its numbers of
+///     G/C (`gc_count`), A/T (`at_count`) and N (`n_count`) characters, in
+///     either case, its number of runs of N characters (`n_runs`) and the
+///     length of the longest one (`longest_n_run`), and the fraction of G and
+///     C among the A, C, G and T bases (`gc_content`). Otherwise, None.
 ///
 /// Raises
 /// ------
+/// SequenceTooShortError
+///    If the input sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
 /// ValueError
-///    If the input sequence is too short (less than 4 characters) or if the
-///    window size is too small (less than 3).
+///    If the level, the alphabet, the bisulfite conversion or the k-mer size
+///    are invalid.
 /// TypeError
 ///    If the input parameters are not of the expected type.
 /// OverflowError
-///    If a negative integer is passed as the window size or score threshold.
-#[pyclass]
+///    If a negative integer is passed as the window size, score threshold,
+///    number of threads, spill threshold or k-mer size.
+/// OSError
+///    If the temporary file used to spill the regions can't be written.
+///
+/// Warns
+/// -----
+/// WindowSizeWarning
+///    If the window size is larger than the sequence.
+///
+/// Notes
+/// -----
+/// DustMasker can be subclassed to customize the regions without modifying
+/// the algorithm, by overriding the `post_process_intervals` hook, called once
+/// the regions are found, and the `post_process_regions` hook, called by
+/// `regions()`. Hooks run after `__new__`, so subclasses that override
+/// `__init__` must call `super().__init__()`, and subclasses that take other
+/// arguments must also override `__new__`, passing only the ones of
+/// DustMasker to `super().__new__()`. Methods that return new
+/// DustMaskers, such as `edit()` or `union()`, return `DustMasker` objects,
+/// without applying the hooks.
+#[pyclass(subclass)]
 struct DustMasker {
     #[pyo3(get)]
-    sequence: String,
+    sequence: Py<PyString>,
     #[pyo3(get)]
     window_size: usize,
     #[pyo3(get)]
     score_threshold: usize,
     #[pyo3(get)]
-    intervals: Vec<(usize, usize)>,
+    kmer_size: usize,
+    /// The algorithm that identified the intervals
+    masker: Arc<dyn Masker>,
+    /// Shared with the buffers that expose the intervals to Python
+    intervals: Arc<Intervals>,
+    spill_threshold: Option<usize>,
+    /// Whether `edit` must scan the whole edited sequence again, as the
+    /// intervals can't be patched by `edit::patch`: they were found with a
+    /// custom alphabet or k-mer size, or combined with other intervals
+    full_rescan: bool,
+    /// Whether sequences that are too short have no regions instead of being
+    /// rejected, which also applies to the sequences returned by `edit`
+    allow_short: bool,
+    /// The runs of ambiguous characters of the sequence, gathered while it was
+    /// scanned
+    ambiguous_intervals: Vec<Interval>,
+    composition: Option<Composition>,
+}
+
+/// Passes the regions to a Python callable in lists of `batch_size` regions as
+/// they are found, to amortize the cost of calling it
+struct IntervalBatches<'py> {
+    callback: Bound<'py, PyAny>,
+    batch_size: usize,
+    batch: Vec<Interval>,
+    /// The first exception raised by the callback. The scan can't be
+    /// interrupted, so the regions that follow it are ignored
+    result: PyResult<()>,
+}
+
+impl<'py> IntervalBatches<'py> {
+    fn new(callback: Bound<'py, PyAny>, batch_size: usize) -> PyResult<Self> {
+        if batch_size == 0 {
+            return Err(PyValueError::new_err(
+                "invalid batch size '0', must be positive",
+            ));
+        }
+        Ok(IntervalBatches {
+            callback,
+            batch_size,
+            batch: Vec::with_capacity(batch_size),
+            result: Ok(()),
+        })
+    }
+
+    fn push(&mut self, start: usize, end: usize) {
+        if self.result.is_err() {
+            return;
+        }
+        self.batch.push((start, end));
+        if self.batch.len() == self.batch_size {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.result.is_ok() && !self.batch.is_empty() {
+            let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size));
+            self.result = self.callback.call1((batch,)).map(|_| ());
+        }
+    }
+
+    /// Pass the last regions to the callback, returning its first exception
+    fn finish(mut self) -> PyResult<()> {
+        self.flush();
+        self.result
+    }
+}
+
+/// A Python callable that is passed the number of bases scanned and of regions
+/// found so far every `interval` bases
+struct Progress {
+    callback: Py<PyAny>,
+    interval: usize,
+}
+
+impl Progress {
+    fn new(callback: Py<PyAny>, interval: usize) -> PyResult<Self> {
+        if interval == 0 {
+            return Err(PyValueError::new_err(
+                "invalid progress interval '0', must be positive",
+            ));
+        }
+        Ok(Progress { callback, interval })
+    }
+}
+
+/// Decides whether a scan must stop, because its `CancelToken` was cancelled
+/// or a signal handler, such as the one of Ctrl-C, or the progress callback
+/// raised an exception
+struct Interruption<'a> {
+    cancel: Option<&'a CancelToken>,
+    progress: Option<&'a Progress>,
+    /// The number of bases after which the progress is reported next
+    next_report: usize,
+    /// The exception raised by a signal handler or the progress callback
+    error: Option<PyErr>,
+}
+
+impl<'a> Interruption<'a> {
+    fn new(cancel: Option<&'a CancelToken>, progress: Option<&'a Progress>) -> Self {
+        Interruption {
+            cancel,
+            progress,
+            next_report: 0,
+            error: None,
+        }
+    }
+
+    fn check(&mut self, position: usize, n_intervals: usize) -> bool {
+        if self.cancel.is_some_and(CancelToken::is_cancelled) {
+            return true;
+        }
+        // Signals are only handled in the main thread, which must hold the
+        // GIL, so it is acquired again if the scan released it
+        let result = Python::with_gil(|py| {
+            py.check_signals()?;
+            match self.progress {
+                Some(progress) if position >= self.next_report => {
+                    self.next_report = position + progress.interval;
+                    progress.callback.call1(py, (position, n_intervals))?;
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        });
+        match result {
+            Ok(()) => false,
+            Err(error) => {
+                self.error = Some(error);
+                true
+            }
+        }
+    }
+
+    /// The exception raised if the scan was stopped. Otherwise, the progress
+    /// of the whole sequence of `length` bases is reported.
+    fn finish<T>(
+        self,
+        result: Result<T, Cancelled>,
+        length: usize,
+        n_intervals: usize,
+    ) -> PyResult<T> {
+        match (result, self.error) {
+            (Ok(value), _) => {
+                if let Some(progress) = self.progress {
+                    Python::with_gil(|py| progress.callback.call1(py, (length, n_intervals)))?;
+                }
+                Ok(value)
+            }
+            (Err(_), Some(error)) => Err(error),
+            (Err(cancelled), None) => Err(errors::cancelled(cancelled)),
+        }
+    }
+}
+
+/// Identify the low-complexity regions of `sequence` with `masker`, also
+/// passing them to `batches` as they are found and gathering the runs of
+/// ambiguous characters of the sequence and, if `composition` is true, its
+/// composition. The scan is stopped if `interruption` decides so.
+fn scan(
+    masker: &dyn Masker,
+    sequence: &str,
+    spill_threshold: Option<usize>,
+    composition: bool,
+    mut batches: Option<&mut IntervalBatches<'_>>,
+    mut interruption: Interruption<'_>,
+) -> PyResult<(Intervals, ScanDetails)> {
+    let mut intervals =
+        SpillingIntervals::new(Intervals::for_sequence(sequence.len()), spill_threshold);
+    let n_intervals = Cell::new(0);
+    let result = masker.process_with_details_and_interrupt(
+        sequence.as_bytes(),
+        composition,
+        &mut |start, end| {
+            if let Some(batches) = batches.as_mut() {
+                batches.push(start, end);
+            }
+            n_intervals.set(n_intervals.get() + 1);
+            intervals.push(start, end)
+        },
+        &mut |position| interruption.check(position, n_intervals.get()),
+    );
+    let details = interruption.finish(result, sequence.len(), n_intervals.get())?;
+    Ok((intervals.finish()?, details))
+}
+
+/// The bytes of memory held by an object, returned by its `memory_usage` method
+fn memory_usage_dict(
+    py: Python<'_>,
+    sequence: usize,
+    intervals: usize,
+    mapped_intervals: usize,
+    buffers: usize,
+) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("sequence", sequence)?;
+    dict.set_item("intervals", intervals)?;
+    dict.set_item("mapped_intervals", mapped_intervals)?;
+    dict.set_item("buffers", buffers)?;
+    dict.set_item("total", sequence + intervals + mapped_intervals + buffers)?;
+    Ok(dict)
+}
+
+/// How the characters of a masked sequence are normalized
+#[derive(Clone, Copy)]
+struct OutputNormalization {
+    /// Converts the unmasked bases to uppercase
+    uppercase: bool,
+    /// Replaces a base with another one, keeping its case (e.g., U with T)
+    conversion: Option<(u8, u8)>,
+    /// Replaces the IUPAC ambiguity codes with N, keeping their case
+    ambiguous_to_n: bool,
+}
+
+impl OutputNormalization {
+    fn new(uppercase: bool, convert: Option<&str>, ambiguous_to_n: bool) -> PyResult<Self> {
+        let conversion = match convert {
+            None => None,
+            Some("UT") => Some((b'U', b'T')),
+            Some("TU") => Some((b'T', b'U')),
+            Some(convert) => {
+                return Err(PyValueError::new_err(format!(
+                    "invalid conversion '{convert}', must be 'UT' or 'TU'"
+                )))
+            }
+        };
+        Ok(OutputNormalization {
+            uppercase,
+            conversion,
+            ambiguous_to_n,
+        })
+    }
+    fn is_identity(&self) -> bool {
+        !self.uppercase && self.conversion.is_none() && !self.ambiguous_to_n
+    }
+    fn apply(&self, c: char) -> char {
+        if !c.is_ascii() {
+            return c;
+        }
+        let lowercase = c.is_ascii_lowercase();
+        let mut base = c.to_ascii_uppercase() as u8;
+        if let Some((from, to)) = self.conversion {
+            if base == from {
+                base = to;
+            }
+        }
+        if self.ambiguous_to_n && b"RYSWKMBDHV".contains(&base) {
+            base = b'N';
+        }
+        if lowercase && !self.uppercase {
+            base = base.to_ascii_lowercase();
+        }
+        base as char
+    }
+    /// Appends the unmasked characters of `segment` to `output`
+    fn push_unmasked(&self, output: &mut String, segment: &str) {
+        if self.is_identity() {
+            output.push_str(segment);
+        } else {
+            output.extend(segment.chars().map(|c| self.apply(c)));
+        }
+    }
+}
+
+/// The bases of `sequence` within `range` with the low-complexity regions
+/// soft-masked, or hard-masked if `hard` is true, and the other characters
+/// normalized. The range must start and end at character boundaries.
+fn mask_range(
+    sequence: &str,
+    intervals: &Intervals,
+    hard: bool,
+    normalization: OutputNormalization,
+    range: Range<usize>,
+) -> String {
+    let mut masked_sequence = String::with_capacity(range.len());
+    let mut last_end = range.start;
+    for index in intervals.first_ending_after(range.start)..intervals.len() {
+        let Some((start, end)) = intervals.get(index) else {
+            break;
+        };
+        if start >= range.end {
+            break;
+        }
+        let (start, end) = (start.max(range.start), end.min(range.end));
+        normalization.push_unmasked(&mut masked_sequence, &sequence[last_end..start]);
+        // Intervals only span A/C/G/T bases, and runs of ambiguous ASCII characters
+        // with `mask_ambiguous`, which are single-byte characters
+        if hard {
+            masked_sequence.extend(std::iter::repeat_n('N', end - start));
+        } else {
+            masked_sequence.extend(
+                sequence[start..end]
+                    .chars()
+                    .map(|c| normalization.apply(c).to_ascii_lowercase()),
+            );
+        }
+        last_end = end;
+    }
+    normalization.push_unmasked(&mut masked_sequence, &sequence[last_end..range.end]);
+    masked_sequence
+}
+
+/// The parameters of DustMasker other than the sequence, the window size, the
+/// score threshold and the number of threads, with the same defaults
+struct Options<'a, 'py> {
+    spill_threshold: Option<usize>,
+    alphabet: Option<Vec<String>>,
+    kmer_size: usize,
+    level: Option<f64>,
+    composition: bool,
+    bisulfite: Option<&'a str>,
+    callback: Option<Bound<'py, PyAny>>,
+    batch_size: usize,
+    cancel: Option<Py<CancelToken>>,
+    progress: Option<Py<PyAny>>,
+    progress_interval: usize,
+    threshold_overrides: Option<Vec<(usize, usize, usize)>>,
+    mask_ambiguous: bool,
+    allow_short: bool,
+}
+
+impl Default for Options<'_, '_> {
+    fn default() -> Self {
+        Options {
+            spill_threshold: None,
+            alphabet: None,
+            kmer_size: DEFAULT_KMER_SIZE,
+            level: None,
+            composition: false,
+            bisulfite: None,
+            callback: None,
+            batch_size: 1000,
+            cancel: None,
+            progress: None,
+            progress_interval: 10_000_000,
+            threshold_overrides: None,
+            mask_ambiguous: false,
+            allow_short: false,
+        }
+    }
+}
+
+/// The regions combined with the ones of a DustMasker by its set operations
+#[derive(FromPyObject)]
+enum OtherIntervals<'py> {
+    Masker(PyRef<'py, DustMasker>),
+    Intervals(Vec<(usize, usize)>),
 }
 
-#[pymethods]
 impl DustMasker {
-    #[new]
-    #[pyo3(signature = (sequence, window_size=64, score_threshold=20))]
-    fn new(sequence: String, window_size: usize, score_threshold: usize) -> PyResult<DustMasker> {
-        validate_inputs(&sequence, window_size)
-            .map_err(|e| PyValueError::new_err(e.to_string()))?;
-        let intervals = SymmetricDust::process(sequence.as_bytes(), window_size, score_threshold);
+    /// Scan `sequence`, with the parameters of DustMasker that aren't given
+    /// in `options` set to their defaults
+    fn with_options<'py>(
+        sequence: Bound<'py, PyString>,
+        window_size: usize,
+        score_threshold: usize,
+        threads: usize,
+        options: Options<'_, 'py>,
+    ) -> PyResult<DustMasker> {
+        let Options {
+            spill_threshold,
+            alphabet,
+            kmer_size,
+            level,
+            composition,
+            bisulfite,
+            callback,
+            batch_size,
+            cancel,
+            progress,
+            progress_interval,
+            threshold_overrides,
+            mask_ambiguous,
+            allow_short,
+        } = options;
+        // The sequence is borrowed from the Python string rather than copied
+        let sequence_str = sequence.to_str()?;
+        let mut builder = DustConfig::builder()
+            .window_size(window_size)
+            .score_threshold(score_threshold)
+            .threads(threads)
+            .spill_threshold(spill_threshold);
+        if let Some(level) = level {
+            builder = builder.level(level);
+        }
+        let config = builder.build().map_err(errors::input)?;
+        let score_threshold = config.score_threshold();
+        if !(allow_short && sequence_str.len() < MIN_SEQUENCE_LENGTH) {
+            config
+                .check_sequence(sequence_str.as_bytes())
+                .map_err(errors::input)?;
+            errors::warn_window_size(sequence.py(), window_size, sequence_str.len())?;
+        }
+        validate_kmer_size(kmer_size, window_size).map_err(errors::input)?;
+        let alphabet = match (alphabet, bisulfite) {
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "a custom alphabet can't be combined with a bisulfite conversion",
+                ))
+            }
+            (Some(symbols), None) => Some(alphabet_from_symbols(&symbols)?),
+            (None, Some(conversion)) => Some(bisulfite_alphabet(conversion)?),
+            (None, None) => None,
+        };
+        let mut batches = callback
+            .map(|callback| IntervalBatches::new(callback, batch_size))
+            .transpose()?;
+        let progress = progress
+            .map(|callback| Progress::new(callback, progress_interval))
+            .transpose()?;
+        let custom_encoding = alphabet.is_some() || kmer_size != DEFAULT_KMER_SIZE;
+        let threshold_overrides = threshold_overrides
+            .map(|overrides| {
+                threshold_overrides_within(overrides, sequence_str.len(), custom_encoding)
+            })
+            .transpose()?;
+        // The results of custom scans are never cached nor patched by `edit`
+        let custom_scan = custom_encoding || threshold_overrides.is_some() || mask_ambiguous;
+        let masker: Arc<dyn Masker> = if let Some(overrides) = threshold_overrides {
+            Arc::new(RegionalDustMasker {
+                window_size,
+                score_threshold,
+                overrides,
+            })
+        } else if custom_encoding {
+            let alphabet = alphabet.unwrap_or_else(Alphabet::dna);
+            let score_threshold = match bisulfite {
+                Some(_) => bisulfite_score_threshold(score_threshold),
+                None => score_threshold,
+            };
+            Arc::new(AlphabetDustMasker {
+                alphabet,
+                window_size,
+                score_threshold,
+                kmer_size,
+            })
+        } else {
+            Arc::new(config.masker())
+        };
+        let masker: Arc<dyn Masker> = if mask_ambiguous {
+            Arc::new(AmbiguousRunsMasker { masker })
+        } else {
+            masker
+        };
+        let cached_intervals = {
+            let mut cache = cache::global();
+            if cache.is_enabled() && !custom_scan {
+                cache.get(&sequence, window_size, score_threshold)?
+            } else {
+                None
+            }
+        };
+        let (intervals, details) = match cached_intervals {
+            Some(intervals) => {
+                if let Some(batches) = batches.as_mut() {
+                    for (start, end) in intervals.iter() {
+                        batches.push(start, end);
+                    }
+                }
+                if let Some(progress) = &progress {
+                    progress
+                        .callback
+                        .call1(sequence.py(), (sequence_str.len(), intervals.len()))?;
+                }
+                let details = ScanDetails::of(sequence_str.as_bytes(), composition);
+                (intervals, details)
+            }
+            None => {
+                let cancel = cancel.as_ref().map(Py::get);
+                let run_scan = |batches: Option<&mut IntervalBatches<'_>>| {
+                    scan(
+                        masker.as_ref(),
+                        sequence_str,
+                        spill_threshold,
+                        composition,
+                        batches,
+                        Interruption::new(cancel, progress.as_ref()),
+                    )
+                };
+                // The GIL is released during the scan so that other threads,
+                // such as the event loop awaiting `mask_async`, can run, unless
+                // the regions are passed to a callback as they are found
+                let (intervals, details) = match batches.as_mut() {
+                    Some(batches) => run_scan(Some(batches))?,
+                    None => sequence.py().allow_threads(|| run_scan(None))?,
+                };
+                let intervals = Arc::new(intervals);
+                let mut cache = cache::global();
+                if cache.is_enabled() && !custom_scan {
+                    cache.insert(
+                        &sequence,
+                        window_size,
+                        score_threshold,
+                        Arc::clone(&intervals),
+                    )?;
+                }
+                (intervals, details)
+            }
+        };
+        if let Some(batches) = batches {
+            batches.finish()?;
+        }
         Ok(DustMasker {
-            sequence,
+            sequence: sequence.unbind(),
             window_size,
             score_threshold,
+            kmer_size,
+            masker,
             intervals,
+            spill_threshold,
+            full_rescan: custom_scan,
+            allow_short,
+            ambiguous_intervals: details.ambiguous_runs,
+            composition: details.composition,
+        })
+    }
+
+    /// The normalized regions of `other`, which must be within the sequence
+    fn other_intervals(
+        &self,
+        py: Python<'_>,
+        other: OtherIntervals<'_>,
+    ) -> PyResult<Vec<Interval>> {
+        let length = self.sequence.bind(py).to_str()?.len();
+        let intervals = match other {
+            OtherIntervals::Masker(masker) => {
+                let other_length = masker.sequence.bind(py).to_str()?.len();
+                if other_length != length {
+                    return Err(PyValueError::new_err(format!(
+                        "the sequences have different lengths: '{length}' and '{other_length}'"
+                    )));
+                }
+                masker.intervals.iter().collect()
+            }
+            OtherIntervals::Intervals(intervals) => intervals,
+        };
+        if let Some((start, end)) = intervals.iter().find(|&&(_, end)| end > length) {
+            return Err(PyValueError::new_err(format!(
+                "invalid region '{start}..{end}' for a sequence of length '{length}'"
+            )));
+        }
+        Ok(interval_set::normalize(intervals))
+    }
+
+    /// Apply the set operation `combine` to the regions of the sequence and
+    /// `other`, returning a DustMasker with the resulting regions
+    fn combine(
+        &self,
+        py: Python<'_>,
+        other: OtherIntervals<'_>,
+        combine: fn(&[Interval], &[Interval]) -> Vec<Interval>,
+    ) -> PyResult<DustMasker> {
+        let other = self.other_intervals(py, other)?;
+        let own = interval_set::normalize(self.intervals.iter());
+        let intervals: Intervals = combine(&own, &other).into_iter().collect();
+        Ok(DustMasker {
+            sequence: self.sequence.clone_ref(py),
+            window_size: self.window_size,
+            score_threshold: self.score_threshold,
+            kmer_size: self.kmer_size,
+            masker: Arc::clone(&self.masker),
+            intervals: Arc::new(intervals),
+            spill_threshold: self.spill_threshold,
+            full_rescan: true,
+            allow_short: self.allow_short,
+            ambiguous_intervals: self.ambiguous_intervals.clone(),
+            composition: self.composition,
+        })
+    }
+}
+
+/// Build an alphabet from the characters that stand for each symbol
+fn alphabet_from_symbols(symbols: &[String]) -> PyResult<Alphabet> {
+    if let Some(symbol) = symbols.iter().find(|symbol| !symbol.is_ascii()) {
+        return Err(PyValueError::new_err(format!(
+            "invalid alphabet symbol '{symbol}', characters must be ASCII"
+        )));
+    }
+    let symbols: Vec<&[u8]> = symbols.iter().map(|symbol| symbol.as_bytes()).collect();
+    Alphabet::new(&symbols).map_err(errors::input)
+}
+
+/// The threshold overrides given as the start and end positions of each region
+/// and its score threshold, which must be within a sequence of `length` bases
+fn threshold_overrides_within(
+    overrides: Vec<(usize, usize, usize)>,
+    length: usize,
+    custom_encoding: bool,
+) -> PyResult<Vec<ThresholdOverride>> {
+    if custom_encoding {
+        return Err(PyValueError::new_err(
+            "threshold overrides can't be combined with a custom alphabet, a bisulfite \
+             conversion or a k-mer size other than 3",
+        ));
+    }
+    let overrides: Vec<ThresholdOverride> = overrides
+        .into_iter()
+        .map(|(start, end, score_threshold)| ThresholdOverride {
+            start,
+            end,
+            score_threshold,
         })
+        .collect();
+    validate_threshold_overrides(&overrides).map_err(errors::input)?;
+    if let Some(region) = overrides.iter().find(|region| region.end > length) {
+        return Err(PyValueError::new_err(format!(
+            "invalid region '{}..{}' for a sequence of length '{length}'",
+            region.start, region.end
+        )));
+    }
+    Ok(overrides)
+}
+
+/// The alphabet of reads with a bisulfite conversion, given as the converted
+/// base followed by the base it is read as
+fn bisulfite_alphabet(conversion: &str) -> PyResult<Alphabet> {
+    match conversion {
+        "CT" => Ok(Alphabet::bisulfite_ct()),
+        "GA" => Ok(Alphabet::bisulfite_ga()),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid bisulfite conversion '{conversion}', must be 'CT' or 'GA'"
+        ))),
+    }
+}
+
+#[pymethods]
+impl DustMasker {
+    #[new]
+    #[pyo3(signature = (sequence, window_size=64, score_threshold=20, threads=1, spill_threshold=None, alphabet=None, kmer_size=3, level=None, composition=false, bisulfite=None, callback=None, batch_size=1000, cancel=None, progress=None, progress_interval=10_000_000, threshold_overrides=None, mask_ambiguous=false, allow_short=false))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        sequence: Bound<'_, PyString>,
+        window_size: usize,
+        score_threshold: usize,
+        threads: usize,
+        spill_threshold: Option<usize>,
+        alphabet: Option<Vec<String>>,
+        kmer_size: usize,
+        level: Option<f64>,
+        composition: bool,
+        bisulfite: Option<&str>,
+        callback: Option<Bound<'_, PyAny>>,
+        batch_size: usize,
+        cancel: Option<Py<CancelToken>>,
+        progress: Option<Py<PyAny>>,
+        progress_interval: usize,
+        threshold_overrides: Option<Vec<(usize, usize, usize)>>,
+        mask_ambiguous: bool,
+        allow_short: bool,
+    ) -> PyResult<DustMasker> {
+        let options = Options {
+            spill_threshold,
+            alphabet,
+            kmer_size,
+            level,
+            composition,
+            bisulfite,
+            callback,
+            batch_size,
+            cancel,
+            progress,
+            progress_interval,
+            threshold_overrides,
+            mask_ambiguous,
+            allow_short,
+        };
+        DustMasker::with_options(sequence, window_size, score_threshold, threads, options)
+    }
+    #[getter]
+    fn level(&self) -> f64 {
+        self.score_threshold as f64 / 10.0
+    }
+    #[getter]
+    fn effective_window_size(&self, py: Python<'_>) -> PyResult<usize> {
+        let length = self.sequence.bind(py).to_str()?.len();
+        Ok(self.window_size.min(length))
+    }
+    #[getter]
+    fn intervals(&self) -> Vec<(usize, usize)> {
+        self.intervals.iter().collect()
+    }
+    #[getter]
+    fn interval_array<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyMemoryView>> {
+        let buffer = Bound::new(py, Buffer::from_intervals(Arc::clone(&self.intervals)))?;
+        PyMemoryView::from_bound(buffer.as_any())
+    }
+    #[getter]
+    fn mask_array<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyMemoryView>> {
+        let mut mask = vec![0; self.sequence.bind(py).to_str()?.len()];
+        for (start, end) in self.intervals.iter() {
+            // Intervals starting past the end of the sequence are empty
+            if start < end {
+                mask[start..end].fill(1);
+            }
+        }
+        let buffer = Bound::new(py, Buffer::from_mask(mask))?;
+        PyMemoryView::from_bound(buffer.as_any())
+    }
+    #[getter]
+    fn minus_strand_intervals(&self, py: Python<'_>) -> PyResult<Vec<(usize, usize)>> {
+        let length = self.sequence.bind(py).to_str()?.len();
+        Ok(strand::reverse_intervals(self.intervals.iter(), length))
+    }
+    #[getter]
+    fn ambiguous_intervals(&self) -> Vec<(usize, usize)> {
+        self.ambiguous_intervals.clone()
+    }
+    #[getter]
+    fn composition<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(composition) = self.composition else {
+            return Ok(None);
+        };
+        let dict = PyDict::new_bound(py);
+        dict.set_item("length", composition.length)?;
+        dict.set_item("gc_count", composition.gc_count)?;
+        dict.set_item("at_count", composition.at_count)?;
+        dict.set_item("n_count", composition.n_count)?;
+        dict.set_item("n_runs", composition.n_runs)?;
+        dict.set_item("longest_n_run", composition.longest_n_run)?;
+        dict.set_item("gc_content", composition.gc_content())?;
+        Ok(Some(dict))
     }
     #[getter]
     fn n_masked_bases(&self) -> usize {
@@ -102,41 +978,2483 @@ impl DustMasker {
     ///     If True, low-complexity regions will be masked with 'N' characters.
     ///     By default, bases within low-complexity regions are converted to
     ///     lowercase (i.e., soft-masking).
+    /// uppercase : bool, default: False
+    ///     If True, the bases outside of low-complexity regions are converted to
+    ///     uppercase, so that only the masked bases are lowercase.
+    /// convert : {'UT', 'TU'}, optional
+    ///     Converts the uracils of RNA sequences to thymines ('UT'), or the
+    ///     thymines to uracils ('TU'), keeping their case.
+    /// ambiguous_to_n : bool, default: False
+    ///     If True, the IUPAC ambiguity codes (e.g., 'R' or 'Y') are replaced
+    ///     with 'N', keeping their case.
     ///
     /// Raises
     /// ------
+    /// ValueError
+    ///    If `convert` is not 'UT' or 'TU'.
     /// TypeError
     ///    If the input parameters are not of the expected type.
-    #[pyo3(signature = (hard=false))]
-    fn mask(&self, hard: bool) -> PyResult<String> {
-        let mut masked_sequence = self.sequence.clone();
-        for &(start, end) in &self.intervals {
-            if hard {
-                let len = end - start;
-                masked_sequence.replace_range(start..end, &"N".repeat(len));
-            } else {
-                let lowercased = self.sequence[start..end].to_lowercase();
-                masked_sequence.replace_range(start..end, &lowercased);
-            }
+    #[pyo3(signature = (hard=false, *, uppercase=false, convert=None, ambiguous_to_n=false))]
+    fn mask(
+        &self,
+        py: Python<'_>,
+        hard: bool,
+        uppercase: bool,
+        convert: Option<&str>,
+        ambiguous_to_n: bool,
+    ) -> PyResult<String> {
+        let normalization = OutputNormalization::new(uppercase, convert, ambiguous_to_n)?;
+        let sequence = self.sequence.bind(py).to_str()?;
+        Ok(mask_range(
+            sequence,
+            &self.intervals,
+            hard,
+            normalization,
+            0..sequence.len(),
+        ))
+    }
+    /// Returns a view of the masked sequence, which masks its characters only
+    /// when they are read, without building the whole masked sequence.
+    ///
+    /// Parameters
+    /// ----------
+    /// hard : bool, default: False
+    ///     If True, low-complexity regions are masked with 'N' characters
+    ///     instead of being converted to lowercase, like in `mask()`.
+    /// uppercase, convert, ambiguous_to_n
+    ///     Normalize the characters of the sequence, like in `mask()`.
+    ///
+    /// Returns
+    /// -------
+    /// MaskedSequence
+    ///     An object that behaves like the string returned by `mask()`: it can
+    ///     be sliced, iterated over, and written to a file with its `write()`
+    ///     method, and `str()` builds the whole masked sequence. Its length
+    ///     and indices are byte offsets, like the positions of the regions.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If `convert` is not 'UT' or 'TU'.
+    #[pyo3(signature = (hard=false, *, uppercase=false, convert=None, ambiguous_to_n=false))]
+    fn masked_view(
+        &self,
+        py: Python<'_>,
+        hard: bool,
+        uppercase: bool,
+        convert: Option<&str>,
+        ambiguous_to_n: bool,
+    ) -> PyResult<MaskedSequence> {
+        Ok(MaskedSequence {
+            sequence: self.sequence.clone_ref(py),
+            intervals: Arc::clone(&self.intervals),
+            hard,
+            normalization: OutputNormalization::new(uppercase, convert, ambiguous_to_n)?,
+        })
+    }
+    /// Plots the fraction of masked bases along the sequence.
+    ///
+    /// Requires matplotlib, which can be installed with the `plot` extra
+    /// (`pip install pydustmasker[plot]`). See `pydustmasker.plotting`.
+    ///
+    /// Parameters
+    /// ----------
+    /// bin_size : int, default: 10000
+    ///     The number of bases over which the masked fraction is computed.
+    ///
+    /// Returns
+    /// -------
+    /// matplotlib.figure.Figure
+    ///     A figure with the masked fraction of each bin, plotted at the
+    ///     position where the bin starts.
+    ///
+    /// Raises
+    /// ------
+    /// ImportError
+    ///    If matplotlib is not installed.
+    /// ValueError
+    ///    If the bin size is not positive.
+    #[pyo3(signature = (bin_size=10_000))]
+    fn plot_density<'py>(slf: &Bound<'py, Self>, bin_size: i64) -> PyResult<Bound<'py, PyAny>> {
+        slf.py()
+            .import_bound("pydustmasker.plotting")?
+            .call_method1("plot_density", (slf, bin_size))
+    }
+    /// Returns a DustMasker whose regions are the bases within the
+    /// low-complexity regions or within the regions of `other`.
+    ///
+    /// Parameters
+    /// ----------
+    /// other : DustMasker or list of tuples
+    ///     A DustMasker of a sequence of the same length, or the start and end
+    ///     positions of regions of the sequence (e.g., tandem repeats), in any
+    ///     order.
+    ///
+    /// Returns
+    /// -------
+    /// DustMasker
+    ///     A DustMasker of the same sequence with the combined regions, sorted
+    ///     and without overlaps. Its `edit()` method scans the whole edited
+    ///     sequence again, returning only its low-complexity regions.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If the sequences have different lengths or a region ends past the end
+    ///    of the sequence.
+    /// TypeError
+    ///    If the input parameters are not of the expected type.
+    fn union(&self, py: Python<'_>, other: OtherIntervals<'_>) -> PyResult<DustMasker> {
+        self.combine(py, other, interval_set::union)
+    }
+    /// Returns a DustMasker whose regions are the bases within both the
+    /// low-complexity regions and the regions of `other`.
+    ///
+    /// See `union()` for the parameters.
+    fn intersection(&self, py: Python<'_>, other: OtherIntervals<'_>) -> PyResult<DustMasker> {
+        self.combine(py, other, interval_set::intersection)
+    }
+    /// Returns a DustMasker whose regions are the bases within the
+    /// low-complexity regions but not within the regions of `other`.
+    ///
+    /// See `union()` for the parameters.
+    fn difference(&self, py: Python<'_>, other: OtherIntervals<'_>) -> PyResult<DustMasker> {
+        self.combine(py, other, interval_set::difference)
+    }
+    /// Returns a DustMasker of a subsequence, given as a slice (e.g.,
+    /// `masker[1000:5000]`), whose regions are the parts of the low-complexity
+    /// regions within it, with positions relative to its start.
+    ///
+    /// The regions are not found again, so regions clipped at the ends of the
+    /// subsequence are kept even if they are shorter than the ones the
+    /// algorithm would find in the subsequence alone. Its `edit()` method
+    /// scans the whole edited sequence again.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If the slice has a step other than 1.
+    /// InvalidCharacterError
+    ///    If the slice splits a non-ASCII character.
+    /// TypeError
+    ///    If the index is not a slice.
+    fn __getitem__(&self, py: Python<'_>, index: &Bound<'_, PySlice>) -> PyResult<DustMasker> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        let indices = index.indices(sequence.len() as isize)?;
+        if indices.step != 1 {
+            return Err(PyValueError::new_err(format!(
+                "invalid slice step '{}', must be 1",
+                indices.step
+            )));
+        }
+        let start = indices.start as usize;
+        let end = std::cmp::max(indices.stop as usize, start);
+        let intervals: Intervals = interval_set::slice(self.intervals.iter(), start, end)
+            .into_iter()
+            .collect();
+        if let Some(&position) = [start, end]
+            .iter()
+            .find(|&&position| !sequence.is_char_boundary(position))
+        {
+            return Err(errors::split_character(sequence, position));
         }
-        Ok(masked_sequence)
+        let subsequence = &sequence[start..end];
+        Ok(DustMasker {
+            sequence: PyString::new_bound(py, subsequence).unbind(),
+            window_size: self.window_size,
+            score_threshold: self.score_threshold,
+            kmer_size: self.kmer_size,
+            masker: Arc::clone(&self.masker),
+            intervals: Arc::new(intervals),
+            spill_threshold: self.spill_threshold,
+            full_rescan: true,
+            allow_short: self.allow_short,
+            ambiguous_intervals: interval_set::slice(
+                self.ambiguous_intervals.iter().copied(),
+                start,
+                end,
+            ),
+            composition: self
+                .composition
+                .map(|_| Composition::of(subsequence.as_bytes())),
+        })
     }
-    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
-        let sequence_preview = if slf.borrow().sequence.len() > 8 {
-            format!("{}…", &slf.borrow().sequence[..8])
-        } else {
-            slf.borrow().sequence.clone()
-        };
-        Ok(format!(
-            "DustMasker(sequence: '{}', intervals: {:?})",
-            sequence_preview,
-            slf.borrow().intervals
-        ))
+    /// Compares the low-complexity regions with the regions of `other`, such as
+    /// the ones found with different parameters.
+    ///
+    /// Parameters
+    /// ----------
+    /// other : DustMasker or list of tuples
+    ///     A DustMasker of a sequence of the same length, or the start and end
+    ///     positions of regions of the sequence, in any order.
+    ///
+    /// Returns
+    /// -------
+    /// dict
+    ///     The regions of the bases within the low-complexity regions only
+    ///     (`only_self`), within the regions of `other` only (`only_other`) and
+    ///     within both (`shared`), their numbers of bases (`n_only_self`,
+    ///     `n_only_other` and `n_shared`) and the Jaccard index of the masked
+    ///     bases (`jaccard`), which is 1 if neither masks any base.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If the sequences have different lengths or a region ends past the end
+    ///    of the sequence.
+    /// TypeError
+    ///    If the input parameters are not of the expected type.
+    fn compare<'py>(
+        &self,
+        py: Python<'py>,
+        other: OtherIntervals<'_>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let other = self.other_intervals(py, other)?;
+        let own = interval_set::normalize(self.intervals.iter());
+        let comparison = interval_set::compare(&own, &other);
+        let dict = PyDict::new_bound(py);
+        dict.set_item("n_only_self", comparison.n_only_a())?;
+        dict.set_item("n_only_other", comparison.n_only_b())?;
+        dict.set_item("n_shared", comparison.n_shared())?;
+        dict.set_item("jaccard", comparison.jaccard())?;
+        dict.set_item("only_self", comparison.only_a)?;
+        dict.set_item("only_other", comparison.only_b)?;
+        dict.set_item("shared", comparison.shared)?;
+        Ok(dict)
+    }
+    /// Evaluates the low-complexity regions against true regions, such as
+    /// curated repeat annotations.
+    ///
+    /// Parameters
+    /// ----------
+    /// truth : DustMasker or list of tuples
+    ///     A DustMasker of a sequence of the same length, or the start and end
+    ///     positions of the true regions of the sequence, in any order. The
+    ///     positions are 0-based and the ends are exclusive, like in BED files.
+    ///
+    /// Returns
+    /// -------
+    /// dict
+    ///     The `precision`, `recall` and `f1` score of the masked bases
+    ///     (`base`) and of the regions (`interval`). At the base level, the
+    ///     precision is the fraction of masked bases that are within true
+    ///     regions, and the recall is the fraction of bases of true regions
+    ///     that are masked. At the interval level, the precision is the
+    ///     fraction of low-complexity regions that overlap a true region, and
+    ///     the recall is the fraction of true regions that overlap a
+    ///     low-complexity region. Scores with a zero denominator are 0.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If the sequences have different lengths or a region ends past the end
+    ///    of the sequence.
+    /// TypeError
+    ///    If the input parameters are not of the expected type.
+    fn evaluate<'py>(
+        &self,
+        py: Python<'py>,
+        truth: OtherIntervals<'_>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let truth = self.other_intervals(py, truth)?;
+        let own = interval_set::normalize(self.intervals.iter());
+        let evaluation = interval_set::evaluate(&own, &truth);
+        let dict = PyDict::new_bound(py);
+        for (level, scores) in [("base", evaluation.base), ("interval", evaluation.interval)] {
+            let scores_dict = PyDict::new_bound(py);
+            scores_dict.set_item("precision", scores.precision)?;
+            scores_dict.set_item("recall", scores.recall)?;
+            scores_dict.set_item("f1", scores.f1)?;
+            dict.set_item(level, scores_dict)?;
+        }
+        Ok(dict)
+    }
+    /// Returns the low-complexity regions as `Region` objects, which describe
+    /// them further than the tuples of `intervals`.
+    ///
+    /// Returns
+    /// -------
+    /// list of Region
+    ///     The low-complexity regions, in the same order as `intervals`, with
+    ///     their `start`, `end` and `length`, the DUST `score` of their bases
+    ///     and the `repeat_unit` that they repeat, if any.
+    ///
+    /// The regions are passed to `post_process_regions`, which subclasses can
+    /// override to filter or annotate them.
+    fn regions<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        let py = slf.py();
+        let masker = slf.borrow();
+        let sequence = masker.sequence.bind(py).to_str()?.as_bytes();
+        let regions: Vec<Region> = masker
+            .intervals
+            .iter()
+            .map(|(start, end)| {
+                // Intervals starting past the end of the sequence are empty
+                let bases = sequence.get(start..end).unwrap_or_default();
+                Region {
+                    start,
+                    end,
+                    score: metrics::dust_score(bases),
+                    repeat_unit: metrics::repeat_unit(bases)
+                        .map(|unit| String::from_utf8_lossy(unit).into_owned()),
+                }
+            })
+            .collect();
+        drop(masker);
+        slf.call_method1("post_process_regions", (regions,))
+    }
+    /// Hook called by `regions()` with the regions it returns, which returns
+    /// them unchanged. Subclasses can override it to filter the regions or to
+    /// annotate them (e.g., returning objects of their own class).
+    ///
+    /// Parameters
+    /// ----------
+    /// regions : list of Region
+    ///     The low-complexity regions.
+    ///
+    /// Returns
+    /// -------
+    /// list
+    ///     The list returned by `regions()`.
+    fn post_process_regions<'py>(&self, regions: Bound<'py, PyAny>) -> Bound<'py, PyAny> {
+        regions
+    }
+    /// Hook called once a DustMasker of a subclass is created, with the
+    /// low-complexity regions that were found, which returns them unchanged.
+    /// Subclasses can override it to filter, extend or merge the regions, and
+    /// the regions it returns replace the ones of the DustMasker. It isn't
+    /// called for `DustMasker` objects.
+    ///
+    /// Parameters
+    /// ----------
+    /// intervals : list of tuples
+    ///     The start and end positions of the low-complexity regions.
+    ///
+    /// Returns
+    /// -------
+    /// list of tuples
+    ///     The start and end positions of the regions to keep, which must be
+    ///     within the sequence. They are sorted and overlapping regions are
+    ///     merged.
+    fn post_process_intervals(&self, intervals: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        intervals
+    }
+    /// Applies `post_process_intervals`. Only subclasses call it, as the
+    /// DustMasker is fully created by `__new__`, so subclasses that override
+    /// `__init__` must call `super().__init__()`.
+    #[pyo3(signature = (*_args, **_kwargs))]
+    fn __init__(
+        slf: &Bound<'_, Self>,
+        _args: &Bound<'_, PyAny>,
+        _kwargs: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let py = slf.py();
+        let intervals: Vec<Interval> = slf.borrow().intervals.iter().collect();
+        let processed: Vec<Interval> = slf
+            .call_method1("post_process_intervals", (intervals.clone(),))?
+            .extract()?;
+        if processed == intervals {
+            return Ok(());
+        }
+        let processed = slf
+            .borrow()
+            .other_intervals(py, OtherIntervals::Intervals(processed))?;
+        let mut masker = slf.borrow_mut();
+        masker.intervals = Arc::new(processed.into_iter().collect());
+        // The regions can't be patched after an edit, as they don't come from
+        // the algorithm alone
+        masker.full_rescan = true;
+        Ok(())
+    }
+    /// Returns an iterator over the k-mers of the sequence that don't overlap
+    /// any low-complexity region.
+    ///
+    /// Only k-mers made of 'A', 'C', 'G' and 'T' bases, in either case, are
+    /// returned, in order of position.
+    ///
+    /// Parameters
+    /// ----------
+    /// k : int
+    ///     The length of the k-mers, between 1 and 32.
+    /// as_int : bool, default: False
+    ///     If True, the k-mers are returned as integers encoding each base with 2
+    ///     bits (A=0, C=1, G=2 and T=3), the first base being the most
+    ///     significant. By default, they are returned as strings.
+    ///
+    /// Returns
+    /// -------
+    /// KmerIterator
+    ///     An iterator over the k-mers.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If `k` is not between 1 and 32.
+    /// TypeError
+    ///    If the input parameters are not of the expected type.
+    #[pyo3(signature = (k, as_int=false))]
+    fn kmers(&self, py: Python<'_>, k: usize, as_int: bool) -> PyResult<KmerIterator> {
+        if !(1..=kmers::MAX_KMER_SIZE).contains(&k) {
+            return Err(PyValueError::new_err(format!(
+                "invalid k-mer size '{k}', must be between '1' and '{}'",
+                kmers::MAX_KMER_SIZE
+            )));
+        }
+        Ok(KmerIterator {
+            sequence: self.sequence.clone_ref(py),
+            intervals: Arc::clone(&self.intervals),
+            cursor: KmerCursor::new(k),
+            kmer_size: k,
+            as_int,
+        })
+    }
+    /// Returns a new DustMasker for the sequence obtained by replacing the bases
+    /// between `start` and `end` with `replacement`.
+    ///
+    /// Only the region surrounding the edit is scanned again, while the
+    /// intervals found elsewhere are reused, which makes it much faster than
+    /// creating a new DustMasker for long sequences. The resulting intervals are
+    /// identical to the ones of a DustMasker created from the edited sequence
+    /// with the same parameters. With a custom alphabet or k-mer size, or if
+    /// the regions were combined with other regions, the whole edited
+    /// sequence is scanned again.
+    ///
+    /// Parameters
+    /// ----------
+    /// start : int
+    ///     The position of the first base to be replaced.
+    /// end : int
+    ///     The position after the last base to be replaced. If equal to `start`,
+    ///     `replacement` is inserted at that position.
+    /// replacement : str
+    ///     The sequence inserted in place of the replaced bases. If empty, the
+    ///     bases are deleted.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If `start` and `end` don't delimit a region of the sequence.
+    /// InvalidCharacterError
+    ///    If `start` or `end` falls within a non-ASCII character.
+    /// SequenceTooShortError
+    ///    If the edited sequence is too short (less than 4 characters) and
+    ///    `allow_short` was False.
+    /// TypeError
+    ///    If the input parameters are not of the expected type.
+    /// OSError
+    ///    If the temporary file used to spill the regions can't be written.
+    fn edit(
+        &self,
+        py: Python<'_>,
+        start: usize,
+        end: usize,
+        replacement: &str,
+    ) -> PyResult<DustMasker> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        if start > end || end > sequence.len() {
+            return Err(PyValueError::new_err(format!(
+                "invalid region '{start}..{end}' for a sequence of length '{}'",
+                sequence.len()
+            )));
+        }
+        if let Some(&position) = [start, end]
+            .iter()
+            .find(|&&position| !sequence.is_char_boundary(position))
+        {
+            return Err(errors::split_character(sequence, position));
+        }
+        let edited_sequence = [&sequence[..start], replacement, &sequence[end..]].concat();
+        if !(self.allow_short && edited_sequence.len() < MIN_SEQUENCE_LENGTH) {
+            validate_inputs(edited_sequence.as_bytes(), self.window_size).map_err(errors::input)?;
+            errors::warn_window_size(py, self.window_size, edited_sequence.len())?;
+        }
+        let (intervals, details) = if self.full_rescan {
+            scan(
+                self.masker.as_ref(),
+                &edited_sequence,
+                self.spill_threshold,
+                self.composition.is_some(),
+                None,
+                Interruption::new(None, None),
+            )?
+        } else {
+            let intervals = edit::patch(
+                edited_sequence.as_bytes(),
+                &self.intervals,
+                start..end,
+                replacement.len(),
+                self.window_size,
+                self.score_threshold,
+                self.spill_threshold,
+            )?;
+            let details = ScanDetails::of(edited_sequence.as_bytes(), self.composition.is_some());
+            (intervals, details)
+        };
+        Ok(DustMasker {
+            sequence: PyString::new_bound(py, &edited_sequence).unbind(),
+            window_size: self.window_size,
+            score_threshold: self.score_threshold,
+            kmer_size: self.kmer_size,
+            masker: Arc::clone(&self.masker),
+            intervals: Arc::new(intervals),
+            spill_threshold: self.spill_threshold,
+            full_rescan: self.full_rescan,
+            allow_short: self.allow_short,
+            ambiguous_intervals: details.ambiguous_runs,
+            composition: details.composition,
+        })
+    }
+    /// Returns the number of bytes of memory held by the DustMasker.
+    ///
+    /// Returns
+    /// -------
+    /// dict
+    ///     The bytes of the Python string of the `sequence`, which is the one
+    ///     given as input rather than a copy, so it may be shared with other
+    ///     objects, the bytes allocated to store the low-complexity regions
+    ///     (`intervals`), including the room reserved for more regions, the
+    ///     bytes of the file the regions were spilled to (`mapped_intervals`),
+    ///     which the operating system pages in and out of memory, the bytes of
+    ///     the object itself, of the state of the algorithm and of the runs of
+    ///     ambiguous characters (`buffers`), and the sum of all of them
+    ///     (`total`). The regions are shared with the
+    ///     `interval_array` views and the cache, so they are held as long as
+    ///     any of them exists.
+    fn memory_usage<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let sequence = self
+            .sequence
+            .bind(py)
+            .call_method0("__sizeof__")?
+            .extract()?;
+        let buffers = size_of::<Self>()
+            + size_of_val(self.masker.as_ref())
+            + self.ambiguous_intervals.capacity() * size_of::<Interval>();
+        memory_usage_dict(
+            py,
+            sequence,
+            self.intervals.allocated_bytes(),
+            self.intervals.mapped_bytes(),
+            buffers,
+        )
+    }
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        let sequence_preview = if sequence.len() > 8 {
+            format!("{}…", &sequence[..8])
+        } else {
+            sequence.to_string()
+        };
+        Ok(format!(
+            "DustMasker(sequence: '{}', intervals: {:?})",
+            sequence_preview,
+            self.intervals.iter().collect::<Vec<_>>()
+        ))
+    }
+    /// Returns an HTML summary of the results, displayed by Jupyter, with the
+    /// first bases of the sequence and their low-complexity regions
+    /// highlighted.
+    fn _repr_html_(&self, py: Python<'_>) -> PyResult<String> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        let n_masked_bases = self.n_masked_bases();
+        let masked_percentage = match sequence.len() {
+            0 => 0.0,
+            length => 100.0 * n_masked_bases as f64 / length as f64,
+        };
+        let mut html = String::from("<div><strong>DustMasker</strong><table>");
+        for (name, value) in [
+            ("Sequence length", sequence.len().to_string()),
+            ("Window size", self.window_size.to_string()),
+            ("Score threshold", self.score_threshold.to_string()),
+            ("Low-complexity regions", self.intervals.len().to_string()),
+            (
+                "Masked bases",
+                format!("{n_masked_bases} ({masked_percentage:.2}%)"),
+            ),
+        ] {
+            html.push_str(&format!("<tr><th>{name}</th><td>{value}</td></tr>"));
+        }
+        html.push_str("</table>");
+        let preview_length = (0..=HTML_PREVIEW_LENGTH.min(sequence.len()))
+            .rev()
+            .find(|&i| sequence.is_char_boundary(i))
+            .unwrap_or(0);
+        if preview_length < sequence.len() {
+            html.push_str(&format!(
+                "<p>First {preview_length} of {} bases:</p>",
+                sequence.len()
+            ));
+        }
+        html.push_str("<pre style=\"white-space: pre-wrap; word-break: break-all\">");
+        let mut last_end = 0;
+        for (start, end) in self.intervals.iter() {
+            if start >= preview_length {
+                break;
+            }
+            let end = end.min(preview_length);
+            push_escaped(&mut html, &sequence[last_end..start]);
+            html.push_str("<mark>");
+            push_escaped(&mut html, &sequence[start..end]);
+            html.push_str("</mark>");
+            last_end = end;
+        }
+        push_escaped(&mut html, &sequence[last_end..preview_length]);
+        html.push_str("</pre></div>");
+        Ok(html)
+    }
+}
+
+/// Number of bytes of the sequence shown by `DustMasker._repr_html_`
+const HTML_PREVIEW_LENGTH: usize = 1000;
+
+/// Append `text` to `html`, escaping the characters with a special meaning
+fn push_escaped(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(c),
+        }
+    }
+}
+
+/// Iterator over the k-mers of a sequence that don't overlap its
+/// low-complexity regions, returned by `DustMasker.kmers()`.
+#[pyclass]
+struct KmerIterator {
+    sequence: Py<PyString>,
+    intervals: Arc<Intervals>,
+    cursor: KmerCursor,
+    kmer_size: usize,
+    as_int: bool,
+}
+
+#[pymethods]
+impl KmerIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        let intervals = &self.intervals;
+        let Some((start, code)) = self
+            .cursor
+            .advance(sequence.as_bytes(), |index| intervals.get(index))
+        else {
+            return Ok(None);
+        };
+        if self.as_int {
+            return Ok(Some(code.into_py(py)));
+        }
+        // K-mers only contain A/C/G/T bases, which are single-byte characters
+        Ok(Some(sequence[start..start + self.kmer_size].into_py(py)))
+    }
+}
+
+/// Number of bases masked at once by the iterators of `MaskedSequence`
+const MASKED_CHUNK_SIZE: usize = 1 << 16;
+
+/// A view of a masked sequence, returned by `DustMasker.masked_view()`, which
+/// masks its characters only when they are read.
+///
+/// It can be used like the string returned by `DustMasker.mask()`: indexing
+/// and slicing it return masked strings, and iterating over it yields the
+/// masked characters one at a time. `str()` builds the whole masked sequence.
+/// Like the positions of the regions, its length and indices are counted in
+/// bytes of the UTF-8 encoded sequence, so they only match the ones of the
+/// string for ASCII sequences.
+#[pyclass(frozen)]
+struct MaskedSequence {
+    sequence: Py<PyString>,
+    intervals: Arc<Intervals>,
+    hard: bool,
+    normalization: OutputNormalization,
+}
+
+/// An index of a `MaskedSequence`: a position or a slice
+#[derive(FromPyObject)]
+enum MaskedIndex<'py> {
+    Position(isize),
+    Slice(Bound<'py, PySlice>),
+}
+
+impl MaskedSequence {
+    /// Masks the bases of `range`, which must start and end at character
+    /// boundaries
+    fn masked(&self, sequence: &str, range: Range<usize>) -> String {
+        mask_range(
+            sequence,
+            &self.intervals,
+            self.hard,
+            self.normalization,
+            range,
+        )
+    }
+    /// Raises an InvalidCharacterError if a character of the single-byte range
+    /// starting at `position` has more than one byte
+    fn check_position(sequence: &str, position: usize) -> PyResult<()> {
+        match [position, position + 1]
+            .into_iter()
+            .find(|&position| !sequence.is_char_boundary(position))
+        {
+            Some(position) => Err(errors::split_character(sequence, position)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[pymethods]
+impl MaskedSequence {
+    fn __len__(&self, py: Python<'_>) -> PyResult<usize> {
+        Ok(self.sequence.bind(py).to_str()?.len())
+    }
+    /// Returns the masked character at a position, or the masked characters
+    /// of a slice.
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///    If the position is outside of the sequence.
+    /// InvalidCharacterError
+    ///    If the position or the slice splits a non-ASCII character.
+    fn __getitem__(&self, py: Python<'_>, index: MaskedIndex<'_>) -> PyResult<String> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        let length = sequence.len() as isize;
+        let indices = match index {
+            MaskedIndex::Position(position) => {
+                let position = if position < 0 {
+                    position + length
+                } else {
+                    position
+                };
+                if !(0..length).contains(&position) {
+                    return Err(PyIndexError::new_err("sequence index out of range"));
+                }
+                let position = position as usize;
+                Self::check_position(sequence, position)?;
+                return Ok(self.masked(sequence, position..position + 1));
+            }
+            MaskedIndex::Slice(slice) => slice.indices(length)?,
+        };
+        if indices.slicelength == 0 {
+            return Ok(String::new());
+        }
+        let (start, step) = (indices.start, indices.step);
+        if step == 1 {
+            let (start, end) = (start as usize, indices.stop as usize);
+            for position in [start, end] {
+                if !sequence.is_char_boundary(position) {
+                    return Err(errors::split_character(sequence, position));
+                }
+            }
+            return Ok(self.masked(sequence, start..end));
+        }
+        // Only the range that covers the selected positions is masked
+        let last = start + step * (indices.slicelength as isize - 1);
+        let (first, last) = (start.min(last) as usize, start.max(last) as usize);
+        let positions = (0..indices.slicelength as isize).map(|i| (start + step * i) as usize);
+        for position in positions.clone() {
+            Self::check_position(sequence, position)?;
+        }
+        let masked = self.masked(sequence, first..last + 1);
+        let masked = masked.as_bytes();
+        Ok(positions
+            .map(|position| char::from(masked[position - first]))
+            .collect())
+    }
+    fn __iter__(slf: &Bound<'_, Self>) -> MaskedSequenceIterator {
+        MaskedSequenceIterator::new(slf.clone().unbind(), MASKED_CHUNK_SIZE, true)
+    }
+    fn __str__(&self, py: Python<'_>) -> PyResult<String> {
+        let sequence = self.sequence.bind(py).to_str()?;
+        Ok(self.masked(sequence, 0..sequence.len()))
+    }
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "MaskedSequence(length: {}, hard: {})",
+            self.__len__(py)?,
+            if self.hard { "True" } else { "False" }
+        ))
+    }
+    /// Iterates over the masked sequence in chunks.
+    ///
+    /// Parameters
+    /// ----------
+    /// size : int, default: 65536
+    ///     The number of bases of each chunk. Chunks of sequences with
+    ///     non-ASCII characters are extended to the end of the character they
+    ///     split.
+    ///
+    /// Returns
+    /// -------
+    /// iterator of str
+    ///     The masked chunks, each one masked when it is requested.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If `size` is 0.
+    #[pyo3(signature = (size=MASKED_CHUNK_SIZE))]
+    fn chunks(slf: &Bound<'_, Self>, size: usize) -> PyResult<MaskedSequenceIterator> {
+        if size == 0 {
+            return Err(PyValueError::new_err("invalid chunk size '0'"));
+        }
+        Ok(MaskedSequenceIterator::new(
+            slf.clone().unbind(),
+            size,
+            false,
+        ))
+    }
+    /// Writes the masked sequence to a file, one chunk at a time.
+    ///
+    /// Parameters
+    /// ----------
+    /// file : file object
+    ///     A text file object, or any object with a `write()` method that
+    ///     accepts strings (e.g., `io.StringIO`).
+    /// chunk_size : int, default: 65536
+    ///     The number of bases masked and written at once.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     The number of characters written.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If `chunk_size` is 0.
+    #[pyo3(signature = (file, chunk_size=MASKED_CHUNK_SIZE))]
+    fn write(slf: &Bound<'_, Self>, file: &Bound<'_, PyAny>, chunk_size: usize) -> PyResult<usize> {
+        let py = slf.py();
+        let mut chunks = Self::chunks(slf, chunk_size)?;
+        let mut n_characters = 0;
+        while let Some(chunk) = chunks.next_chunk(py)? {
+            n_characters += chunk.chars().count();
+            file.call_method1("write", (chunk,))?;
+        }
+        Ok(n_characters)
+    }
+}
+
+/// Iterator over the characters or the chunks of a `MaskedSequence`, which
+/// masks one chunk at a time.
+#[pyclass]
+struct MaskedSequenceIterator {
+    view: Py<MaskedSequence>,
+    position: usize,
+    chunk_size: usize,
+    characters: bool,
+    chunk: String,
+    offset: usize,
+}
+
+impl MaskedSequenceIterator {
+    fn new(view: Py<MaskedSequence>, chunk_size: usize, characters: bool) -> Self {
+        MaskedSequenceIterator {
+            view,
+            position: 0,
+            chunk_size,
+            characters,
+            chunk: String::new(),
+            offset: 0,
+        }
+    }
+    /// Masks the next chunk of the sequence, if any
+    fn next_chunk(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        let view = self.view.get();
+        let sequence = view.sequence.bind(py).to_str()?;
+        if self.position >= sequence.len() {
+            return Ok(None);
+        }
+        let mut end = (self.position + self.chunk_size).min(sequence.len());
+        while !sequence.is_char_boundary(end) {
+            end += 1;
+        }
+        let chunk = view.masked(sequence, self.position..end);
+        self.position = end;
+        Ok(Some(chunk))
+    }
+}
+
+#[pymethods]
+impl MaskedSequenceIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        if !self.characters {
+            return self.next_chunk(py);
+        }
+        if self.offset >= self.chunk.len() {
+            match self.next_chunk(py)? {
+                Some(chunk) => (self.chunk, self.offset) = (chunk, 0),
+                None => return Ok(None),
+            }
+        }
+        let character = self.chunk[self.offset..].chars().next().unwrap_or_default();
+        self.offset += character.len_utf8();
+        Ok(Some(character.to_string()))
+    }
+}
+
+/// A low-complexity region, returned by `DustMasker.regions()`.
+///
+/// Attributes
+/// ----------
+/// start : int
+///     The position of the first base of the region.
+/// end : int
+///     The position after the last base of the region.
+/// length : int
+///     The number of bases of the region.
+/// score : float
+///     The DUST score of the bases of the region: the number of pairs of
+///     identical triplets divided by the number of triplets minus one, on the
+///     scale of `DustMasker.level`.
+/// repeat_unit : str or None
+///     The unit of 1 to 6 bases whose tandem repetition best matches the
+///     region, allowing for a quarter of mismatched bases, or None if no unit
+///     matches it (e.g., for regions enriched in a few bases without a
+///     period).
+///
+/// A region can be unpacked into its start and end positions, like the tuples
+/// of `DustMasker.intervals`.
+#[pyclass(frozen, eq)]
+#[derive(Clone, PartialEq)]
+struct Region {
+    #[pyo3(get)]
+    start: usize,
+    #[pyo3(get)]
+    end: usize,
+    #[pyo3(get)]
+    score: f64,
+    #[pyo3(get)]
+    repeat_unit: Option<String>,
+}
+
+#[pymethods]
+impl Region {
+    #[getter]
+    fn length(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        let positions: PyObject = (self.start, self.end).into_py(py);
+        positions.into_bound(py).iter()
+    }
+    fn __repr__(&self) -> String {
+        let repeat_unit = match &self.repeat_unit {
+            Some(unit) => format!("'{unit}'"),
+            None => "None".to_string(),
+        };
+        format!(
+            "Region(start: {}, end: {}, score: {:.2}, repeat_unit: {})",
+            self.start, self.end, self.score, repeat_unit
+        )
+    }
+}
+
+/// A flag that cancels the scans of the DustMaskers it is passed to, which
+/// can be set from another thread, for example when the client of a web
+/// service disconnects.
+///
+/// Cancelled scans stop within a few milliseconds and raise a
+/// `ScanCancelledError`. A token can be shared by several scans, and can't be
+/// reset once it is cancelled.
+///
+/// Attributes
+/// ----------
+/// cancelled : bool
+///     Whether `cancel()` was called.
+#[pyclass(frozen)]
+struct CancelToken {
+    cancelled: AtomicBool,
+}
+
+impl CancelToken {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[pymethods]
+impl CancelToken {
+    #[new]
+    fn new() -> Self {
+        CancelToken {
+            cancelled: AtomicBool::new(false),
+        }
+    }
+    /// Cancels the scans that use the token, including the ones that are
+    /// already running.
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.is_cancelled()
+    }
+    fn __repr__(&self) -> String {
+        let cancelled = if self.is_cancelled() { "True" } else { "False" };
+        format!("CancelToken(cancelled: {cancelled})")
+    }
+}
+
+/// Accumulate the masking of many sequences, such as the records of a FASTA
+/// file, and summarize it.
+///
+/// Only the masked fraction of each record and the most masked records are
+/// kept, so the DustMasker of each record can be discarded once it is added.
+///
+/// Parameters
+/// ----------
+/// top_n : int, default: 10
+///     The number of most masked records that are kept.
+///
+/// Attributes
+/// ----------
+/// n_records : int
+///     The number of records added so far.
+/// n_bases : int
+///     The total length of the records.
+/// n_masked_bases : int
+///     The total number of masked bases.
+/// n_intervals : int
+///     The total number of low-complexity regions.
+/// masked_fraction : float
+///     The fraction of the bases of all the records that are masked, or 0 if
+///     there are none.
+/// masked_fractions : list of float
+///     The masked fraction of each record, in the order they were added, or 0
+///     for empty records.
+/// most_masked : list of tuples
+///     The name, length, number of masked bases and masked fraction of the
+///     `top_n` records with the highest masked fractions, sorted by decreasing
+///     masked fraction and then number of masked bases.
+#[pyclass]
+struct MaskingSummary {
+    summary: Summary,
+}
+
+#[pymethods]
+impl MaskingSummary {
+    #[new]
+    #[pyo3(signature = (top_n=10))]
+    fn new(top_n: usize) -> Self {
+        MaskingSummary {
+            summary: Summary::new(top_n),
+        }
+    }
+    /// Adds the masking of a record.
+    ///
+    /// Parameters
+    /// ----------
+    /// name : str
+    ///     The name of the record, reported in `most_masked`.
+    /// masker : DustMasker
+    ///     The DustMasker of the sequence of the record.
+    fn add(&mut self, py: Python<'_>, name: String, masker: PyRef<'_, DustMasker>) -> PyResult<()> {
+        self.summary.add(RecordSummary {
+            name,
+            length: masker.sequence.bind(py).to_str()?.len(),
+            n_masked_bases: masker.n_masked_bases(),
+            n_intervals: masker.intervals.len(),
+        });
+        Ok(())
+    }
+    #[getter]
+    fn n_records(&self) -> usize {
+        self.summary.n_records()
+    }
+    #[getter]
+    fn n_bases(&self) -> usize {
+        self.summary.n_bases()
+    }
+    #[getter]
+    fn n_masked_bases(&self) -> usize {
+        self.summary.n_masked_bases()
+    }
+    #[getter]
+    fn n_intervals(&self) -> usize {
+        self.summary.n_intervals()
+    }
+    #[getter]
+    fn masked_fraction(&self) -> f64 {
+        self.summary.masked_fraction()
+    }
+    #[getter]
+    fn masked_fractions(&self) -> Vec<f64> {
+        self.summary.masked_fractions().to_vec()
+    }
+    #[getter]
+    fn most_masked(&self) -> Vec<(String, usize, usize, f64)> {
+        self.summary
+            .most_masked()
+            .into_iter()
+            .map(|record| {
+                let masked_fraction = record.masked_fraction();
+                (
+                    record.name,
+                    record.length,
+                    record.n_masked_bases,
+                    masked_fraction,
+                )
+            })
+            .collect()
+    }
+    /// Returns quantiles of the masked fractions of the records.
+    ///
+    /// Parameters
+    /// ----------
+    /// q : list of float
+    ///     The quantiles to compute, between 0 and 1 (e.g., `[0.5, 0.9]` for
+    ///     the median and the 90th percentile).
+    ///
+    /// Returns
+    /// -------
+    /// list of float
+    ///     The quantiles, interpolated linearly between the closest records like
+    ///     the default method of `numpy.quantile`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If a quantile is not between 0 and 1 or no record was added.
+    fn quantiles(&self, q: Vec<f64>) -> PyResult<Vec<f64>> {
+        q.into_iter()
+            .map(|q| {
+                if !(0.0..=1.0).contains(&q) {
+                    return Err(PyValueError::new_err(format!(
+                        "invalid quantile '{q}', must be between '0' and '1'"
+                    )));
+                }
+                self.summary
+                    .quantile(q)
+                    .ok_or_else(|| PyValueError::new_err("no record was added"))
+            })
+            .collect()
+    }
+    fn __repr__(&self) -> String {
+        format!(
+            "MaskingSummary(n_records: {}, n_bases: {}, masked_fraction: {:.4})",
+            self.summary.n_records(),
+            self.summary.n_bases(),
+            self.summary.masked_fraction()
+        )
+    }
+}
+
+/// Identify low-complexity regions in a nucleotide sequence that is received in
+/// chunks, without holding the whole sequence in memory.
+///
+/// Chunks are passed to `feed()` in order and `finish()` is called once the
+/// sequence is complete. The intervals are identical to the ones obtained by
+/// creating a DustMasker from the concatenated chunks.
+///
+/// Parameters
+/// ----------
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows, which is 10 times the mean score per
+///     triplet above which a region is masked. The minimum allowed value is 0.
+/// level : float, optional
+///     The mean score per triplet above which a region is masked, which
+///     replaces `score_threshold` if given (see `DustMasker`).
+///
+/// Attributes
+/// ----------
+/// window_size : int
+///     The length of the window used by symmetric DUST algorithm.
+/// score_threshold : int
+///     Score threshold for subwindows.
+/// level : float
+///     The mean score per triplet equivalent to the score threshold.
+/// n_bases : int
+///     The number of bases fed so far.
+///
+/// Raises
+/// ------
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If the level is not a non-negative multiple of 0.1.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size or score threshold.
+#[pyclass]
+struct StreamingDustMasker {
+    #[pyo3(get)]
+    window_size: usize,
+    #[pyo3(get)]
+    score_threshold: usize,
+    #[pyo3(get)]
+    n_bases: usize,
+    /// `None` once the stream is finished
+    stream: Option<StreamingDust>,
+}
+
+impl StreamingDustMasker {
+    fn stream(&mut self) -> PyResult<&mut StreamingDust> {
+        self.stream
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("the stream is already finished"))
+    }
+}
+
+#[pymethods]
+impl StreamingDustMasker {
+    #[new]
+    #[pyo3(signature = (window_size=64, score_threshold=20, level=None))]
+    fn new(
+        window_size: usize,
+        score_threshold: usize,
+        level: Option<f64>,
+    ) -> PyResult<StreamingDustMasker> {
+        let mut builder = DustConfig::builder()
+            .window_size(window_size)
+            .score_threshold(score_threshold);
+        if let Some(level) = level {
+            builder = builder.level(level);
+        }
+        let score_threshold = builder.build().map_err(errors::input)?.score_threshold();
+        Ok(StreamingDustMasker {
+            window_size,
+            score_threshold,
+            n_bases: 0,
+            stream: Some(StreamingDust::new(window_size, score_threshold)),
+        })
+    }
+    #[getter]
+    fn level(&self) -> f64 {
+        self.score_threshold as f64 / 10.0
+    }
+    /// Scans the next chunk of the sequence and returns the low-complexity
+    /// regions that were completed so far.
+    ///
+    /// Parameters
+    /// ----------
+    /// chunk : str
+    ///     The next part of the nucleotide sequence.
+    ///
+    /// Returns
+    /// -------
+    /// list of tuples
+    ///     The start and end positions, relative to the whole sequence, of the
+    ///     low-complexity regions that can no longer change.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If the stream is already finished.
+    /// TypeError
+    ///    If the input parameters are not of the expected type.
+    fn feed(&mut self, chunk: &str) -> PyResult<Vec<(usize, usize)>> {
+        let mut intervals = Vec::new();
+        let stream = self.stream()?;
+        stream.feed(chunk.as_bytes(), |start, end| intervals.push((start, end)));
+        self.n_bases = stream.n_bases();
+        Ok(intervals)
+    }
+    /// Marks the end of the sequence and returns the remaining low-complexity
+    /// regions.
+    ///
+    /// Returns
+    /// -------
+    /// list of tuples
+    ///     The start and end positions of the low-complexity regions that were
+    ///     not returned by `feed()`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///    If the stream is already finished.
+    /// SequenceTooShortError
+    ///    If the sequence is too short (less than 4 characters).
+    fn finish(&mut self) -> PyResult<Vec<(usize, usize)>> {
+        self.stream()?;
+        if self.n_bases < MIN_SEQUENCE_LENGTH {
+            return Err(errors::input(InputError::SequenceLengthError(self.n_bases)));
+        }
+        let mut intervals = Vec::new();
+        if let Some(stream) = self.stream.take() {
+            stream.finish(|start, end| intervals.push((start, end)));
+        }
+        Ok(intervals)
+    }
+    /// Returns the number of bytes of memory held by the StreamingDustMasker.
+    ///
+    /// Returns
+    /// -------
+    /// dict
+    ///     The same keys as `DustMasker.memory_usage()`. Neither the sequence
+    ///     nor the regions returned by `feed()` are kept, so only the `buffers`
+    ///     that hold the state of the algorithm and the regions that can still
+    ///     change are counted, which don't grow with the length of the
+    ///     sequence.
+    fn memory_usage<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let buffers = size_of::<Self>()
+            + self
+                .stream
+                .as_ref()
+                .map_or(0, StreamingDust::allocated_bytes);
+        memory_usage_dict(py, 0, 0, 0, buffers)
+    }
+    fn __repr__(&self) -> String {
+        format!(
+            "StreamingDustMasker(window_size: {}, score_threshold: {}, n_bases: {})",
+            self.window_size, self.score_threshold, self.n_bases
+        )
+    }
+}
+
+/// Set the maximum number of entries of the cache of DustMasker results.
+///
+/// While the cache is enabled, creating a DustMasker from a sequence that was
+/// recently processed with the same window size and score threshold reuses
+/// the intervals that were found, which is useful when processing datasets
+/// with many duplicate sequences. Entries are indexed by a hash of the
+/// sequence and parameters, and the oldest ones are evicted first when the
+/// cache is full. Cached sequences are kept alive but not copied.
+///
+/// Parameters
+/// ----------
+/// max_entries : int
+///     The maximum number of cached results. The cache is disabled if 0, which
+///     is the default.
+///
+/// Raises
+/// ------
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the maximum number of entries.
+#[pyfunction]
+fn set_cache_size(max_entries: usize) {
+    cache::global().set_capacity(max_entries);
+}
+
+/// Remove all the entries of the cache of DustMasker results and reset its
+/// statistics.
+#[pyfunction]
+fn clear_cache() {
+    cache::global().clear();
+}
+
+/// Returns statistics of the cache of DustMasker results.
+///
+/// Returns
+/// -------
+/// dict
+///     The number of `hits` and `misses` since the cache was last cleared, its
+///     maximum number of entries (`max_entries`) and its current number of
+///     entries (`n_entries`).
+#[pyfunction]
+fn cache_info() -> HashMap<&'static str, usize> {
+    let (hits, misses, max_entries, n_entries) = cache::global().info();
+    HashMap::from([
+        ("hits", hits),
+        ("misses", misses),
+        ("max_entries", max_entries),
+        ("n_entries", n_entries),
+    ])
+}
+
+/// Count the bases within low-complexity regions of a nucleotide sequence,
+/// without storing the regions.
+///
+/// This is faster and uses less memory than creating a DustMasker when only
+/// the amount of masking is needed, such as when computing the masked fraction
+/// of many sequences.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence to be processed. Characters
+///     other than 'A', 'C', 'G', 'T', 'a', 'c', 'g', 't' will be considered
+///     ambiguous bases. The minimum allowed sequence length is 4 bases.
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows. The minimum allowed value is 0.
+/// threads : int, default: 1
+///     Number of threads used to process the sequence. If 0, one thread per
+///     available CPU core is used.
+/// bin_size : int, optional
+///     If given, the masked bases are counted separately within each
+///     consecutive bin of `bin_size` bases (the last bin can be shorter).
+///
+/// Returns
+/// -------
+/// int or list of int
+///     The number of masked bases, which is equal to the `n_masked_bases`
+///     attribute of a DustMasker, or the number of masked bases in each bin if
+///     `bin_size` is given.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If the input sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If the bin size is 0.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size, score threshold,
+///    number of threads or bin size.
+#[pyfunction]
+#[pyo3(signature = (sequence, window_size=64, score_threshold=20, threads=1, bin_size=None))]
+fn count_masked_bases(
+    py: Python<'_>,
+    sequence: &str,
+    window_size: usize,
+    score_threshold: usize,
+    threads: usize,
+    bin_size: Option<usize>,
+) -> PyResult<PyObject> {
+    validate_inputs(sequence.as_bytes(), window_size).map_err(errors::input)?;
+    let sequence = sequence.as_bytes();
+    Ok(match bin_size {
+        None => {
+            count::count_masked_bases(sequence, window_size, score_threshold, threads).into_py(py)
+        }
+        Some(0) => return Err(PyValueError::new_err("invalid bin size '0'")),
+        Some(bin_size) => {
+            count::bin_masked_bases(sequence, window_size, score_threshold, threads, bin_size)
+                .into_py(py)
+        }
+    })
+}
+
+/// The offsets of the sequences packed in a buffer, as integers of any width,
+/// such as the offsets of an Arrow string array
+#[derive(FromPyObject)]
+enum PackedOffsets {
+    I32(PyBuffer<i32>),
+    I64(PyBuffer<i64>),
+    U32(PyBuffer<u32>),
+    U64(PyBuffer<u64>),
+    List(Vec<usize>),
+}
+
+impl PackedOffsets {
+    fn to_vec(&self, py: Python<'_>) -> PyResult<Vec<usize>> {
+        fn positions<T: Copy + TryInto<usize>>(values: Vec<T>) -> PyResult<Vec<usize>> {
+            values
+                .into_iter()
+                .map(|value| {
+                    value
+                        .try_into()
+                        .map_err(|_| PyValueError::new_err("invalid negative offset"))
+                })
+                .collect()
+        }
+        match self {
+            PackedOffsets::I32(buffer) => positions(buffer.to_vec(py)?),
+            PackedOffsets::I64(buffer) => positions(buffer.to_vec(py)?),
+            PackedOffsets::U32(buffer) => positions(buffer.to_vec(py)?),
+            PackedOffsets::U64(buffer) => positions(buffer.to_vec(py)?),
+            PackedOffsets::List(offsets) => Ok(offsets.clone()),
+        }
+    }
+}
+
+/// Identify the low-complexity regions of many sequences, such as millions of
+/// short contigs, without creating a Python object per sequence or region.
+///
+/// The sequences are distributed among threads, each one of them processed in
+/// a single thread, and the regions of all the sequences are returned in a
+/// single flat array.
+///
+/// Parameters
+/// ----------
+/// sequences : iterable of str or bytes, or bytes-like
+///     The sequences (e.g., a list or a NumPy array of strings), whose
+///     characters are read in place. If `offsets` is given, a bytes-like
+///     object (e.g., the data buffer of an Arrow string array) with the
+///     concatenated sequences instead, which is copied once.
+/// offsets : bytes-like or list of int, optional
+///     The position of each sequence in the concatenated `sequences`, followed
+///     by the end of the last one, as in Arrow string arrays (e.g.,
+///     `numpy.asarray(array.offsets)` for an Arrow array whose data buffer is
+///     `array.buffers()[2]`).
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows. The minimum allowed value is 0.
+/// threads : int, default: 1
+///     Number of threads that process the sequences. If 0, one thread per
+///     available CPU core is used.
+/// allow_short : bool, default: False
+///     If True, sequences shorter than 4 characters have no regions instead of
+///     raising a `SequenceTooShortError`.
+///
+/// Returns
+/// -------
+/// tuple of memoryview
+///     The start and end positions of the low-complexity regions of all the
+///     sequences as a flat array of unsigned integers (start_1, end_1, start_2,
+///     end_2, …), like the `interval_array` attribute of a DustMasker, and an
+///     array of 64-bit unsigned integers (format 'Q') where the regions of the
+///     i-th sequence are the ones from the `offsets[i]`-th to the
+///     `offsets[i + 1]`-th. Positions are relative to the start of each
+///     sequence. They can be converted to NumPy arrays with `numpy.asarray`
+///     (e.g., `numpy.asarray(intervals).reshape(-1, 2)`).
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If a sequence is too short (less than 4 characters) and `allow_short` is
+///    False.
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If the offsets aren't increasing or exceed the length of the
+///    concatenated sequences.
+/// TypeError
+///    If the input parameters are not of the expected type, or if `sequences`
+///    is a single sequence and `offsets` is not given.
+/// OverflowError
+///    If a negative integer is passed as the window size, score threshold or
+///    number of threads.
+///
+/// Warns
+/// -----
+/// WindowSizeWarning
+///    If the window size is larger than a sequence.
+#[pyfunction]
+#[pyo3(signature = (sequences, offsets=None, window_size=64, score_threshold=20, threads=1, allow_short=false))]
+fn mask_bulk<'py>(
+    py: Python<'py>,
+    sequences: &Bound<'py, PyAny>,
+    offsets: Option<PackedOffsets>,
+    window_size: usize,
+    score_threshold: usize,
+    threads: usize,
+    allow_short: bool,
+) -> PyResult<(Bound<'py, PyMemoryView>, Bound<'py, PyMemoryView>)> {
+    let items: Vec<Bound<'py, PyAny>>;
+    let packed: Vec<u8>;
+    let mut slices: Vec<&[u8]> = match offsets {
+        None => {
+            // A single sequence would be iterated as characters or integers
+            if sequences.is_instance_of::<PyString>() || sequences.is_instance_of::<PyBytes>() {
+                return Err(PyTypeError::new_err(
+                    "expected a list of sequences, not a single sequence",
+                ));
+            }
+            items = sequences.iter()?.collect::<PyResult<_>>()?;
+            items
+                .iter()
+                .map(|item| match item.downcast::<PyString>() {
+                    Ok(sequence) => Ok(sequence.to_str()?.as_bytes()),
+                    Err(_) => Ok(item.downcast::<PyBytes>()?.as_bytes()),
+                })
+                .collect::<PyResult<_>>()?
+        }
+        Some(offsets) => {
+            packed = PyBuffer::<u8>::get_bound(sequences)?.to_vec(py)?;
+            let offsets = offsets.to_vec(py)?;
+            if let Some(pair) = offsets
+                .windows(2)
+                .find(|pair| pair[0] > pair[1] || pair[1] > packed.len())
+            {
+                return Err(PyValueError::new_err(format!(
+                    "invalid offsets '{}' and '{}' for sequences of length '{}'",
+                    pair[0],
+                    pair[1],
+                    packed.len()
+                )));
+            }
+            offsets
+                .windows(2)
+                .map(|pair| &packed[pair[0]..pair[1]])
+                .collect()
+        }
+    };
+    for sequence in slices.iter_mut() {
+        if allow_short && sequence.len() < MIN_SEQUENCE_LENGTH {
+            // Empty sequences have no regions
+            *sequence = &[];
+        } else {
+            validate_inputs(sequence, window_size).map_err(errors::input)?;
+        }
+    }
+    let lengths = slices.iter().map(|sequence| sequence.len());
+    if let Some(shortest) = lengths.filter(|&length| length > 0).min() {
+        errors::warn_window_size(py, window_size, shortest)?;
+    }
+    let results = py
+        .allow_threads(|| parallel::process_batch(&slices, window_size, score_threshold, threads));
+    let interval_offsets = std::iter::once(0)
+        .chain(results.iter().scan(0, |count, sequence_intervals| {
+            *count += sequence_intervals.len() as u64;
+            Some(*count)
+        }))
+        .collect();
+    let intervals: Intervals = results.into_iter().flatten().collect();
+    let intervals = Bound::new(py, Buffer::from_intervals(Arc::new(intervals)))?;
+    let interval_offsets = Bound::new(py, Buffer::from_integers(interval_offsets))?;
+    Ok((
+        PyMemoryView::from_bound(intervals.as_any())?,
+        PyMemoryView::from_bound(interval_offsets.as_any())?,
+    ))
+}
+
+/// Check whether the fraction of masked bases of a nucleotide sequence exceeds
+/// a threshold.
+///
+/// Processing stops as soon as the low-complexity regions found so far are
+/// enough to exceed the threshold, which makes it much faster than creating a
+/// DustMasker when filtering out low-complexity sequences, such as reads.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence to be processed. Characters
+///     other than 'A', 'C', 'G', 'T', 'a', 'c', 'g', 't' will be considered
+///     ambiguous bases. The minimum allowed sequence length is 4 bases.
+/// max_masked_fraction : float
+///     The fraction of masked bases (between 0 and 1) above which the sequence
+///     is considered low-complexity.
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows. The minimum allowed value is 0.
+///
+/// Returns
+/// -------
+/// bool
+///     True if the fraction of masked bases is greater than
+///     `max_masked_fraction`.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If the input sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If the fraction is not between 0 and 1.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size or score threshold.
+#[pyfunction]
+#[pyo3(signature = (sequence, max_masked_fraction, window_size=64, score_threshold=20))]
+fn exceeds_masked_fraction(
+    sequence: &str,
+    max_masked_fraction: f64,
+    window_size: usize,
+    score_threshold: usize,
+) -> PyResult<bool> {
+    validate_inputs(sequence.as_bytes(), window_size).map_err(errors::input)?;
+    if !(0.0..=1.0).contains(&max_masked_fraction) {
+        return Err(PyValueError::new_err(format!(
+            "invalid masked fraction '{max_masked_fraction}', must be between '0' and '1'"
+        )));
+    }
+    // The number of masked bases is an integer, so it exceeds the fraction if
+    // and only if it exceeds its integer part
+    let max_masked_bases = (max_masked_fraction * sequence.len() as f64).floor() as usize;
+    Ok(count::exceeds_masked_bases(
+        sequence.as_bytes(),
+        window_size,
+        score_threshold,
+        max_masked_bases,
+    ))
+}
+
+/// Creates a DustMasker whose score threshold masks the fraction of the
+/// sequence closest to a target.
+///
+/// The threshold is found with a binary search, which counts the masked bases
+/// of about ten thresholds without storing the regions, so that the masking
+/// can be set by the fraction of the sequence to be masked (e.g., 3% of a
+/// genome) rather than by the threshold.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence to be processed. Characters
+///     other than 'A', 'C', 'G', 'T', 'a', 'c', 'g', 't' will be considered
+///     ambiguous bases. The minimum allowed sequence length is 4 bases.
+/// masked_fraction : float
+///     The target fraction of masked bases, between 0 and 1.
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// threads : int, default: 1
+///     Number of threads used to process the sequence, like in DustMasker.
+///
+/// Returns
+/// -------
+/// DustMasker
+///     The DustMasker of the sequence with the chosen `score_threshold`, the
+///     highest one among the thresholds whose masked fractions are equally
+///     close to the target. Its actual masked fraction is
+///     `n_masked_bases / len(sequence)`.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If the input sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If the masked fraction is not between 0 and 1.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size or number of threads.
+#[pyfunction]
+#[pyo3(signature = (sequence, masked_fraction, window_size=64, threads=1))]
+fn calibrate(
+    sequence: Bound<'_, PyString>,
+    masked_fraction: f64,
+    window_size: usize,
+    threads: usize,
+) -> PyResult<DustMasker> {
+    let sequence_str = sequence.to_str()?;
+    validate_inputs(sequence_str.as_bytes(), window_size).map_err(errors::input)?;
+    if !(0.0..=1.0).contains(&masked_fraction) {
+        return Err(PyValueError::new_err(format!(
+            "invalid masked fraction '{masked_fraction}', must be between '0' and '1'"
+        )));
+    }
+    let score_threshold = count::calibrate_score_threshold(
+        sequence_str.as_bytes(),
+        window_size,
+        masked_fraction,
+        threads,
+    );
+    DustMasker::with_options(
+        sequence,
+        window_size,
+        score_threshold,
+        threads,
+        Options::default(),
+    )
+}
+
+/// Identify low-complexity regions in a 2-bit packed nucleotide sequence
+/// without decoding it.
+///
+/// Bases are packed four per byte, with the first base in the two most
+/// significant bits, as in the UCSC .2bit format. The mapping between codes and
+/// nucleotides doesn't affect the result. Ambiguous bases, which cannot be
+/// represented with 2 bits, are given as a list of regions.
+///
+/// Parameters
+/// ----------
+/// packed : bytes
+///     The packed nucleotide sequence.
+/// length : int
+///     The number of bases in the sequence. The minimum allowed sequence length
+///     is 4 bases.
+/// n_blocks : list of tuples, default: []
+///     The start and end positions of the regions made of ambiguous bases,
+///     sorted and non-overlapping.
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows. The minimum allowed value is 0.
+///
+/// Returns
+/// -------
+/// list of tuples
+///     The start and end positions of the low-complexity regions, identical to
+///     the intervals of a DustMasker created from the decoded sequence.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If the sequence is too short (less than 4 bases).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If `packed` holds fewer than `length` bases or if the N blocks are
+///    invalid.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the length, window size or score
+///    threshold.
+#[pyfunction]
+#[pyo3(signature = (packed, length, n_blocks=Vec::new(), window_size=64, score_threshold=20))]
+fn intervals_from_2bit(
+    packed: &[u8],
+    length: usize,
+    n_blocks: Vec<(usize, usize)>,
+    window_size: usize,
+    score_threshold: usize,
+) -> PyResult<Vec<(usize, usize)>> {
+    if length < MIN_SEQUENCE_LENGTH {
+        return Err(errors::input(InputError::SequenceLengthError(length)));
+    }
+    DustConfig::builder()
+        .window_size(window_size)
+        .score_threshold(score_threshold)
+        .build()
+        .map_err(errors::input)?;
+    let mut last_end = 0;
+    for &(start, end) in &n_blocks {
+        if start < last_end || start > end || end > length {
+            return Err(errors::input(InputError::NBlockError(start, end)));
+        }
+        last_end = end;
+    }
+    let n_blocks: Vec<_> = n_blocks
+        .into_iter()
+        .map(|(start, end)| start..end)
+        .collect();
+    let mut intervals = Vec::new();
+    twobit::process_with(
+        packed,
+        length,
+        &n_blocks,
+        window_size,
+        score_threshold,
+        |start, end| intervals.push((start, end)),
+    )
+    .map_err(errors::input)?;
+    Ok(intervals)
+}
+
+/// Find the perfect intervals of a nucleotide sequence, the intermediate
+/// regions that are merged into its low-complexity regions.
+///
+/// A perfect interval is a region whose score is above the threshold and at
+/// least as high as the score of any region it contains. The low-complexity
+/// regions identified by DustMasker are the union of the overlapping or
+/// adjacent perfect intervals returned by this function.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence to be processed. Characters
+///     other than 'A', 'C', 'G', 'T', 'a', 'c', 'g', 't' will be considered
+///     ambiguous bases. The minimum allowed sequence length is 4 bases.
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows. The minimum allowed value is 0.
+///
+/// Returns
+/// -------
+/// list of tuples
+///     The start and end positions, the score and the length of each perfect
+///     interval, sorted by start position. The score is the number of pairs of
+///     identical triplets in the interval and the length is its number of
+///     triplets minus one, so that the interval is perfect because
+///     `10 * score / length` is above `score_threshold`. Unlike the
+///     low-complexity regions, the end position is not clipped to the end of
+///     the sequence.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If the input sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size or score threshold.
+#[pyfunction]
+#[pyo3(signature = (sequence, window_size=64, score_threshold=20))]
+fn perfect_intervals(
+    sequence: &str,
+    window_size: usize,
+    score_threshold: usize,
+) -> PyResult<Vec<(usize, usize, usize, usize)>> {
+    validate_inputs(sequence.as_bytes(), window_size).map_err(errors::input)?;
+    Ok(
+        SymmetricDust::perfect_intervals(sequence.as_bytes(), window_size, score_threshold)
+            .into_iter()
+            .map(|interval| (interval.start, interval.finish, interval.score, interval.l))
+            .collect(),
+    )
+}
+
+/// The state of the window reported to Python (see `trace_window`)
+type WindowStateTuple = (usize, usize, usize, usize, usize, bool);
+
+/// Trace the state of the window of the symmetric DUST algorithm while it scans
+/// a nucleotide sequence.
+///
+/// This is meant to debug differences with other implementations of the
+/// algorithm, as it reports the running scores defined in the paper after each
+/// base is added to the window. Ambiguous bases and the two bases that follow
+/// them aren't added to the window, so no state is reported for them.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence to be processed. Characters
+///     other than 'A', 'C', 'G', 'T', 'a', 'c', 'g', 't' will be considered
+///     ambiguous bases. The minimum allowed sequence length is 4 bases.
+/// window_size : int, default: 64
+///     The length of the window used by symmetric DUST algorithm. The minimum
+///     allowed value is 3.
+/// score_threshold : int, default: 20
+///     Score threshold for subwindows. The minimum allowed value is 0.
+/// callback : callable, optional
+///     If given, it is called with the state of the window after each base
+///     instead of collecting the states in a list.
+///
+/// Returns
+/// -------
+/// list of tuples or None
+///     The state of the window after each base, given as the position of the
+///     base, the number of triplets in the window, the scores `rw` and `rv`, the
+///     number of triplets `L` of the suffix scored by `rv`, and whether the
+///     window score triggered the search for perfect intervals. None if a
+///     callback is given.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If the input sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size or score threshold.
+#[pyfunction]
+#[pyo3(signature = (sequence, window_size=64, score_threshold=20, callback=None))]
+fn trace_window(
+    sequence: &str,
+    window_size: usize,
+    score_threshold: usize,
+    callback: Option<Bound<'_, PyAny>>,
+) -> PyResult<Option<Vec<WindowStateTuple>>> {
+    validate_inputs(sequence.as_bytes(), window_size).map_err(errors::input)?;
+    let to_tuple = |state: &WindowState| {
+        (
+            state.position,
+            state.n_triplets,
+            state.rw,
+            state.rv,
+            state.l,
+            state.triggered,
+        )
+    };
+    match callback {
+        Some(callback) => {
+            // The scan can't be interrupted, so the states that follow an
+            // exception are ignored
+            let mut result = Ok(());
+            SymmetricDust::trace_window(
+                sequence.as_bytes(),
+                window_size,
+                score_threshold,
+                |state| {
+                    if result.is_ok() {
+                        result = callback.call1((to_tuple(state),)).map(|_| ());
+                    }
+                },
+            );
+            result.map(|_| None)
+        }
+        None => {
+            let mut states = Vec::new();
+            SymmetricDust::trace_window(
+                sequence.as_bytes(),
+                window_size,
+                score_threshold,
+                |state| states.push(to_tuple(state)),
+            );
+            Ok(Some(states))
+        }
+    }
+}
+
+/// Returns the reverse complement of a nucleotide sequence.
+///
+/// The IUPAC nucleotide codes are complemented preserving their case ('S', 'W'
+/// and 'N' are their own complements, and 'U' is complemented to 'A'), and any
+/// other character is kept as is.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence.
+///
+/// Returns
+/// -------
+/// str
+///     The reverse complement of the sequence.
+///
+/// Raises
+/// ------
+/// TypeError
+///    If the input parameters are not of the expected type.
+#[pyfunction]
+fn reverse_complement(sequence: &str) -> String {
+    if sequence.is_ascii() {
+        let reverse_complement = strand::reverse_complement(sequence.as_bytes());
+        // The complements of ASCII characters are ASCII characters
+        return String::from_utf8(reverse_complement).unwrap();
+    }
+    sequence
+        .chars()
+        .rev()
+        .map(|c| match u8::try_from(c) {
+            Ok(base) if base.is_ascii() => strand::complement(base) as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Converts low-complexity regions to the coordinates of the minus strand.
+///
+/// Parameters
+/// ----------
+/// intervals : list of tuples
+///     The start and end positions of the regions, sorted by start position,
+///     such as the `intervals` attribute of a DustMasker.
+/// length : int
+///     The length of the sequence.
+///
+/// Returns
+/// -------
+/// list of tuples
+///     The start and end positions of the regions in the reverse complement of
+///     the sequence, sorted by start position.
+///
+/// Raises
+/// ------
+/// ValueError
+///    If a region ends past the end of the sequence.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as a position or as the length.
+#[pyfunction]
+fn reverse_intervals(
+    intervals: Vec<(usize, usize)>,
+    length: usize,
+) -> PyResult<Vec<(usize, usize)>> {
+    if let Some((start, end)) = intervals.iter().find(|&&(_, end)| end > length) {
+        return Err(PyValueError::new_err(format!(
+            "invalid region '{start}..{end}' for a sequence of length '{length}'"
+        )));
+    }
+    Ok(strand::reverse_intervals(intervals.into_iter(), length))
+}
+
+/// Concatenates the results of several sequences (e.g., the contigs of a
+/// scaffold) into the results of a single sequence.
+///
+/// The low-complexity regions of each sequence are offset to its position in
+/// the concatenated sequence and merged with the regions that overlap or are
+/// adjacent to them, such as the regions ending and starting at the junction
+/// of two sequences. The regions are not found again, so regions that span a
+/// junction only if the sequences are concatenated aren't found.
+///
+/// Parameters
+/// ----------
+/// maskers : list of DustMasker
+///     The results of each sequence, in the order of the concatenated
+///     sequence. They must have been created with the same window size, score
+///     threshold and k-mer size.
+/// offsets : list of int, optional
+///     The position of the first base of each sequence in the concatenated
+///     sequence, in increasing order. The bases between the end of a sequence
+///     and the start of the next one (e.g., scaffolding gaps) are 'N'. If not
+///     given, the sequences are concatenated without gaps.
+///
+/// Returns
+/// -------
+/// DustMasker
+///     A DustMasker of the concatenated sequence with the offset regions. Its
+///     `edit()` method scans the whole edited sequence again.
+///
+/// Raises
+/// ------
+/// ValueError
+///    If no DustMasker is given, if they were created with different
+///    parameters, if the number of offsets differs from the number of
+///    DustMaskers or if a sequence would overlap the previous one.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as an offset.
+#[pyfunction]
+#[pyo3(signature = (maskers, offsets=None))]
+fn concatenate(
+    py: Python<'_>,
+    maskers: Vec<PyRef<'_, DustMasker>>,
+    offsets: Option<Vec<usize>>,
+) -> PyResult<DustMasker> {
+    let Some(first) = maskers.first() else {
+        return Err(PyValueError::new_err("no DustMasker to concatenate"));
+    };
+    if let Some(masker) = maskers.iter().find(|masker| {
+        (masker.window_size, masker.score_threshold, masker.kmer_size)
+            != (first.window_size, first.score_threshold, first.kmer_size)
+    }) {
+        return Err(PyValueError::new_err(format!(
+            "the DustMaskers have different parameters: window size '{}', score threshold \
+             '{}' and k-mer size '{}', and window size '{}', score threshold '{}' and \
+             k-mer size '{}'",
+            first.window_size,
+            first.score_threshold,
+            first.kmer_size,
+            masker.window_size,
+            masker.score_threshold,
+            masker.kmer_size
+        )));
+    }
+    let sequences = maskers
+        .iter()
+        .map(|masker| masker.sequence.bind(py).to_str())
+        .collect::<PyResult<Vec<&str>>>()?;
+    let offsets = match offsets {
+        Some(offsets) if offsets.len() != maskers.len() => {
+            return Err(PyValueError::new_err(format!(
+                "invalid number of offsets '{}', must be the number of DustMaskers '{}'",
+                offsets.len(),
+                maskers.len()
+            )));
+        }
+        Some(offsets) => offsets,
+        None => sequences
+            .iter()
+            .scan(0, |end, sequence| {
+                let offset = *end;
+                *end += sequence.len();
+                Some(offset)
+            })
+            .collect(),
+    };
+    let mut sequence = String::new();
+    let mut intervals = Vec::new();
+    for ((masker, part), &offset) in maskers.iter().zip(&sequences).zip(&offsets) {
+        if offset < sequence.len() {
+            return Err(PyValueError::new_err(format!(
+                "invalid offset '{offset}', the sequence would overlap the previous one, \
+                 which ends at '{}'",
+                sequence.len()
+            )));
+        }
+        sequence.extend(std::iter::repeat_n('N', offset - sequence.len()));
+        sequence.push_str(part);
+        intervals.extend(
+            masker
+                .intervals
+                .iter()
+                .map(|(start, end)| (offset + start, offset + end.min(part.len()))),
+        );
+    }
+    let concatenated: Intervals = interval_set::normalize(intervals).into_iter().collect();
+    // The padding between the sequences is made of N characters, which join
+    // the runs of ambiguous characters at their ends
+    let details = ScanDetails::of(
+        sequence.as_bytes(),
+        maskers.iter().all(|masker| masker.composition.is_some()),
+    );
+    Ok(DustMasker {
+        sequence: PyString::new_bound(py, &sequence).unbind(),
+        window_size: first.window_size,
+        score_threshold: first.score_threshold,
+        kmer_size: first.kmer_size,
+        masker: Arc::clone(&first.masker),
+        intervals: Arc::new(concatenated),
+        spill_threshold: first.spill_threshold,
+        full_rescan: true,
+        allow_short: first.allow_short,
+        ambiguous_intervals: details.ambiguous_runs,
+        composition: details.composition,
+    })
+}
+
+/// Simulates a random nucleotide sequence with planted low-complexity regions.
+///
+/// The planted regions don't overlap, and each one of them repeats a random
+/// unit (e.g., a single base for homopolymers, or two bases for dinucleotide
+/// repeats). Together with `DustMasker.evaluate()`, the simulated sequences can
+/// be used to choose the parameters of the algorithm.
+///
+/// Parameters
+/// ----------
+/// length : int
+///     The length of the sequence.
+/// n_regions : int, default: 10
+///     The number of planted regions.
+/// region_length : tuple of int, default: (20, 200)
+///     The minimum and maximum lengths of the planted regions.
+/// unit_length : tuple of int, default: (1, 6)
+///     The minimum and maximum lengths of the units repeated in each region.
+/// mutation_rate : float, default: 0.0
+///     The probability of replacing each base of the planted regions with a
+///     random base, to make the repeats imperfect.
+/// seed : int, optional
+///     The seed of the random number generator. The same seed always gives
+///     the same sequence. By default, a random seed is used.
+///
+/// Returns
+/// -------
+/// tuple
+///     The simulated sequence and the start and end positions of the planted
+///     regions, sorted by start position.
+///
+/// Raises
+/// ------
+/// ValueError
+///    If the ranges of lengths are empty or include 0, if the mutation rate is
+///    not between 0 and 1, or if the planted regions may not fit in the
+///    sequence.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as a length, the number of regions or
+///    the seed.
+#[pyfunction]
+#[pyo3(signature = (length, n_regions=10, region_length=(20, 200), unit_length=(1, 6), mutation_rate=0.0, seed=None))]
+fn simulate(
+    length: usize,
+    n_regions: usize,
+    region_length: (usize, usize),
+    unit_length: (usize, usize),
+    mutation_rate: f64,
+    seed: Option<u64>,
+) -> PyResult<(String, Vec<(usize, usize)>)> {
+    let seed = seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+    let simulation = dustmasker::simulate::simulate(
+        length,
+        n_regions,
+        region_length.0..=region_length.1,
+        unit_length.0..=unit_length.1,
+        mutation_rate,
+        seed,
+    )
+    .map_err(errors::input)?;
+    // The simulated sequence only contains A/C/G/T bases
+    let sequence = String::from_utf8(simulation.sequence).unwrap();
+    Ok((sequence, simulation.planted))
+}
+
+/// One or more sequences passed to `sweep`
+#[derive(FromPyObject)]
+enum Sequences {
+    One(PyBackedStr),
+    Many(Vec<PyBackedStr>),
+}
+
+/// Counts the low-complexity regions of nucleotide sequences for each
+/// combination of window sizes and score thresholds.
+///
+/// Each sequence is scanned once for all the combinations, sharing the
+/// encoding of its bases, and the regions are counted without being stored,
+/// which makes it much faster than creating a DustMasker for each combination.
+///
+/// Parameters
+/// ----------
+/// sequences : str or list of str
+///     A nucleotide sequence, or multiple sequences (e.g., the records of a
+///     FASTA file) whose counts are added up. Characters other than 'A', 'C',
+///     'G', 'T', 'a', 'c', 'g', 't' will be considered ambiguous bases. The
+///     minimum allowed sequence length is 4 bases.
+/// window_sizes : list of int, default: [64]
+///     The window sizes to be tried. The minimum allowed value is 3.
+/// score_thresholds : list of int, default: [20]
+///     The score thresholds to be tried.
+///
+/// Returns
+/// -------
+/// dict of lists
+///     A table with one row per combination, in the order of `window_sizes`
+///     and then `score_thresholds`, with the columns `window_size`,
+///     `score_threshold`, `n_intervals`, `n_masked_bases` and
+///     `masked_fraction`. It can be converted to a data frame with
+///     `pandas.DataFrame` or `polars.DataFrame`.
+///
+/// Raises
+/// ------
+/// SequenceTooShortError
+///    If a sequence is too short (less than 4 characters).
+/// InvalidWindowError
+///    If a window size is too small (less than 3).
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as a window size or score threshold.
+#[pyfunction]
+#[pyo3(signature = (sequences, window_sizes=vec![64], score_thresholds=vec![20]))]
+fn sweep<'py>(
+    py: Python<'py>,
+    sequences: Sequences,
+    window_sizes: Vec<usize>,
+    score_thresholds: Vec<usize>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let sequences = match sequences {
+        Sequences::One(sequence) => vec![sequence],
+        Sequences::Many(sequences) => sequences,
+    };
+    let mut points: Vec<SweepPoint> = window_sizes
+        .iter()
+        .flat_map(|&window_size| {
+            score_thresholds
+                .iter()
+                .map(move |&score_threshold| SweepPoint {
+                    window_size,
+                    score_threshold,
+                    ..SweepPoint::default()
+                })
+        })
+        .collect();
+    let mut n_bases = 0;
+    for sequence in &sequences {
+        let sequence_points =
+            dustmasker::sweep::sweep(sequence.as_bytes(), &window_sizes, &score_thresholds)
+                .map_err(errors::input)?;
+        for (point, sequence_point) in points.iter_mut().zip(sequence_points) {
+            point.n_intervals += sequence_point.n_intervals;
+            point.n_masked_bases += sequence_point.n_masked_bases;
+        }
+        n_bases += sequence.len();
+    }
+    let table = PyDict::new_bound(py);
+    let column = |get: fn(&SweepPoint) -> usize| points.iter().map(get).collect::<Vec<_>>();
+    table.set_item("window_size", column(|point| point.window_size))?;
+    table.set_item("score_threshold", column(|point| point.score_threshold))?;
+    table.set_item("n_intervals", column(|point| point.n_intervals))?;
+    table.set_item("n_masked_bases", column(|point| point.n_masked_bases))?;
+    let masked_fractions: Vec<f64> = points
+        .iter()
+        .map(|point| match n_bases {
+            0 => 0.0,
+            n_bases => point.n_masked_bases as f64 / n_bases as f64,
+        })
+        .collect();
+    table.set_item("masked_fraction", masked_fractions)?;
+    Ok(table)
+}
+
+/// Names of the metrics computed by `window_metrics`
+const WINDOW_METRICS: [&str; 3] = ["dust", "entropy", "gc"];
+
+/// Computes complexity metrics over sliding windows of a nucleotide sequence.
+///
+/// The metrics are computed for every window, regardless of whether it is
+/// masked, so that they can be plotted along the sequence or used as features.
+/// Windows start every `step` bases from the first base of the sequence, and
+/// the last window is the last one that fits in the sequence, so sequences
+/// shorter than the window have none.
+///
+/// Parameters
+/// ----------
+/// sequence : str
+///     A string representing the nucleotide sequence to be processed. Characters
+///     other than 'A', 'C', 'G', 'T', 'a', 'c', 'g', 't' are not counted, and
+///     neither are the triplets that include them.
+/// window_size : int, default: 64
+///     The length of the windows. The minimum allowed value is 3.
+/// step : int, default: 1
+///     The distance between the starts of consecutive windows.
+/// metrics : list of str, default: ["dust", "entropy", "gc"]
+///     The metrics to compute: the DUST score of the window (`dust`), which is
+///     the number of pairs of identical triplets divided by the number of
+///     triplets minus one and is on the scale of `DustMasker.level`, the
+///     Shannon entropy of the frequencies of its A, C, G and T bases, in bits
+///     (`entropy`), and its fraction of G and C among them (`gc`).
+///
+/// Returns
+/// -------
+/// dict of memoryview
+///     The value of each metric for each window, as an array of 64-bit floats
+///     (format 'd'), which can be converted to a NumPy array with
+///     `numpy.asarray`.
+///
+/// Raises
+/// ------
+/// InvalidWindowError
+///    If the window size is too small (less than 3).
+/// ValueError
+///    If the step is 0 or a metric is unknown.
+/// TypeError
+///    If the input parameters are not of the expected type.
+/// OverflowError
+///    If a negative integer is passed as the window size or step.
+#[pyfunction]
+#[pyo3(signature = (sequence, window_size=64, step=1, metrics=None))]
+fn window_metrics<'py>(
+    py: Python<'py>,
+    sequence: &str,
+    window_size: usize,
+    step: usize,
+    metrics: Option<Vec<String>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let names = metrics.unwrap_or_else(|| WINDOW_METRICS.map(String::from).to_vec());
+    if let Some(name) = names
+        .iter()
+        .find(|name| !WINDOW_METRICS.contains(&name.as_str()))
+    {
+        return Err(PyValueError::new_err(format!(
+            "unknown metric '{name}', must be one of {WINDOW_METRICS:?}"
+        )));
+    }
+    let mut values =
+        metrics::window_metrics(sequence.as_bytes(), window_size, step).map_err(errors::input)?;
+    let dict = PyDict::new_bound(py);
+    for name in names {
+        if dict.contains(&name)? {
+            continue;
+        }
+        let metric = match name.as_str() {
+            "dust" => std::mem::take(&mut values.dust),
+            "entropy" => std::mem::take(&mut values.entropy),
+            _ => std::mem::take(&mut values.gc),
+        };
+        let buffer = Bound::new(py, Buffer::from_values(metric))?;
+        dict.set_item(name, PyMemoryView::from_bound(buffer.as_any())?)?;
     }
+    Ok(dict)
 }
 
 #[pymodule]
 fn _pydustmasker(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    // Forward the log records of the library to the `logging` module, under
+    // the `dustmasker` logger and its children
+    #[cfg(feature = "tracing")]
+    pyo3_log::init();
     m.add_class::<DustMasker>()?;
+    m.add_class::<StreamingDustMasker>()?;
+    m.add_class::<KmerIterator>()?;
+    m.add_class::<MaskedSequence>()?;
+    m.add_class::<MaskedSequenceIterator>()?;
+    m.add_class::<Region>()?;
+    m.add_class::<MaskingSummary>()?;
+    m.add_class::<CancelToken>()?;
+    m.add_function(wrap_pyfunction!(set_cache_size, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(cache_info, m)?)?;
+    m.add_function(wrap_pyfunction!(count_masked_bases, m)?)?;
+    m.add_function(wrap_pyfunction!(mask_bulk, m)?)?;
+    m.add_function(wrap_pyfunction!(exceeds_masked_fraction, m)?)?;
+    m.add_function(wrap_pyfunction!(calibrate, m)?)?;
+    m.add_function(wrap_pyfunction!(intervals_from_2bit, m)?)?;
+    m.add_function(wrap_pyfunction!(perfect_intervals, m)?)?;
+    m.add_function(wrap_pyfunction!(trace_window, m)?)?;
+    m.add_function(wrap_pyfunction!(reverse_complement, m)?)?;
+    m.add_function(wrap_pyfunction!(reverse_intervals, m)?)?;
+    m.add_function(wrap_pyfunction!(concatenate, m)?)?;
+    m.add_function(wrap_pyfunction!(simulate, m)?)?;
+    m.add_function(wrap_pyfunction!(window_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(sweep, m)?)?;
     Ok(())
 }
@@ -1,4 +1,50 @@
-from pydustmasker import DustMasker
+import _thread
+import array
+import asyncio
+import gzip
+import io
+import random
+import struct
+import threading
+import time
+import warnings
+
+import pytest
+
+from pydustmasker import (
+    CancelToken,
+    DustMasker,
+    IndexedFasta,
+    MaskingSummary,
+    StreamingDustMasker,
+    analyze,
+    annotation,
+    cache_info,
+    calibrate,
+    clear_cache,
+    concatenate,
+    convert_intervals,
+    count_masked_bases,
+    errors,
+    exceeds_masked_fraction,
+    fasta,
+    intervals_from_2bit,
+    mask_async,
+    mask_bulk,
+    mask_for_gene_finding,
+    output,
+    perfect_intervals,
+    plotting,
+    reverse_complement,
+    reverse_intervals,
+    set_cache_size,
+    simulate,
+    sweep,
+    trace_window,
+    window_metrics,
+    write_bed,
+    write_intervals,
+)
 
 
 def test_dust_masker_creation():
@@ -10,6 +56,27 @@ def test_dust_masker_creation():
     assert isinstance(masker.intervals, list)
 
 
+def test_sequence_is_not_copied():
+    sequence = "TACCCCCCCGCGTTTTTTT"
+    masker = DustMasker(sequence)
+    assert masker.sequence is sequence
+
+
+def test_interval_array():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTT", window_size=64, score_threshold=20)
+    assert masker.interval_array.format == "I"
+    assert masker.interval_array.readonly
+    assert masker.interval_array.tolist() == [2, 9, 12, 19]
+
+
+def test_mask_array():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTT", window_size=64, score_threshold=20)
+    mask_array = masker.mask_array
+    del masker
+    assert mask_array.format == "?"
+    assert mask_array.tolist() == [False] * 2 + [True] * 7 + [False] * 3 + [True] * 7
+
+
 def test_n_masked_bases():
     masker = DustMasker("TACCCCCCCGCGTTTTTTT", window_size=64, score_threshold=20)
     assert masker.n_masked_bases == 14
@@ -36,7 +103,1133 @@ def test_window_size():
     assert masker.intervals == []
 
 
+def test_large_window_size():
+    sequence = "ACGTACGTAAAAAAAAAAAAAAAA"
+    expected = DustMasker(sequence, window_size=len(sequence)).intervals
+    for window_size in [10**8, 2**40, 2**63 + 1]:
+        for threads in [1, 4]:
+            masker = DustMasker(sequence, window_size=window_size, threads=threads)
+            assert masker.intervals == expected
+
+
 def test_score_threshold():
     masker = DustMasker("TACCCCCCCGCGTTTTTTT", window_size=64, score_threshold=128)
     assert masker.score_threshold == 128
     assert masker.intervals == []
+
+
+def test_threads():
+    rng = random.Random(42)
+    sequence = "".join(
+        rng.choice(["A", "C", "G", "T", "CA" * 10])
+        if rng.random() < 0.01
+        else rng.choice("ACGT")
+        for _ in range(200_000)
+    )
+    masker = DustMasker(sequence, threads=4)
+    assert masker.intervals == DustMasker(sequence).intervals
+
+
+def test_spill_threshold():
+    sequence = "ACGTACGTTTTTTTTTTTTACGATCGATCGCCCCCCCCCCCCAGCTAGTCGAAAAAAAAAAAAGCAT" * 50
+    expected = DustMasker(sequence)
+    for spill_threshold in [0, 10, 1_000]:
+        masker = DustMasker(sequence, spill_threshold=spill_threshold)
+        assert masker.intervals == expected.intervals
+        assert masker.interval_array.tolist() == expected.interval_array.tolist()
+        assert masker.mask() == expected.mask()
+        edited = masker.edit(100, 110, "")
+        assert edited.intervals == expected.edit(100, 110, "").intervals
+
+
+def test_edit():
+    rng = random.Random(42)
+    sequence = "".join(
+        rng.choice(["A", "C", "G", "T", "N", "CA" * 10])
+        if rng.random() < 0.02
+        else rng.choice("ACGT")
+        for _ in range(20_000)
+    )
+    masker = DustMasker(sequence)
+    for start, end, replacement in [
+        (10_000, 10_010, "TTTTTTTTTTTTTTTTTTTT"),
+        (5_000, 5_000, "ACACACACACACACACACACAC"),
+        (12_000, 12_500, ""),
+        (0, 3, "NNN"),
+        (len(sequence) - 5, len(sequence), "GGGGGGGGGG"),
+    ]:
+        edited = masker.edit(start, end, replacement)
+        expected = DustMasker(sequence[:start] + replacement + sequence[end:])
+        assert edited.sequence == expected.sequence
+        assert edited.intervals == expected.intervals
+
+
+def test_streaming():
+    rng = random.Random(42)
+    sequence = "".join(
+        rng.choice(["A", "C", "G", "T", "N", "CA" * 10])
+        if rng.random() < 0.02
+        else rng.choice("ACGT")
+        for _ in range(20_000)
+    )
+    streaming_masker = StreamingDustMasker(window_size=32)
+    intervals = []
+    position = 0
+    while position < len(sequence):
+        chunk = sequence[position : position + rng.randint(1, 1_000)]
+        intervals.extend(streaming_masker.feed(chunk))
+        position += len(chunk)
+    intervals.extend(streaming_masker.finish())
+    assert streaming_masker.n_bases == len(sequence)
+    assert intervals == DustMasker(sequence, window_size=32).intervals
+
+
+def test_intervals_from_2bit():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCTNNNNACACACACACAC"
+    codes = {"T": 0, "C": 1, "A": 2, "G": 3, "N": 0}
+    packed = bytearray((len(sequence) + 3) // 4)
+    for i, base in enumerate(sequence):
+        packed[i // 4] |= codes[base] << (6 - 2 * (i % 4))
+    intervals = intervals_from_2bit(
+        bytes(packed), len(sequence), n_blocks=[(32, 36)], score_threshold=10
+    )
+    assert intervals == DustMasker(sequence, score_threshold=10).intervals
+    with pytest.raises(ValueError):
+        intervals_from_2bit(bytes(packed[:-1]), len(sequence))
+
+
+def test_count_masked_bases():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    assert count_masked_bases(sequence, score_threshold=10) == 17
+    assert count_masked_bases(sequence, score_threshold=10, bin_size=10) == [8, 2, 7, 0]
+
+
+def test_exceeds_masked_fraction():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    # 17 of the 32 bases are masked
+    assert exceeds_masked_fraction(sequence, 0.5, score_threshold=10)
+    assert not exceeds_masked_fraction(sequence, 17 / 32, score_threshold=10)
+    assert exceeds_masked_fraction("ACGT" + "A" * 10_000, 0.9)
+    assert not exceeds_masked_fraction(sequence, 1)
+
+
+def test_alphabet():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    dna = ["Aa", "Cc", "Gg", "Tt"]
+    masker = DustMasker(sequence, score_threshold=10, alphabet=dna)
+    assert masker.intervals == DustMasker(sequence, score_threshold=10).intervals
+    # Methylated cytosines are ambiguous by default
+    methylated = "ACGTMMMMMMMMMMMMMMMMMMACGT"
+    assert DustMasker(methylated).intervals == []
+    masker = DustMasker(methylated, alphabet=[*dna, "M"])
+    assert masker.intervals == [(4, 22)]
+    assert masker.edit(4, 10, "").intervals == [(4, 16)]
+    with pytest.raises(ValueError):
+        DustMasker(sequence, alphabet=["A", "C", "G", "T", "M", "R", "Y"])
+    with pytest.raises(ValueError):
+        DustMasker(sequence, alphabet=["Aa", "Ca"])
+
+
+def test_kmer_size():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    masker = DustMasker(sequence, score_threshold=10, kmer_size=3)
+    assert masker.intervals == [(2, 12), (23, 30)]
+    # Dinucleotides extend the TA repeat over the following TATG
+    masker = DustMasker(sequence, score_threshold=10, kmer_size=2)
+    assert masker.kmer_size == 2
+    assert masker.intervals == [(2, 16), (23, 30)]
+    assert masker.edit(0, 0, "").intervals == masker.intervals
+    for kmer_size in (1, 6):
+        with pytest.raises(ValueError):
+            DustMasker(sequence, kmer_size=kmer_size)
+    with pytest.raises(ValueError):
+        DustMasker(sequence, window_size=3, kmer_size=4)
+
+
+def test_level():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    masker = DustMasker(sequence, level=1.0)
+    assert masker.score_threshold == 10
+    assert masker.level == 1.0
+    assert masker.intervals == DustMasker(sequence, score_threshold=10).intervals
+    assert DustMasker(sequence, level=2.3).score_threshold == 23
+    assert StreamingDustMasker(level=1.5).score_threshold == 15
+    for level in (2.05, -1.0, float("nan")):
+        with pytest.raises(ValueError):
+            DustMasker(sequence, level=level)
+
+
+def test_reverse_complement():
+    assert reverse_complement("ACGTacgtNRYu-") == "-aRYNacgtACGT"
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    masker = DustMasker(sequence, score_threshold=10)
+    assert masker.minus_strand_intervals == [(2, 9), (20, 30)]
+    assert reverse_intervals(masker.intervals, len(sequence)) == [(2, 9), (20, 30)]
+    with pytest.raises(ValueError):
+        reverse_intervals([(0, 40)], len(sequence))
+
+
+def test_composition():
+    sequence = "ACGTNNNNacgtnnGGCC" + "AT" * 500
+    assert DustMasker(sequence).composition is None
+    for threads in (1, 2):
+        masker = DustMasker(sequence, threads=threads, composition=True)
+        assert masker.composition == {
+            "length": 1018,
+            "gc_count": 8,
+            "at_count": 1004,
+            "n_count": 6,
+            "n_runs": 2,
+            "longest_n_run": 4,
+            "gc_content": 8 / 1012,
+        }
+    masker = DustMasker(sequence, kmer_size=2, composition=True)
+    assert masker.composition["n_runs"] == 2
+    assert masker.edit(4, 8, "").composition["n_count"] == 2
+
+
+def test_kmers():
+    masker = DustMasker("ACGTNacgTTTTTTTTTTTTTTTTTTTTTTGACT")
+    assert masker.intervals == [(10, 32)]
+    assert list(masker.kmers(3)) == ["ACG", "CGT", "acg", "cgT", "gTT"]
+    assert list(masker.kmers(3, as_int=True)) == [6, 27, 6, 27, 47]
+    assert list(masker.kmers(6)) == []
+    with pytest.raises(ValueError):
+        masker.kmers(33)
+
+
+def test_repr_html():
+    html = DustMasker("<ACGT>TACCCCCCCGCGTTTTTTT")._repr_html_()
+    assert "&lt;ACGT&gt;TACC<mark>CCCCCGC</mark>GTT<mark>TTTTT</mark>" in html
+    assert "12 (48.00%)" in html
+    html = DustMasker("ACGT" * 1000)._repr_html_()
+    assert "First 1000 of 4000 bases" in html
+
+
+def test_plot_density():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTT")
+    assert plotting.masked_fractions(masker, bin_size=8) == [6 / 8, 5 / 8, 1]
+    # The bins are made of bytes, like the positions of the intervals
+    non_ascii = DustMasker("é" * 10 + "A" * 30)
+    assert plotting.masked_fractions(non_ascii, bin_size=25) == [5 / 25, 1]
+    with pytest.raises(ValueError):
+        plotting.masked_fractions(masker, bin_size=0)
+    try:
+        import matplotlib  # noqa: F401
+    except ImportError:
+        with pytest.raises(ImportError):
+            masker.plot_density()
+    else:
+        figure = masker.plot_density(bin_size=8)
+        assert len(figure.axes) == 1
+
+
+def test_interval_set_operations():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTT")
+    assert masker.intervals == [(2, 9), (12, 19)]
+    repeats = [(15, 19), (0, 4), (3, 5)]
+    assert masker.union(repeats).intervals == [(0, 9), (12, 19)]
+    assert masker.intersection(repeats).intervals == [(2, 5), (15, 19)]
+    assert masker.difference(repeats).intervals == [(5, 9), (12, 15)]
+    other = DustMasker("TACCCCCCCGCGTTTTTTT", score_threshold=40)
+    assert masker.union(other).intervals == masker.intervals
+    assert masker.difference(other).n_masked_bases == 14 - other.n_masked_bases
+    edited = masker.union(repeats).edit(0, 0, "")
+    assert edited.intervals == masker.intervals
+    with pytest.raises(ValueError):
+        masker.union(DustMasker("ACGTACGT"))
+    with pytest.raises(ValueError):
+        masker.union([(0, 20)])
+
+
+def test_compare():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTT")
+    comparison = masker.compare([(15, 19), (0, 4), (3, 5)])
+    assert comparison == {
+        "n_only_self": 7,
+        "n_only_other": 2,
+        "n_shared": 7,
+        "jaccard": 7 / 16,
+        "only_self": [(5, 9), (12, 15)],
+        "only_other": [(0, 2)],
+        "shared": [(2, 5), (15, 19)],
+    }
+    assert masker.compare(masker)["jaccard"] == 1
+    assert masker.compare([])["n_only_self"] == masker.n_masked_bases
+
+
+def test_evaluate():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTT")
+    evaluation = masker.evaluate([(0, 4), (15, 19), (10, 11)])
+    assert evaluation["base"]["precision"] == 6 / 14
+    assert evaluation["base"]["recall"] == 6 / 9
+    assert evaluation["interval"] == {"precision": 1, "recall": 2 / 3, "f1": 0.8}
+    assert masker.evaluate(masker)["base"]["f1"] == 1
+    assert masker.evaluate([])["base"] == {"precision": 0, "recall": 0, "f1": 0}
+
+
+def test_simulate():
+    sequence, planted = simulate(10_000, n_regions=5, seed=42)
+    assert len(sequence) == 10_000
+    assert set(sequence) == set("ACGT")
+    assert len(planted) == 5
+    assert all(20 <= end - start <= 200 for start, end in planted)
+    assert simulate(10_000, n_regions=5, seed=42) == (sequence, planted)
+    homopolymers, homopolymer_regions = simulate(
+        1_000, n_regions=1, unit_length=(1, 1), seed=1
+    )
+    start, end = homopolymer_regions[0]
+    assert len(set(homopolymers[start:end])) == 1
+    evaluation = DustMasker(sequence).evaluate(planted)
+    assert evaluation["interval"]["recall"] > 0.5
+    with pytest.raises(ValueError):
+        simulate(100, n_regions=1, region_length=(20, 200))
+    with pytest.raises(ValueError):
+        simulate(1_000, unit_length=(0, 6))
+    with pytest.raises(ValueError):
+        simulate(10_000, mutation_rate=1.5)
+
+def test_perfect_intervals():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    intervals = perfect_intervals(sequence, score_threshold=10)
+    for start, end, score, length in intervals:
+        assert 10 * score > 10 * length
+        assert start < end
+    # The regions are the union of the overlapping perfect intervals
+    merged = []
+    for start, end, _, _ in intervals:
+        if merged and start <= merged[-1][1]:
+            merged[-1] = (merged[-1][0], max(merged[-1][1], end))
+        else:
+            merged.append((start, end))
+    assert merged == DustMasker(sequence, score_threshold=10).intervals
+
+def test_trace_window():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT"
+    states = trace_window(sequence, window_size=16, score_threshold=10)
+    # No state is reported for the first two bases
+    assert [state[0] for state in states] == list(range(2, len(sequence)))
+    assert all(n_triplets <= 14 for _, n_triplets, _, _, _, _ in states)
+    assert any(triggered for *_, triggered in states)
+    reported = []
+    trace_window(sequence, window_size=16, score_threshold=10, callback=reported.append)
+    assert reported == states
+
+def test_errors():
+    with pytest.raises(errors.SequenceTooShortError) as error:
+        DustMasker("ACG")
+    assert error.value.length == 3
+    with pytest.raises(errors.InvalidWindowError) as error:
+        StreamingDustMasker(window_size=2)
+    assert error.value.window_size == 2
+    with pytest.raises(errors.InvalidCharacterError) as error:
+        DustMasker("ACGTÅACGT").edit(5, 6, "")
+    assert (error.value.position, error.value.char) == (4, "Å")
+    # The exceptions can also be caught as a ValueError
+    with pytest.raises(ValueError):
+        count_masked_bases("ACGT", window_size=0)
+
+def test_cache():
+    set_cache_size(2)
+    try:
+        sequences = ["CGTATATATATAGTATGCGTACTGGGGGGGCT", "TACCCCCCCGCGTTTTTTT"]
+        expected = [DustMasker(sequence).intervals for sequence in sequences]
+        # Build equal strings that are distinct objects
+        for sequence, intervals in zip(sequences, expected):
+            assert DustMasker("".join(list(sequence))).intervals == intervals
+        assert DustMasker(sequences[0], window_size=32).intervals == expected[0]
+        assert cache_info() == {"hits": 2, "misses": 3, "max_entries": 2, "n_entries": 2}
+        clear_cache()
+        assert cache_info() == {"hits": 0, "misses": 0, "max_entries": 2, "n_entries": 0}
+    finally:
+        set_cache_size(0)
+        clear_cache()
+
+
+def test_window_metrics():
+    metrics = window_metrics("AAAAAAAAACGTACGTNN", window_size=8, step=8)
+    assert list(metrics) == ["dust", "entropy", "gc"]
+    assert metrics["dust"].format == "d"
+    assert metrics["dust"].tolist() == [3.0, 0.4]
+    assert metrics["entropy"].tolist() == [0.0, 2.0]
+    assert metrics["gc"].tolist() == [0.0, 0.5]
+    sliding = window_metrics("ACGTNACGT", window_size=4, metrics=["gc"])
+    assert list(sliding) == ["gc"]
+    assert sliding["gc"].tolist() == [0.5, 2 / 3, 1 / 3, 1 / 3, 2 / 3, 0.5]
+    assert len(window_metrics("ACG", window_size=4)["dust"]) == 0
+    with pytest.raises(ValueError):
+        window_metrics("ACGT", step=0)
+    with pytest.raises(ValueError):
+        window_metrics("ACGT", metrics=["complexity"])
+    with pytest.raises(errors.InvalidWindowError):
+        window_metrics("ACGT", window_size=2)
+
+
+def test_regions():
+    masker = DustMasker("TACCCCCCCGCGTTTTTTTAGCTAGTCACACACACACACATGCA")
+    regions = masker.regions()
+    assert [tuple(region) for region in regions] == masker.intervals
+    assert [(region.start, region.end) for region in regions] == masker.intervals
+    assert [region.length for region in regions] == [7, 7, 14]
+    assert [region.repeat_unit for region in regions] == ["C", "T", "CA"]
+    assert regions[0].score == 2.5
+    assert repr(regions[0]) == "Region(start: 2, end: 9, score: 2.50, repeat_unit: 'C')"
+    assert regions == masker.regions()
+    region = DustMasker("AGAAAGAGGAAAGGAGAAAAGGAGAAAGGAAGAGAAAG").regions()[0]
+    assert region.repeat_unit is None
+
+
+def test_bisulfite():
+    sequence, planted = simulate(20_000, n_regions=20, seed=7)
+    converted = sequence.replace("C", "T")
+
+    def masked_fraction(masker):
+        return sum(end - start for start, end in masker.intervals) / 20_000
+
+    assert masked_fraction(DustMasker(converted)) > 0.3
+    masker = DustMasker(converted, bisulfite="CT")
+    assert masker.score_threshold == 20
+    assert masked_fraction(masker) < 0.15
+    assert masker.evaluate(planted)["interval"]["recall"] > 0.5
+    # Partially converted Cs are compared with the Ts they can be read as
+    assert (
+        DustMasker("ACGTCTCTTTCTTCCTTTTCTCTCTTCCTTTCACGT", bisulfite="CT").intervals
+        == DustMasker("ACGTTTTTTTTTTTTTTTTTTTTTTTTTTTTTACGT", bisulfite="CT").intervals
+    )
+    reverse = reverse_complement(converted)
+    assert masked_fraction(DustMasker(reverse, bisulfite="GA")) == masked_fraction(
+        masker
+    )
+    with pytest.raises(ValueError):
+        DustMasker(sequence, bisulfite="CG")
+    with pytest.raises(ValueError):
+        DustMasker(sequence, bisulfite="CT", alphabet=["Aa", "Cc", "Gg", "Tt"])
+
+
+def test_annotation(tmp_path):
+    gff = tmp_path / "annotation.gff"
+    gff.write_text(
+        "##gff-version 3\n"
+        "chr1\t.\tgene\t1\t10\t.\t+\t.\tID=gene1;Name=abc%3B1\n"
+        "chr1\t.\texon\t3\t6\t.\t+\t.\tParent=gene1\n"
+        "chr1\t.\tgene\t15\t24\t.\t-\t.\tID=gene2\n"
+        "chr2\t.\tgene\t1\t4\t.\t+\t.\tID=gene3\n"
+    )
+    features = annotation.read_features(gff)
+    assert [feature.type for feature in features] == ["gene", "exon", "gene", "gene"]
+    assert (features[0].start, features[0].end) == (0, 10)
+    assert features[0].name == "abc;1"
+    genes = annotation.read_features(str(gff), types=["gene"])
+    assert [gene.name for gene in genes] == ["abc;1", "gene2", "gene3"]
+    masker = DustMasker("TACCCCCCCGCGTTTTTTTACGTA")
+    assert masker.intervals == [(2, 9), (12, 19)]
+    fractions = annotation.feature_masked_fractions({"chr1": masker}, genes)
+    assert [(gene.name, fraction) for gene, fraction in fractions] == [
+        ("abc;1", 0.7),
+        ("gene2", 0.5),
+    ]
+    regions = annotation.region_features({"chr1": masker}, features)
+    assert [
+        (seqid, start, end, [feature.name for feature in overlapping])
+        for seqid, start, end, overlapping in regions
+    ] == [("chr1", 2, 9, ["abc;1", None]), ("chr1", 12, 19, ["gene2"])]
+    gtf = tmp_path / "annotation.gtf"
+    gtf.write_text(
+        'chr1\tsource\tgene\t3\t12\t.\t+\t.\tgene_id "g1"; gene_name "ABC";\n'
+    )
+    (gene,) = annotation.read_features(gtf)
+    assert gene.attributes == {"gene_id": "g1", "gene_name": "ABC"}
+    assert gene.name == "ABC"
+    with pytest.raises(errors.FileFormatError):
+        annotation.read_features(io.StringIO("chr1\tgene\t1\t10\n"))
+
+
+def test_window_size_warning():
+    sequence = "TACCCCCCCGCGTTTTTTT"
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        masker = DustMasker(sequence, window_size=64)
+        DustMasker(sequence * 4, window_size=64).edit(0, 19, "")
+        DustMasker(sequence, window_size=19)
+    assert [warning.category for warning in caught] == [errors.WindowSizeWarning] * 2
+    assert masker.effective_window_size == 19
+    assert masker.intervals == DustMasker(sequence, window_size=19).intervals
+    assert DustMasker(sequence * 4).effective_window_size == 64
+
+
+def test_sweep():
+    sequences, _ = simulate(5_000, n_regions=10, mutation_rate=0.1, seed=3)
+    records = [sequences[:2_000], sequences[2_000:] + "NNNNACGT"]
+    table = sweep(records, window_sizes=[16, 64], score_thresholds=[10, 20, 40])
+    assert table["window_size"] == [16, 16, 16, 64, 64, 64]
+    assert table["score_threshold"] == [10, 20, 40, 10, 20, 40]
+    for row, (window_size, score_threshold) in enumerate(
+        zip(table["window_size"], table["score_threshold"])
+    ):
+        maskers = [
+            DustMasker(record, window_size=window_size, score_threshold=score_threshold)
+            for record in records
+        ]
+        assert table["n_intervals"][row] == sum(len(m.intervals) for m in maskers)
+        assert table["n_masked_bases"][row] == sum(m.n_masked_bases for m in maskers)
+        assert table["masked_fraction"][row] == table["n_masked_bases"][row] / 5_008
+    assert sweep("ACGTTTTTTTTTTTTT") == {
+        "window_size": [64],
+        "score_threshold": [20],
+        "n_intervals": [1],
+        "n_masked_bases": [13],
+        "masked_fraction": [13 / 16],
+    }
+    with pytest.raises(errors.InvalidWindowError):
+        sweep(records, window_sizes=[2])
+
+
+def test_calibrate():
+    sequence, _ = simulate(20_000, n_regions=20, mutation_rate=0.15, seed=5)
+    masker = calibrate(sequence, 0.03)
+    fraction = masker.n_masked_bases / len(sequence)
+    table = sweep(sequence, score_thresholds=list(range(321)))
+    closest = min(abs(f - 0.03) for f in table["masked_fraction"])
+    assert abs(fraction - 0.03) == closest
+    assert masker.intervals == DustMasker(
+        sequence, score_threshold=masker.score_threshold
+    ).intervals
+    assert calibrate(sequence, 0.0).n_masked_bases == 0
+    assert calibrate(sequence, 1.0).score_threshold == 0
+    with pytest.raises(ValueError):
+        calibrate(sequence, 1.5)
+
+
+def test_analyze():
+    genome, _ = simulate(200_000, n_regions=50, seed=11)
+    report = analyze([genome[:150_000], genome[150_000:]], sample_length=1_000)
+    assert report["n_bases"] == 200_000
+    assert report["n_sampled_bases"] == 100_000
+    assert abs(report["gc_content"] - 0.5) < 0.02
+    assert report["ambiguous_fraction"] == 0
+    assert list(report["masked_fractions"]) == [16, 32, 64, 128]
+    assert (report["window_size"], report["score_threshold"]) == (64, 20)
+    assert report["masked_fraction"] == report["masked_fractions"][64]
+    assert "default parameters" in report["rationale"]
+    rng = random.Random(11)
+    at_rich = "".join(rng.choices("ACGT", weights=[4, 1, 1, 4], k=50_000))
+    report = analyze(at_rich)
+    assert report["n_sampled_bases"] == 50_000
+    assert report["score_threshold"] == 50
+    assert report["masked_fraction"] < report["masked_fractions"][64]
+    assert "level 5.0" in report["rationale"]
+    with pytest.raises(ValueError):
+        analyze("NNNNNNNN")
+
+
+def test_masking_summary():
+    records = {
+        "low": "ACGTAGCTAGTCGATCGATG" * 5,
+        "high": "ACGT" + "A" * 40 + "ACGT",
+        "empty": "",
+        "mid": "ACGATCGTAGCTAGCATCGA" + "C" * 30 + "TGCATGCTAGCTAGTCGATC",
+    }
+    summary = MaskingSummary(top_n=2)
+    for name, sequence in records.items():
+        if sequence:
+            summary.add(name, DustMasker(sequence))
+    maskers = [DustMasker(sequence) for sequence in records.values() if sequence]
+    assert summary.n_records == 3
+    assert summary.n_bases == sum(len(sequence) for sequence in records.values())
+    assert summary.n_masked_bases == sum(m.n_masked_bases for m in maskers)
+    assert summary.n_intervals == sum(len(m.intervals) for m in maskers)
+    assert summary.masked_fraction == summary.n_masked_bases / summary.n_bases
+    assert summary.masked_fractions == [
+        m.n_masked_bases / len(m.sequence) for m in maskers
+    ]
+    assert [name for name, *_ in summary.most_masked] == ["high", "mid"]
+    name, length, n_masked_bases, fraction = summary.most_masked[0]
+    assert (length, n_masked_bases) == (48, maskers[1].n_masked_bases)
+    assert fraction == n_masked_bases / length
+    fractions = sorted(summary.masked_fractions)
+    assert summary.quantiles([0, 0.5, 1]) == fractions
+    assert summary.quantiles([0.25]) == [(fractions[0] + fractions[1]) / 2]
+    with pytest.raises(ValueError):
+        summary.quantiles([1.5])
+    with pytest.raises(ValueError):
+        MaskingSummary().quantiles([0.5])
+    assert repr(MaskingSummary()).startswith("MaskingSummary(n_records: 0")
+
+
+def test_callback():
+    sequence = "ACGTAGCTAGTCGATCGATG".join(base * 30 for base in "ACGTACGTAC")
+    batches = []
+    masker = DustMasker(sequence, callback=batches.append, batch_size=3)
+    assert [len(batch) for batch in batches] == [3, 3, 3, 1]
+    assert [interval for batch in batches for interval in batch] == masker.intervals
+    batches = []
+    DustMasker(sequence, threads=2, composition=True, callback=batches.append)
+    assert batches == [masker.intervals]
+    calls = []
+
+    def failing(batch):
+        calls.append(batch)
+        raise RuntimeError("sink is full")
+
+    with pytest.raises(RuntimeError):
+        DustMasker(sequence, callback=failing, batch_size=1)
+    assert len(calls) == 1
+    with pytest.raises(ValueError):
+        DustMasker(sequence, callback=batches.append, batch_size=0)
+    # The callback can mask other sequences while the scan is running
+    inner = []
+    DustMasker(
+        sequence,
+        callback=lambda batch: inner.append(DustMasker("TACCCCCCCGCGTTTTTTT")),
+        batch_size=1,
+    )
+    assert len(inner) == len(masker.intervals)
+    assert all(m.intervals == inner[0].intervals for m in inner)
+
+
+def test_mask_async():
+    sequence = "ACGTAGCTAGTCGATCGATG".join(base * 30 for base in "ACGTACGTAC")
+
+    async def mask_all():
+        return await asyncio.gather(
+            mask_async(sequence),
+            mask_async(sequence, window_size=32, score_threshold=30),
+        )
+
+    default, custom = asyncio.run(mask_all())
+    assert default.intervals == DustMasker(sequence).intervals
+    assert custom.intervals == DustMasker(sequence, 32, 30).intervals
+    with pytest.raises(ValueError):
+        asyncio.run(mask_async("ACG"))
+
+
+def test_memory_usage():
+    sequence = "ACGTAGCTAGTCGATCGATG".join(base * 30 for base in "ACGTACGTAC")
+    masker = DustMasker(sequence)
+    usage = masker.memory_usage()
+    assert list(usage) == [
+        "sequence",
+        "intervals",
+        "mapped_intervals",
+        "buffers",
+        "total",
+    ]
+    assert usage["sequence"] >= len(sequence)
+    assert usage["intervals"] >= 8 * len(masker.intervals)
+    assert usage["mapped_intervals"] == 0
+    assert usage["total"] == sum(usage.values()) - usage["total"]
+    spilled = DustMasker(sequence, spill_threshold=1).memory_usage()
+    assert spilled["intervals"] == 0
+    assert spilled["mapped_intervals"] >= 16 * len(masker.intervals)
+    streaming_masker = StreamingDustMasker()
+    usage = streaming_masker.memory_usage()
+    assert usage["sequence"] == usage["intervals"] == 0
+    streaming_masker.feed(sequence * 10)
+    assert streaming_masker.memory_usage()["buffers"] < 2 * usage["buffers"] + 4096
+
+
+def test_cancel():
+    sequence = "ACGTAGCTAGTCGATCGATG".join(base * 30 for base in "ACGTACGTAC")
+    token = CancelToken()
+    assert repr(token) == "CancelToken(cancelled: False)"
+    assert DustMasker(sequence, cancel=token).intervals == DustMasker(sequence).intervals
+    token.cancel()
+    assert token.cancelled
+    for kwargs in [{}, {"threads": 2}, {"kmer_size": 4}, {"composition": True}]:
+        with pytest.raises(errors.ScanCancelledError):
+            DustMasker(sequence, cancel=token, **kwargs)
+    # Long scans are stopped while they run, from another thread
+    long_sequence = "".join(random.Random(3).choices("ACGT", k=20_000_000))
+    for threads in [1, 4]:
+        token = CancelToken()
+        timer = threading.Timer(0.05, token.cancel)
+        timer.start()
+        started = time.perf_counter()
+        with pytest.raises(errors.ScanCancelledError):
+            DustMasker(long_sequence, threads=threads, cancel=token)
+        assert time.perf_counter() - started < 1
+        timer.join()
+    timer = threading.Timer(0.05, _thread.interrupt_main)
+    timer.start()
+    with pytest.raises(KeyboardInterrupt):
+        DustMasker(long_sequence)
+    timer.join()
+
+
+def test_progress():
+    sequence = "".join(random.Random(5).choices("ACGT", k=5_000_000))
+    sequence = sequence[:2_500_000] + "A" * 100 + sequence[2_500_000:]
+    masker = DustMasker(sequence)
+    reports = []
+    DustMasker(
+        sequence, progress=lambda *report: reports.append(report), progress_interval=1
+    )
+    positions = [position for position, _ in reports]
+    assert positions == [i * 2**20 for i in range(5)] + [len(sequence)]
+    assert reports[-1] == (len(sequence), len(masker.intervals))
+    assert all(a[1] <= b[1] for a, b in zip(reports, reports[1:]))
+    reports = []
+    DustMasker(sequence, progress=lambda *report: reports.append(report))
+    assert reports == [(0, 0), (len(sequence), len(masker.intervals))]
+    reports = []
+    DustMasker(sequence, threads=4, progress=lambda *report: reports.append(report))
+    assert reports[-1] == (len(sequence), len(masker.intervals))
+    assert all(position <= len(sequence) for position, _ in reports)
+
+    def failing(position, n_intervals):
+        if position > 0:
+            raise RuntimeError("window closed")
+
+    with pytest.raises(RuntimeError):
+        DustMasker(sequence, progress=failing, progress_interval=1)
+    # The progress callback can mask other sequences while the scan is running
+    for threads in [1, 4]:
+        inner = []
+        DustMasker(
+            sequence,
+            threads=threads,
+            progress=lambda *_: inner.append(DustMasker("TACCCCCCCGCGTTTTTTT")),
+            progress_interval=1,
+        )
+        assert len(inner) >= 2
+        assert all(m.intervals == inner[0].intervals for m in inner)
+    with pytest.raises(ValueError):
+        DustMasker(sequence, progress=failing, progress_interval=0)
+
+
+def test_subclass():
+    rng = random.Random(42)
+    sequence = "".join(rng.choice("ACGT") for _ in range(100)) + "A" * 40
+    sequence += "".join(rng.choice("ACGT") for _ in range(100)) + "T" * 12
+
+    class LongRegionMasker(DustMasker):
+        def post_process_intervals(self, intervals):
+            return [(start, end) for start, end in intervals if end - start >= 20]
+
+    base = DustMasker(sequence, score_threshold=10)
+    masker = LongRegionMasker(sequence, score_threshold=10)
+    assert isinstance(masker, DustMasker)
+    assert masker.intervals == [
+        (start, end) for start, end in base.intervals if end - start >= 20
+    ]
+    assert masker.intervals != base.intervals
+    assert masker.n_masked_bases == sum(end - start for start, end in masker.intervals)
+    assert sum(base.islower() for base in masker.mask()) == masker.n_masked_bases
+
+    class LabelledMasker(DustMasker):
+        def __new__(cls, sequence, label, **kwargs):
+            return super().__new__(cls, sequence, **kwargs)
+
+        def __init__(self, sequence, label, **kwargs):
+            super().__init__(sequence, **kwargs)
+            self.label = label
+
+        def post_process_regions(self, regions):
+            return [(self.label, region.start, region.end) for region in regions]
+
+    masker = LabelledMasker(sequence, "chr1", window_size=64)
+    assert masker.label == "chr1"
+    assert masker.regions() == [("chr1", start, end) for start, end in masker.intervals]
+
+    class InvalidMasker(DustMasker):
+        def post_process_intervals(self, intervals):
+            return [(0, len(sequence) + 1)]
+
+    with pytest.raises(ValueError):
+        InvalidMasker(sequence)
+
+
+def test_slice():
+    rng = random.Random(42)
+    sequence = "".join(rng.choice("ACGT") for _ in range(100)) + "A" * 40
+    sequence += "".join(rng.choice("ACGT") for _ in range(100)) + "T" * 12
+    masker = DustMasker(sequence, score_threshold=10, composition=True)
+    assert len(masker.intervals) > 1
+    sliced = masker[110:245]
+    assert sliced.sequence == sequence[110:245]
+    assert sliced.intervals == [
+        (max(start, 110) - 110, min(end, 245) - 110)
+        for start, end in masker.intervals
+        if start < 245 and end > 110
+    ]
+    assert sliced.mask() == masker.mask()[110:245]
+    assert sliced.composition["length"] == 135
+    assert masker[:].intervals == masker.intervals
+    assert masker[-12:].intervals == [(0, 12)]
+    assert masker[50:50].intervals == []
+    assert masker[300:].sequence == ""
+    with pytest.raises(ValueError):
+        masker[::2]
+    with pytest.raises(TypeError):
+        masker[0]
+    with pytest.raises(errors.InvalidCharacterError):
+        DustMasker("ACGT" * 10 + "é" + "ACGT" * 10)[:41]
+
+
+def test_concatenate():
+    rng = random.Random(42)
+    contig1 = "".join(rng.choice("ACGT") for _ in range(100)) + "A" * 40
+    contig2 = "T" * 30 + "".join(rng.choice("ACGT") for _ in range(100))
+    masker1 = DustMasker(contig1)
+    masker2 = DustMasker(contig2)
+    masker = concatenate([masker1, masker2])
+    assert masker.sequence == contig1 + contig2
+    assert masker.intervals == [(100, 172)]
+    assert masker.mask() == masker1.mask() + masker2.mask()
+    masker = concatenate([masker1, masker2], offsets=[10, 160])
+    assert masker.sequence == "N" * 10 + contig1 + "N" * 10 + contig2
+    assert masker.intervals == [(110, 150), (160, 192)]
+    assert concatenate([masker1]).intervals == masker1.intervals
+    with pytest.raises(ValueError):
+        concatenate([])
+    with pytest.raises(ValueError):
+        concatenate([masker1, masker2], offsets=[0, 100])
+    with pytest.raises(ValueError):
+        concatenate([masker1, masker2], offsets=[0])
+    with pytest.raises(ValueError):
+        concatenate([masker1, DustMasker(contig2, window_size=32)])
+
+
+def test_threshold_overrides():
+    rng = random.Random(42)
+    sequence = "".join(rng.choice("ACGT") for _ in range(200)) + "ACGTTGCA" * 6
+    sequence += "".join(rng.choice("ACGT") for _ in range(200)) + "ACGTTGCA" * 6
+    sequence += "".join(rng.choice("ACGT") for _ in range(200))
+    default = DustMasker(sequence)
+    assert len(default.intervals) == 2
+    masker = DustMasker(sequence, threshold_overrides=[(0, 300, 40)])
+    assert masker.intervals == default.intervals[1:]
+    assert DustMasker(sequence, score_threshold=40).intervals == []
+    masker = DustMasker(sequence, threshold_overrides=[(300, len(sequence), 10)])
+    lenient = DustMasker(sequence, score_threshold=10)
+    assert masker.intervals[0] == default.intervals[0]
+    assert masker.intervals[1:] == [
+        interval for interval in lenient.intervals if interval[0] > 250
+    ]
+    masker = DustMasker(sequence, threshold_overrides=[(300, len(sequence), 20)])
+    assert masker.intervals == default.intervals
+    with pytest.raises(ValueError):
+        DustMasker(sequence, threshold_overrides=[(0, 300, 40), (200, 400, 10)])
+    with pytest.raises(ValueError):
+        DustMasker(sequence, threshold_overrides=[(0, len(sequence) + 1, 40)])
+    with pytest.raises(ValueError):
+        DustMasker(sequence, threshold_overrides=[(0, 300, 40)], kmer_size=4)
+
+
+def test_indexed_fasta(tmp_path):
+    rng = random.Random(42)
+    records = {
+        "chr1": "".join(rng.choice("ACGT") for _ in range(500)) + "A" * 40,
+        "chr2": "".join(rng.choice("ACGT") for _ in range(300)),
+    }
+    content = b""
+    fai = ""
+    for name, sequence in records.items():
+        content += f">{name}\n".encode()
+        lines = [sequence[i : i + 60] for i in range(0, len(sequence), 60)]
+        fai += f"{name}\t{len(sequence)}\t{len(content)}\t60\t61\n"
+        content += "".join(line + "\n" for line in lines).encode()
+    (tmp_path / "genome.fa").write_bytes(content)
+    (tmp_path / "genome.fa.fai").write_text(fai)
+    # bgzip files are series of gzip members, listed in the .gzi index
+    compressed = b""
+    blocks = []
+    for i in range(0, len(content), 100):
+        if i > 0:
+            blocks.append((len(compressed), i))
+        compressed += gzip.compress(content[i : i + 100])
+    (tmp_path / "genome.fa.gz").write_bytes(compressed)
+    (tmp_path / "genome.fa.gz.fai").write_text(fai)
+    gzi = struct.pack("<Q", len(blocks))
+    gzi += b"".join(struct.pack("<QQ", *block) for block in blocks)
+    (tmp_path / "genome.fa.gz.gzi").write_bytes(gzi)
+    for path in [tmp_path / "genome.fa", tmp_path / "genome.fa.gz"]:
+        with IndexedFasta(path) as fasta:
+            assert fasta.names == ["chr1", "chr2"]
+            assert "chr2" in fasta
+            assert fasta.fetch("chr1") == records["chr1"]
+            assert fasta.fetch("chr2", 55, 250) == records["chr2"][55:250]
+            assert fasta.fetch("chr2", 10, 10) == ""
+            masker = fasta.mask("chr1", 450)
+            assert masker.intervals == [
+                (start - 450, end - 450)
+                for start, end in DustMasker(records["chr1"]).intervals
+                if start >= 450
+            ]
+            with pytest.raises(KeyError):
+                fasta.fetch("chr3")
+            with pytest.raises(ValueError):
+                fasta.fetch("chr2", 0, 301)
+    (tmp_path / "genome.fa.gz.gzi").unlink()
+    with pytest.raises(FileNotFoundError):
+        IndexedFasta(tmp_path / "genome.fa.gz")
+    (tmp_path / "genome.fa.fai").write_text("chr1\t540\n")
+    with pytest.raises(errors.FileFormatError):
+        IndexedFasta(tmp_path / "genome.fa")
+
+
+def test_mask_records():
+    rng = random.Random(42)
+    records = {
+        "chr1": "".join(rng.choice("ACGT") for _ in range(100)) + "A" * 40,
+        "chr2": "T" * 30 + "".join(rng.choice("ACGT") for _ in range(100)),
+    }
+
+    # Stand-ins for the records of pyfastx (`seq` attribute) and pyfaidx
+    # (converted to strings)
+    class Sequence:
+        def __init__(self, seq):
+            self.seq = seq
+
+    class FastaRecord:
+        def __init__(self, seq):
+            self._seq = seq
+
+        def __str__(self):
+            return self._seq
+
+    class Fasta:
+        def __init__(self, record_type):
+            self.record_type = record_type
+            self.fetched = []
+
+        def keys(self):
+            return iter(records)
+
+        def __getitem__(self, name):
+            self.fetched.append(name)
+            return self.record_type(records[name])
+
+    expected = {
+        name: DustMasker(sequence).intervals for name, sequence in records.items()
+    }
+    for record_type in [Sequence, FastaRecord]:
+        indexed = Fasta(record_type)
+        masked = fasta.mask_records(indexed, window_size=64)
+        name, masker = next(masked)
+        assert indexed.fetched == ["chr1"]
+        assert (name, masker.intervals) == ("chr1", expected["chr1"])
+        assert [(name, masker.intervals) for name, masker in masked] == [
+            ("chr2", expected["chr2"])
+        ]
+        masker = fasta.mask_record(record_type(records["chr2"]))
+        assert masker.intervals == expected["chr2"]
+    masked = dict(fasta.mask_records(records, names=["chr2"]))
+    assert masked["chr2"].sequence == records["chr2"]
+    with pytest.raises(KeyError):
+        list(fasta.mask_records(records, names=["chr3"]))
+
+
+def test_mask_for_gene_finding():
+    rng = random.Random(42)
+    contigs = [
+        "".join(rng.choice("ACGT") for _ in range(length)) + "A" * 40
+        for length in range(100, 2000, 100)
+    ]
+    masked = mask_for_gene_finding(contigs, workers=4, window_size=32)
+    assert masked == [
+        DustMasker(contig, window_size=32).mask(hard=True).encode()
+        for contig in contigs
+    ]
+    assert all(contig.endswith(b"N" * 40) for contig in masked)
+    assert mask_for_gene_finding([]) == []
+    with pytest.raises(errors.SequenceTooShortError):
+        mask_for_gene_finding(["ACGT" * 10, "ACG"])
+
+
+def test_mask_bulk():
+    rng = random.Random(42)
+    sequences = [
+        "".join(rng.choice("ACGT") for _ in range(rng.randrange(50, 300)))
+        + "A" * rng.randrange(0, 40)
+        for _ in range(200)
+    ]
+    expected = [DustMasker(sequence).intervals for sequence in sequences]
+
+    def regions(intervals, offsets):
+        pairs = list(zip(intervals[::2], intervals[1::2]))
+        return [pairs[offsets[i] : offsets[i + 1]] for i in range(len(offsets) - 1)]
+
+    intervals, offsets = mask_bulk(sequences, threads=4)
+    assert offsets.format == "Q" and len(offsets) == len(sequences) + 1
+    assert regions(intervals, offsets) == expected
+    intervals, offsets = mask_bulk([sequence.encode() for sequence in sequences])
+    assert regions(intervals, offsets) == expected
+    packed = "".join(sequences).encode()
+    starts = [0]
+    for sequence in sequences:
+        starts.append(starts[-1] + len(sequence))
+    for typecode in ["i", "q", "L"]:
+        intervals, offsets = mask_bulk(packed, offsets=array.array(typecode, starts))
+        assert regions(intervals, offsets) == expected
+    intervals, offsets = mask_bulk(packed, offsets=starts[:3], threads=0)
+    assert regions(intervals, offsets) == expected[:2]
+    intervals, offsets = mask_bulk([])
+    assert len(intervals) == 0 and list(offsets) == [0]
+    with pytest.raises(ValueError):
+        mask_bulk(packed, offsets=[0, len(packed) + 1])
+    with pytest.raises(errors.SequenceTooShortError):
+        mask_bulk(["ACGT" * 10, "ACG"])
+    with pytest.raises(TypeError):
+        mask_bulk(["ACGT" * 10, 42])
+    for sequence in ["ACGT" * 10, b"ACGT" * 10]:
+        with pytest.raises(TypeError, match="list of sequences"):
+            mask_bulk(sequence)
+
+
+def test_masked_view():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGCT" * 3
+    masker = DustMasker(sequence, score_threshold=10)
+    for hard in (False, True):
+        masked = masker.mask(hard=hard)
+        view = masker.masked_view(hard=hard)
+        assert len(view) == len(masked)
+        assert str(view) == masked
+        assert view[5] == masked[5]
+        assert view[-1] == masked[-1]
+        slices = (slice(1, 30), slice(20, 80, 3), slice(None, None, -2), slice(5, 5))
+        for index in slices:
+            assert view[index] == masked[index]
+        assert "".join(view) == masked
+        assert list(view.chunks(10)) == [
+            masked[i : i + 10] for i in range(0, len(masked), 10)
+        ]
+        file = io.StringIO()
+        assert view.write(file, chunk_size=7) == len(masked)
+        assert file.getvalue() == masked
+    assert repr(masker.masked_view()) == "MaskedSequence(length: 96, hard: False)"
+    with pytest.raises(IndexError):
+        masker.masked_view()[96]
+    with pytest.raises(ValueError):
+        masker.masked_view().chunks(0)
+    masker = DustMasker("ÀCGTATATATATA", score_threshold=10)
+    view = masker.masked_view()
+    assert str(view) == masker.mask()
+    assert "".join(view) == masker.mask()
+    assert view[2:] == masker.mask()[1:]
+    assert len(view) == len(masker.mask().encode()) == 14
+    with pytest.raises(errors.InvalidCharacterError):
+        view[1]
+
+
+def test_mask_normalization():
+    sequence = "acgtRYacgTATATATATATATATAgU"
+    masker = DustMasker(sequence, score_threshold=10)
+    start, end = masker.intervals[0]
+    masked = masker.mask()
+    assert masker.mask(uppercase=True) == (
+        sequence[:start].upper() + masked[start:end] + sequence[end:].upper()
+    )
+    assert masker.mask(ambiguous_to_n=True) == masked.replace("R", "N").replace("Y", "N")
+    assert masker.mask(convert="UT") == masked.replace("U", "T").replace("u", "t")
+    assert masker.mask(convert="TU") == masked.replace("T", "U").replace("t", "u")
+    normalized = masker.mask(uppercase=True, convert="UT", ambiguous_to_n=True)
+    assert normalized == "ACGTNNACGTAtatatatatatatagt"
+    assert masker.mask(hard=True, uppercase=True)[start:end] == "N" * (end - start)
+    view = masker.masked_view(uppercase=True, convert="UT", ambiguous_to_n=True)
+    assert str(view) == normalized
+    assert view[2:12] == normalized[2:12]
+    with pytest.raises(ValueError):
+        masker.mask(convert="AT")
+
+
+def test_coordinate_systems(tmp_path):
+    masker = DustMasker("CGTATATATATAGTATGCGTACTGGGGGGGCT", score_threshold=10)
+    assert convert_intervals(masker) == [(2, 12), (23, 30)]
+    assert convert_intervals(masker, "1-based") == [(3, 12), (24, 30)]
+    assert convert_intervals(masker.regions(), "1-based") == [(3, 12), (24, 30)]
+    with pytest.raises(ValueError):
+        convert_intervals(masker, "bed")
+    file = io.StringIO()
+    assert write_intervals(file, {"seq1": masker, "seq2": [(0, 4)]}) == 3
+    assert file.getvalue() == "seq1\t2\t12\nseq1\t23\t30\nseq2\t0\t4\n"
+    path = tmp_path / "intervals.txt"
+    write_intervals(path, [("seq1", masker)], "1-based", format="interval")
+    assert path.read_text() == ">seq1\n3 - 12\n24 - 30\n"
+    with pytest.raises(ValueError):
+        write_intervals(file, {}, format="bed")
+
+
+def test_write_bed(tmp_path):
+    masker = DustMasker("CGTATATATATAGTATGCGTACTGGGGGGGCT", score_threshold=10)
+    (ta, g) = masker.regions()
+    assert output.repeat_class(ta) == "dinucleotide"
+    assert output.repeat_class(g) == "homopolymer"
+    file = io.StringIO()
+    assert write_bed(file, {"seq1": masker}) == 2
+    assert file.getvalue() == "seq1\t2\t12\nseq1\t23\t30\n"
+    file = io.StringIO()
+    write_bed(file, [("seq1", masker)], columns=6, strand="+", score_scale=10)
+    lines = [line.split("\t") for line in file.getvalue().splitlines()]
+    assert lines[0] == [
+        "seq1", "2", "12", "low_complexity", str(round(ta.score * 10)), "+"
+    ]
+    path = tmp_path / "dust.bed"
+    colors = {"homopolymer": "0,0,0"}
+    write_bed(path, {"seq1": masker}, columns=9, name=None, colors=colors)
+    lines = [line.split("\t") for line in path.read_text().splitlines()]
+    assert lines[0][3] == "dinucleotide"
+    assert lines[0][6:] == ["2", "12", output.REPEAT_CLASS_COLORS["dinucleotide"]]
+    assert lines[1][3] == "homopolymer"
+    assert lines[1][8] == "0,0,0"
+    assert all(len(line) == 9 for line in lines)
+    file = io.StringIO()
+    write_bed(file, {"seq1": [(0, 4)]}, columns=4)
+    assert file.getvalue() == "seq1\t0\t4\tlow_complexity\n"
+    with pytest.raises(ValueError):
+        write_bed(file, {"seq1": [(0, 4)]}, columns=5)
+    with pytest.raises(ValueError):
+        write_bed(file, {}, columns=7)
+    with pytest.raises(ValueError):
+        write_bed(file, {}, strand="x")
+
+
+def test_mask_ambiguous():
+    sequence = "CGTATATATATAGTATGCGTACTGGGGGGGNNNRYACGTACGTTGCAnn"
+    masker = DustMasker(sequence, score_threshold=10, mask_ambiguous=True)
+    assert masker.intervals == [(2, 12), (23, 35), (47, 49)]
+    assert masker.mask(hard=True)[23:] == "N" * 12 + "ACGTACGTTGCANN"
+    assert masker.mask()[23:35] == "gggggggnnnry"
+    edited = masker.edit(40, 41, "N")
+    assert edited.intervals == [(2, 12), (23, 35), (40, 41), (47, 49)]
+    assert masker[30:].intervals == [(0, 5), (17, 19)]
+
+
+def test_ambiguous_intervals():
+    sequence = "NNCGTATATATATAGTATGCGTACTGGGGRYGGGcgtnàNN"
+    masker = DustMasker(sequence)
+    assert masker.ambiguous_intervals == [(0, 2), (29, 31), (37, 38), (40, 42)]
+    masker = DustMasker(sequence, mask_ambiguous=True)
+    assert set(masker.ambiguous_intervals) <= set(masker.intervals)
+    assert DustMasker("ACGTACGT").ambiguous_intervals == []
+    expected = [(0, 2), (29, 31), (37, 38), (40, 42)]
+    for kwargs in [{"composition": True}, {"threads": 2}, {"kmer_size": 4}]:
+        assert DustMasker(sequence, **kwargs).ambiguous_intervals == expected
+    masker = DustMasker(sequence)
+    assert masker[1:30].ambiguous_intervals == [(0, 1), (28, 29)]
+    assert masker.edit(0, 2, "AC").ambiguous_intervals == expected[1:]
+
+
+def test_allow_short():
+    for sequence in ("", "A", "ACG"):
+        with pytest.raises(errors.SequenceTooShortError):
+            DustMasker(sequence)
+        with warnings.catch_warnings():
+            warnings.simplefilter("error")
+            masker = DustMasker(sequence, allow_short=True)
+        assert masker.intervals == []
+        assert masker.mask() == sequence
+    assert DustMasker("NNN", allow_short=True, mask_ambiguous=True).intervals == [(0, 3)]
+    masker = DustMasker("ACGTACGT", allow_short=True)
+    assert masker.edit(0, 8, "AC").intervals == []
+    assert masker.edit(0, 8, "").mask() == ""
+    with pytest.raises(errors.SequenceTooShortError):
+        DustMasker("ACGTACGT").edit(0, 8, "AC")
+    assert "0 (0.00%)" in DustMasker("", allow_short=True)._repr_html_()
+    sequences = ["CGTATATATATAGTATGCGTACTGGGGGGGCT", "AC", "", "GGGGGGGG"]
+    with pytest.raises(errors.SequenceTooShortError):
+        mask_bulk(sequences)
+    intervals, offsets = mask_bulk(sequences, score_threshold=10, allow_short=True)
+    assert list(intervals) == [2, 12, 23, 30, 0, 8]
+    assert list(offsets) == [0, 2, 2, 2, 3]
+    assert mask_for_gene_finding(["ACG"], allow_short=True) == [b"ACG"]